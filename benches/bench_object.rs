@@ -0,0 +1,28 @@
+#![feature(test)]
+
+extern crate test;
+use pyo3::prelude::*;
+use test::Bencher;
+
+#[bench]
+fn clone_and_drop_object(b: &mut Bencher) {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py.None();
+    b.iter(|| {
+        for _ in 0..1000 {
+            let _clone = test::black_box(obj.clone_ref(py));
+        }
+    });
+}
+
+#[bench]
+fn convert_to_object(b: &mut Bencher) {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    b.iter(|| {
+        for i in 0..1000u64 {
+            let _obj = test::black_box(i.to_object(py));
+        }
+    });
+}
@@ -0,0 +1,404 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+//! Code generation for `#[derive(FromPyObject)]`
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+/// Where the value of a named field is looked up on the python object
+#[derive(Debug, Clone, PartialEq)]
+enum FieldGetter {
+    /// `getattr(obj, name)`, the default
+    Attribute(Option<syn::LitStr>),
+    /// `obj[key]`
+    Item(Option<syn::Lit>),
+    /// The field is not extracted but initialized with `Default::default()`
+    Skip,
+}
+
+/// Derives `FromPyObject` for structs and enums
+pub fn build_derive_from_pyobject(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let cls = &ast.ident;
+    let (lifetime, generics) = build_generics(&ast.generics)?;
+
+    let body = match &ast.data {
+        syn::Data::Struct(data) => {
+            let self_path: syn::Path = syn::parse_quote!(Self);
+            impl_extract_fields(cls, &self_path, &cls.to_string(), &data.fields)?
+        }
+        syn::Data::Enum(data) => impl_extract_enum(cls, data)?,
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "#[derive(FromPyObject)] is not supported for unions",
+            ))
+        }
+    };
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics pyo3::FromPyObject<#lifetime> for #cls #ty_generics #where_clause {
+            fn extract(_obj: &#lifetime pyo3::types::PyAny) -> pyo3::PyResult<Self> {
+                let _py = pyo3::PyNativeType::py(_obj);
+                #body
+            }
+        }
+    })
+}
+
+/// Finds the lifetime of the extracted value, adding a `'source` lifetime if the type has none,
+/// and requires `FromPyObject` for every type parameter
+fn build_generics(generics: &syn::Generics) -> syn::Result<(syn::Lifetime, syn::Generics)> {
+    let mut generics = generics.clone();
+    let lifetime = {
+        let mut lifetimes = generics.lifetimes();
+        match (lifetimes.next(), lifetimes.next()) {
+            (None, _) => None,
+            (Some(def), None) => Some(def.lifetime.clone()),
+            (Some(_), Some(def)) => {
+                return Err(syn::Error::new_spanned(
+                    def,
+                    "#[derive(FromPyObject)] supports at most one lifetime parameter",
+                ))
+            }
+        }
+    };
+    let lifetime = match lifetime {
+        Some(lifetime) => lifetime,
+        None => {
+            let lifetime = syn::Lifetime::new("'source", Span::call_site());
+            generics.params.insert(
+                0,
+                syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime.clone())),
+            );
+            lifetime
+        }
+    };
+
+    let type_params: Vec<syn::Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for ident in type_params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ident: pyo3::FromPyObject<#lifetime>));
+    }
+
+    Ok((lifetime, generics))
+}
+
+/// Tries all variants in declaration order and returns the first one that could be extracted
+fn impl_extract_enum(cls: &syn::Ident, data: &syn::DataEnum) -> syn::Result<TokenStream> {
+    if data.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            cls,
+            "#[derive(FromPyObject)] requires an enum with at least one variant",
+        ));
+    }
+
+    let mut variant_names = Vec::new();
+    let mut attempts = Vec::new();
+    for variant in data.variants.iter() {
+        let ident = &variant.ident;
+        let name = format!("{}::{}", cls, ident);
+        let path: syn::Path = syn::parse_quote!(Self::#ident);
+        let body = impl_extract_fields(ident, &path, &name, &variant.fields)?;
+
+        variant_names.push(ident.to_string());
+        attempts.push(quote! {
+            match (|| -> pyo3::PyResult<Self> { #body })() {
+                Ok(_value) => return Ok(_value),
+                Err(_err) => _errors.push(_err),
+            }
+        });
+    }
+
+    let cls_name = cls.to_string();
+
+    Ok(quote! {
+        let mut _errors = Vec::new();
+        #(#attempts)*
+        Err(pyo3::derive_utils::failed_to_extract_enum(
+            _py,
+            #cls_name,
+            &[#(#variant_names),*],
+            _errors,
+        ))
+    })
+}
+
+/// Generates the extraction of the fields of a struct or of a single enum variant
+fn impl_extract_fields(
+    item: &syn::Ident,
+    path: &syn::Path,
+    name: &str,
+    fields: &syn::Fields,
+) -> syn::Result<TokenStream> {
+    match fields {
+        syn::Fields::Named(fields) => {
+            let mut field_values = Vec::new();
+            for field in fields.named.iter() {
+                let ident = field.ident.as_ref().unwrap();
+                let ty = &field.ty;
+                let field_name = ident.to_string();
+                let getter = match parse_field_getter(&field.attrs)? {
+                    FieldGetter::Attribute(Some(attr)) => quote! {
+                        pyo3::ObjectProtocol::getattr(_obj, #attr)
+                    },
+                    FieldGetter::Attribute(None) => quote! {
+                        pyo3::ObjectProtocol::getattr(_obj, #field_name)
+                    },
+                    FieldGetter::Item(Some(key)) => quote! {
+                        pyo3::ObjectProtocol::get_item(_obj, #key)
+                    },
+                    FieldGetter::Item(None) => quote! {
+                        pyo3::ObjectProtocol::get_item(_obj, #field_name)
+                    },
+                    FieldGetter::Skip => {
+                        field_values.push(quote! {
+                            #ident: ::std::default::Default::default()
+                        });
+                        continue;
+                    }
+                };
+                field_values.push(quote! {
+                    #ident: #getter
+                        .and_then(<#ty as pyo3::FromPyObject>::extract)
+                        .map_err(|e| {
+                            pyo3::derive_utils::failed_to_extract_field(_py, e, #name, #field_name)
+                        })?
+                });
+            }
+            Ok(quote! {
+                Ok(#path { #(#field_values),* })
+            })
+        }
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            // Newtypes are transparent: they are extracted like the wrapped type
+            let field = fields.unnamed.first().unwrap();
+            check_no_field_getter(&field.attrs)?;
+            let ty = &field.ty;
+            Ok(quote! {
+                Ok(#path(
+                    <#ty as pyo3::FromPyObject>::extract(_obj).map_err(|e| {
+                        pyo3::derive_utils::failed_to_extract_field(_py, e, #name, "0")
+                    })?
+                ))
+            })
+        }
+        syn::Fields::Unnamed(fields) => {
+            let len = fields.unnamed.len();
+            let mut field_values = Vec::new();
+            for (index, field) in fields.unnamed.iter().enumerate() {
+                check_no_field_getter(&field.attrs)?;
+                let ty = &field.ty;
+                let field_name = index.to_string();
+                let index = index as isize;
+                field_values.push(quote! {
+                    _seq.get_item(#index)
+                        .and_then(<#ty as pyo3::FromPyObject>::extract)
+                        .map_err(|e| {
+                            pyo3::derive_utils::failed_to_extract_field(_py, e, #name, #field_name)
+                        })?
+                });
+            }
+            Ok(quote! {
+                let _seq = pyo3::derive_utils::extract_sequence_of_len(_obj, #name, #len)?;
+                Ok(#path(#(#field_values),*))
+            })
+        }
+        syn::Fields::Unit => Err(syn::Error::new_spanned(
+            item,
+            format!(
+                "#[derive(FromPyObject)] can not extract `{}` because it has no fields",
+                name
+            ),
+        )),
+    }
+}
+
+/// Tuple fields are always extracted by position, so they don't accept `#[pyo3(...)]`
+fn check_no_field_getter(attrs: &[syn::Attribute]) -> syn::Result<()> {
+    for attr in attrs.iter() {
+        if attr.path.is_ident("pyo3") {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[pyo3(attribute)] and #[pyo3(item)] are only supported on named fields",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parses `#[pyo3(attribute)]`, `#[pyo3(attribute("name"))]`, `#[pyo3(item)]`
+/// and `#[pyo3(item("key"))]`.
+///
+/// `#[pyo3(rename = "name")]` and `#[pyo3(skip)]` are shared with `#[derive(IntoPyObject)]`:
+/// the new name is used as attribute name or item key unless one is given explicitly.
+fn parse_field_getter(attrs: &[syn::Attribute]) -> syn::Result<FieldGetter> {
+    let mut getter = None;
+    let mut rename = None;
+    let mut skip = false;
+
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("pyo3") {
+            continue;
+        }
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected #[pyo3(attribute)] or #[pyo3(item)]",
+                ))
+            }
+        };
+        for nested in list.nested.iter() {
+            let parsed = match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("skip") => {
+                    skip = true;
+                    continue;
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(ref nv))
+                    if nv.path.is_ident("rename") =>
+                {
+                    match nv.lit {
+                        syn::Lit::Str(ref name) => rename = Some(name.clone()),
+                        ref other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "the new name must be a string literal",
+                            ))
+                        }
+                    }
+                    continue;
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("attribute") => {
+                    FieldGetter::Attribute(None)
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("item") => {
+                    FieldGetter::Item(None)
+                }
+                syn::NestedMeta::Meta(syn::Meta::List(ref list))
+                    if list.path.is_ident("attribute") && list.nested.len() == 1 =>
+                {
+                    match list.nested.first().unwrap() {
+                        syn::NestedMeta::Lit(syn::Lit::Str(ref name)) => {
+                            FieldGetter::Attribute(Some(name.clone()))
+                        }
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "the attribute name must be a string literal",
+                            ))
+                        }
+                    }
+                }
+                syn::NestedMeta::Meta(syn::Meta::List(ref list))
+                    if list.path.is_ident("item") && list.nested.len() == 1 =>
+                {
+                    match list.nested.first().unwrap() {
+                        syn::NestedMeta::Lit(ref key) => FieldGetter::Item(Some(key.clone())),
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "the item key must be a literal",
+                            ))
+                        }
+                    }
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "expected `attribute`, `attribute(\"name\")`, `item`, `item(key)`, \
+                         `rename = \"name\"` or `skip`",
+                    ))
+                }
+            };
+            if getter.is_some() {
+                return Err(syn::Error::new_spanned(
+                    nested,
+                    "only one of `attribute` and `item` can be used per field",
+                ));
+            }
+            getter = Some(parsed);
+        }
+    }
+
+    if skip {
+        return Ok(FieldGetter::Skip);
+    }
+    Ok(
+        match (getter.unwrap_or(FieldGetter::Attribute(None)), rename) {
+            (FieldGetter::Attribute(None), Some(name)) => FieldGetter::Attribute(Some(name)),
+            (FieldGetter::Item(None), Some(name)) => FieldGetter::Item(Some(syn::Lit::Str(name))),
+            (getter, _) => getter,
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_field_getter, FieldGetter};
+    use syn::parse_quote;
+
+    fn getter(field: syn::Field) -> syn::Result<FieldGetter> {
+        parse_field_getter(&field.attrs)
+    }
+
+    #[test]
+    fn test_default_is_attribute() {
+        let field: syn::FieldsNamed = parse_quote!({ x: i32 });
+        let field = field.named.into_iter().next().unwrap();
+        assert_eq!(getter(field).unwrap(), FieldGetter::Attribute(None));
+    }
+
+    #[test]
+    fn test_getters() {
+        let fields: syn::FieldsNamed = parse_quote!({
+            #[pyo3(attribute("y"))]
+            a: i32,
+            #[pyo3(item)]
+            b: i32,
+            #[pyo3(item(0))]
+            c: i32,
+            #[pyo3(item, rename = "e")]
+            d: i32,
+            #[pyo3(rename = "g")]
+            f: i32,
+            #[pyo3(skip)]
+            h: i32,
+        });
+        let getters: Vec<_> = fields
+            .named
+            .into_iter()
+            .map(|f| getter(f).unwrap())
+            .collect();
+        assert_eq!(
+            getters,
+            vec![
+                FieldGetter::Attribute(Some(parse_quote!("y"))),
+                FieldGetter::Item(None),
+                FieldGetter::Item(Some(parse_quote!(0))),
+                FieldGetter::Item(Some(parse_quote!("e"))),
+                FieldGetter::Attribute(Some(parse_quote!("g"))),
+                FieldGetter::Skip,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errs() {
+        let fields: syn::FieldsNamed = parse_quote!({
+            #[pyo3(attribute, item)]
+            a: i32,
+            #[pyo3(get)]
+            b: i32,
+            #[pyo3(attribute(x))]
+            c: i32,
+        });
+        for field in fields.named {
+            assert!(getter(field).is_err());
+        }
+    }
+}
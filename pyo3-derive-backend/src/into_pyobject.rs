@@ -0,0 +1,420 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+//! Code generation for `#[derive(IntoPyObject)]`
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// How a struct (or the payload of an enum variant) is represented in python
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Repr {
+    /// A `dict` mapping the field names to the values, the default for named fields
+    Dict,
+    /// A `tuple` of the values in declaration order, the default for tuple structs
+    Tuple,
+    /// The value of the only field, the default for tuple structs with a single field
+    Transparent,
+    /// `None`, used for unit structs
+    Unit,
+    /// A new instance of the `#[pyclass]`, created from a clone of the value
+    PyClass,
+}
+
+/// The `#[pyo3(...)]` options of a single field or variant
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ItemAttrs {
+    rename: Option<syn::LitStr>,
+    skip: bool,
+}
+
+/// Whether we generate `ToPyObject` (converting from a reference) or `IntoPy<PyObject>`
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    ToObject,
+    IntoPy,
+}
+
+/// Derives `ToPyObject` and `IntoPy<PyObject>` for structs and enums
+pub fn build_derive_into_pyobject(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let cls = &ast.ident;
+    let container_repr = parse_container_repr(&ast.attrs)?;
+
+    let (to_object, into_py) = match &ast.data {
+        syn::Data::Struct(data) => {
+            let repr = match container_repr {
+                Some(repr) => repr,
+                None => default_repr(&data.fields),
+            };
+            if repr == Repr::PyClass {
+                return Ok(impl_pyclass(ast));
+            }
+            let self_path: syn::Path = syn::parse_quote!(Self);
+            let to_object = impl_struct_arm(&self_path, &data.fields, repr, Mode::ToObject)?;
+            let into_py = impl_struct_arm(&self_path, &data.fields, repr, Mode::IntoPy)?;
+            (
+                quote! { match self { #to_object } },
+                quote! { match self { #into_py } },
+            )
+        }
+        syn::Data::Enum(data) => {
+            if container_repr.is_some() {
+                return Err(syn::Error::new_spanned(
+                    cls,
+                    "the representation of an enum can not be chosen, \
+                     use #[pyo3(...)] on the fields of the variants instead",
+                ));
+            }
+            (
+                impl_enum(data, Mode::ToObject)?,
+                impl_enum(data, Mode::IntoPy)?,
+            )
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                cls,
+                "#[derive(IntoPyObject)] is not supported for unions",
+            ))
+        }
+    };
+
+    let to_object_generics = add_trait_bounds(&ast.generics, quote!(pyo3::ToPyObject));
+    let into_py_generics = add_trait_bounds(&ast.generics, quote!(pyo3::IntoPy<pyo3::PyObject>));
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+    let (to_object_impl, _, to_object_where) = to_object_generics.split_for_impl();
+    let (into_py_impl, _, into_py_where) = into_py_generics.split_for_impl();
+
+    Ok(quote! {
+        impl #to_object_impl pyo3::ToPyObject for #cls #ty_generics #to_object_where {
+            fn to_object(&self, py: pyo3::Python) -> pyo3::PyObject {
+                #to_object
+            }
+        }
+
+        impl #into_py_impl pyo3::IntoPy<pyo3::PyObject> for #cls #ty_generics #into_py_where {
+            fn into_py(self, py: pyo3::Python) -> pyo3::PyObject {
+                #into_py
+            }
+        }
+    })
+}
+
+/// `#[pyclass]` already implements `IntoPy<PyObject>`, so we only add `ToPyObject`
+///
+/// `ToPyObject` can't return an error, so like the `IntoPy<PyObject>` of the class, it panics
+/// if the instance can't be created, e.g. when the type object fails to initialize.
+fn impl_pyclass(ast: &syn::DeriveInput) -> TokenStream {
+    let cls = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    quote! {
+        impl #impl_generics pyo3::ToPyObject for #cls #ty_generics #where_clause {
+            fn to_object(&self, py: pyo3::Python) -> pyo3::PyObject {
+                pyo3::IntoPy::into_py(
+                    pyo3::Py::new(py, ::std::clone::Clone::clone(self))
+                        .expect("Failed to create an instance of the pyclass"),
+                    py,
+                )
+            }
+        }
+    }
+}
+
+fn default_repr(fields: &syn::Fields) -> Repr {
+    match fields {
+        syn::Fields::Named(_) => Repr::Dict,
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Repr::Transparent,
+        syn::Fields::Unnamed(_) => Repr::Tuple,
+        syn::Fields::Unit => Repr::Unit,
+    }
+}
+
+fn add_trait_bounds(generics: &syn::Generics, bound: TokenStream) -> syn::Generics {
+    let mut generics = generics.clone();
+    let type_params: Vec<syn::Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for ident in type_params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ident: #bound));
+    }
+    generics
+}
+
+/// Unit variants become their name as `str`, all other variants become a `dict` with
+/// the variant name as the only key and the fields as value.
+fn impl_enum(data: &syn::DataEnum, mode: Mode) -> syn::Result<TokenStream> {
+    let mut arms = Vec::new();
+    for variant in data.variants.iter() {
+        let attrs = parse_item_attrs(&variant.attrs)?;
+        if attrs.skip {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "#[pyo3(skip)] is not supported on enum variants",
+            ));
+        }
+        let ident = &variant.ident;
+        let tag = match attrs.rename {
+            Some(rename) => rename.value(),
+            None => ident.to_string(),
+        };
+        let path: syn::Path = syn::parse_quote!(Self::#ident);
+        let repr = default_repr(&variant.fields);
+        let arm = if repr == Repr::Unit {
+            quote! {
+                #path => pyo3::ToPyObject::to_object(#tag, py),
+            }
+        } else {
+            let (pattern, value) = impl_fields(&path, &variant.fields, repr, mode)?;
+            quote! {
+                #pattern => {
+                    let _value: pyo3::PyObject = #value;
+                    pyo3::types::IntoPyDict::into_py_dict(vec![(#tag, _value)], py).into()
+                }
+            }
+        };
+        arms.push(arm);
+    }
+    Ok(quote! {
+        match self {
+            #(#arms)*
+        }
+    })
+}
+
+fn impl_struct_arm(
+    path: &syn::Path,
+    fields: &syn::Fields,
+    repr: Repr,
+    mode: Mode,
+) -> syn::Result<TokenStream> {
+    let (pattern, value) = impl_fields(path, fields, repr, mode)?;
+    Ok(quote! {
+        #pattern => #value,
+    })
+}
+
+/// Returns a pattern destructuring the fields and an expression converting them to a `PyObject`
+fn impl_fields(
+    path: &syn::Path,
+    fields: &syn::Fields,
+    repr: Repr,
+    mode: Mode,
+) -> syn::Result<(TokenStream, TokenStream)> {
+    let mut bindings = Vec::new();
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    let mut kept_fields = Vec::new();
+
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = parse_item_attrs(&field.attrs)?;
+        let binding = match field.ident {
+            Some(ref ident) => format_ident!("_field_{}", ident),
+            None => format_ident!("_field_{}", index),
+        };
+        if attrs.skip {
+            bindings.push(quote!(_));
+            continue;
+        }
+        let name = match (attrs.rename, &field.ident) {
+            (Some(rename), Some(_)) => rename.value(),
+            (None, Some(ident)) => ident.to_string(),
+            (Some(rename), None) => {
+                return Err(syn::Error::new_spanned(
+                    rename,
+                    "#[pyo3(rename)] is only supported on named fields",
+                ))
+            }
+            (None, None) => index.to_string(),
+        };
+        values.push(match mode {
+            Mode::ToObject => quote!(pyo3::ToPyObject::to_object(#binding, py)),
+            Mode::IntoPy => quote!(pyo3::IntoPy::<pyo3::PyObject>::into_py(#binding, py)),
+        });
+        bindings.push(quote!(#binding));
+        names.push(name);
+        kept_fields.push(field);
+    }
+
+    let pattern = match fields {
+        syn::Fields::Named(fields) => {
+            let idents = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
+            quote!(#path { #(#idents: #bindings),* })
+        }
+        syn::Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        syn::Fields::Unit => quote!(#path),
+    };
+
+    let value = match repr {
+        Repr::Dict => quote! {
+            pyo3::types::IntoPyDict::into_py_dict(
+                vec![#((#names, #values)),*] as Vec<(&str, pyo3::PyObject)>,
+                py,
+            ).into()
+        },
+        Repr::Tuple => quote! {
+            pyo3::types::PyTuple::new(py, vec![#(#values),*] as Vec<pyo3::PyObject>).into()
+        },
+        Repr::Transparent => {
+            if values.len() != 1 {
+                let message = "#[pyo3(transparent)] requires exactly one field that is not skipped";
+                return Err(match kept_fields.get(1) {
+                    // The first field that is too many
+                    Some(field) => syn::Error::new_spanned(field, message),
+                    None if *fields == syn::Fields::Unit => syn::Error::new_spanned(path, message),
+                    None => syn::Error::new_spanned(fields, message),
+                });
+            }
+            let value = &values[0];
+            quote!(#value)
+        }
+        Repr::Unit => quote!(py.None()),
+        Repr::PyClass => unreachable!(),
+    };
+
+    Ok((pattern, value))
+}
+
+/// Parses `#[pyo3(dict)]`, `#[pyo3(tuple)]`, `#[pyo3(transparent)]` and `#[pyo3(pyclass)]`
+fn parse_container_repr(attrs: &[syn::Attribute]) -> syn::Result<Option<Repr>> {
+    let mut repr = None;
+    for meta in pyo3_metas(attrs)? {
+        let parsed = match meta {
+            syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("dict") => Repr::Dict,
+            syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("tuple") => {
+                Repr::Tuple
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("transparent") => {
+                Repr::Transparent
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("pyclass") => {
+                Repr::PyClass
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "expected `dict`, `tuple`, `transparent` or `pyclass`",
+                ))
+            }
+        };
+        if repr.is_some() {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "only one representation can be chosen",
+            ));
+        }
+        repr = Some(parsed);
+    }
+    Ok(repr)
+}
+
+/// Parses `#[pyo3(rename = "name")]` and `#[pyo3(skip)]`, ignoring the options that only
+/// apply to `#[derive(FromPyObject)]`
+fn parse_item_attrs(attrs: &[syn::Attribute]) -> syn::Result<ItemAttrs> {
+    let mut item_attrs = ItemAttrs::default();
+    for meta in pyo3_metas(attrs)? {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::Path(ref path)) if path.is_ident("skip") => {
+                item_attrs.skip = true;
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(ref nv)) if nv.path.is_ident("rename") => {
+                match nv.lit {
+                    syn::Lit::Str(ref name) => item_attrs.rename = Some(name.clone()),
+                    ref other => {
+                        return Err(syn::Error::new_spanned(
+                            other,
+                            "the new name must be a string literal",
+                        ))
+                    }
+                }
+            }
+            syn::NestedMeta::Meta(ref meta)
+                if meta.path().is_ident("attribute") || meta.path().is_ident("item") => {}
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "expected `rename = \"name\"` or `skip`",
+                ))
+            }
+        }
+    }
+    Ok(item_attrs)
+}
+
+/// Collects the items of all `#[pyo3(...)]` attributes
+fn pyo3_metas(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::NestedMeta>> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("pyo3") {
+            continue;
+        }
+        match attr.parse_meta()? {
+            syn::Meta::List(list) => metas.extend(list.nested),
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected #[pyo3(...)] with a list of options",
+                ))
+            }
+        }
+    }
+    Ok(metas)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_container_repr, parse_item_attrs, ItemAttrs, Repr};
+    use syn::parse_quote;
+
+    #[test]
+    fn test_container_repr() {
+        let ast: syn::DeriveInput = parse_quote! {
+            #[pyo3(tuple)]
+            struct Point { x: i32, y: i32 }
+        };
+        assert_eq!(parse_container_repr(&ast.attrs).unwrap(), Some(Repr::Tuple));
+
+        let ast: syn::DeriveInput = parse_quote! {
+            #[pyo3(tuple, dict)]
+            struct Point { x: i32, y: i32 }
+        };
+        assert!(parse_container_repr(&ast.attrs).is_err());
+    }
+
+    #[test]
+    fn test_item_attrs() {
+        let fields: syn::FieldsNamed = parse_quote!({
+            #[pyo3(rename = "b")]
+            a: i32,
+            #[pyo3(skip)]
+            c: i32,
+            #[pyo3(item, rename = "e")]
+            d: i32,
+        });
+        let attrs: Vec<_> = fields
+            .named
+            .iter()
+            .map(|f| parse_item_attrs(&f.attrs).unwrap())
+            .collect();
+        assert_eq!(
+            attrs,
+            vec![
+                ItemAttrs {
+                    rename: Some(parse_quote!("b")),
+                    skip: false
+                },
+                ItemAttrs {
+                    rename: None,
+                    skip: true
+                },
+                ItemAttrs {
+                    rename: Some(parse_quote!("e")),
+                    skip: false
+                },
+            ]
+        );
+
+        let fields: syn::FieldsNamed = parse_quote!({
+            #[pyo3(rename = b)]
+            a: i32,
+        });
+        assert!(parse_item_attrs(&fields.named[0].attrs).is_err());
+    }
+}
@@ -4,7 +4,9 @@
 #![recursion_limit = "1024"]
 
 mod defs;
+mod from_pyobject;
 mod func;
+mod into_pyobject;
 mod method;
 mod module;
 mod pyclass;
@@ -12,11 +14,17 @@ mod pyfunction;
 mod pyimpl;
 mod pymethod;
 mod pyproto;
+mod slots;
+mod stubs;
 mod utils;
 
-pub use module::{add_fn_to_module, process_functions_in_module, py_init};
-pub use pyclass::{build_py_class, PyClassArgs};
+pub use from_pyobject::build_derive_from_pyobject;
+pub use into_pyobject::build_derive_into_pyobject;
+pub use module::{
+    add_fn_to_module, process_functions_in_module, py_init, py_init_multi_phase, PyModuleArgs,
+};
+pub use pyclass::{build_py_class, build_py_enum, PyClassArgs};
 pub use pyfunction::PyFunctionAttr;
-pub use pyimpl::{build_py_methods, impl_methods};
+pub use pyimpl::{build_py_methods, impl_methods, PyMethodsArgs};
 pub use pyproto::build_py_proto;
 pub use utils::get_doc;
@@ -2,6 +2,7 @@
 
 use crate::pyfunction::Argument;
 use crate::pyfunction::PyFunctionAttr;
+use crate::utils::TextSignatureAttr;
 use proc_macro2::TokenStream;
 use quote::quote;
 use quote::ToTokens;
@@ -28,12 +29,15 @@ pub enum FnType {
     FnCall,
     FnClass,
     FnStatic,
+    ClassAttribute,
     PySelf(syn::TypePath),
 }
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct FnSpec<'a> {
     pub tp: FnType,
+    /// Whether the method takes `&mut self`, so that it needs the mutable borrow of the instance
+    pub mutable_self: bool,
     pub attrs: Vec<Argument>,
     pub args: Vec<FnArg<'a>>,
     pub output: syn::Type,
@@ -56,11 +60,13 @@ impl<'a> FnSpec<'a> {
         let (mut fn_type, fn_attrs) = parse_attributes(meth_attrs)?;
 
         let mut has_self = false;
+        let mut mutable_self = false;
         let mut arguments = Vec::new();
         for input in sig.inputs.iter() {
             match input {
-                syn::FnArg::Receiver(_) => {
+                syn::FnArg::Receiver(receiver) => {
                     has_self = true;
+                    mutable_self = receiver.mutability.is_some();
                 }
                 syn::FnArg::Typed(syn::PatType {
                     ref pat, ref ty, ..
@@ -115,6 +121,7 @@ impl<'a> FnSpec<'a> {
 
         Ok(FnSpec {
             tp: fn_type,
+            mutable_self,
             attrs: fn_attrs,
             args: arguments,
             output: ty,
@@ -182,6 +189,75 @@ impl<'a> FnSpec<'a> {
         None
     }
 
+    /// Builds the signature of the function as CPython expects it for `__text_signature__`,
+    /// e.g. `name($self, a, b=0, *args, c=None, **kwargs)`, using the `#[args]` of the function
+    pub fn text_signature(
+        &self,
+        name: &str,
+        attr: Option<&TextSignatureAttr>,
+    ) -> syn::Result<Option<String>> {
+        let first = match self.tp {
+            FnType::Fn | FnType::PySelf(_) => Some("$self"),
+            FnType::FnClass => Some("$cls"),
+            FnType::FnStatic => None,
+            _ => {
+                return match attr {
+                    Some(TextSignatureAttr::Override(ref lit)) => Err(syn::Error::new_spanned(
+                        lit,
+                        "#[text_signature] is not supported for this kind of method",
+                    )),
+                    _ => Ok(None),
+                };
+            }
+        };
+
+        match attr {
+            Some(TextSignatureAttr::Disabled) => return Ok(None),
+            Some(TextSignatureAttr::Override(ref lit)) => {
+                let mut signature = lit.value();
+                if let Some(first) = first {
+                    signature = if signature == "()" {
+                        format!("({})", first)
+                    } else {
+                        format!("({}, {}", first, &signature[1..])
+                    };
+                }
+                return Ok(Some(format!("{}{}", name, signature)));
+            }
+            None => (),
+        }
+
+        let mut params: Vec<String> = first.into_iter().map(String::from).collect();
+        let mut keyword_only = false;
+        let mut kwargs = None;
+        for arg in self.args.iter() {
+            if arg.py {
+                continue;
+            }
+            if self.is_args(arg.name) {
+                keyword_only = true;
+                params.push(format!("*{}", arg.name));
+                continue;
+            }
+            if self.is_kwargs(arg.name) {
+                kwargs = Some(format!("**{}", arg.name));
+                continue;
+            }
+            if self.is_kw_only(arg.name) && !keyword_only {
+                keyword_only = true;
+                params.push("*".to_string());
+            }
+            params.push(match self.default_value(arg.name) {
+                Some(default) => format!("{}={}", arg.name, python_default(&default)),
+                None if arg.optional.is_some() => format!("{}=None", arg.name),
+                None => arg.name.to_string(),
+            });
+        }
+        params.extend(kwargs);
+
+        Ok(Some(format!("{}({})", name, params.join(", "))))
+    }
+
     pub fn is_kw_only(&self, name: &syn::Ident) -> bool {
         for s in self.attrs.iter() {
             if let Argument::Kwarg(ref path, _) = s {
@@ -194,6 +270,31 @@ impl<'a> FnSpec<'a> {
     }
 }
 
+/// Literals that are written the same way in rust and python are shown as they are,
+/// everything else is shown as `...`
+fn python_default(default: &TokenStream) -> String {
+    match syn::parse2::<syn::Expr>(default.clone()) {
+        Ok(syn::Expr::Lit(syn::ExprLit { ref lit, .. })) => match lit {
+            syn::Lit::Int(ref int) if int.suffix().is_empty() => int.base10_digits().to_string(),
+            syn::Lit::Float(ref float) if float.suffix().is_empty() => {
+                float.base10_digits().to_string()
+            }
+            syn::Lit::Bool(ref b) if b.value => "True".to_string(),
+            syn::Lit::Bool(_) => "False".to_string(),
+            _ => "...".to_string(),
+        },
+        Ok(syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            ref expr,
+            ..
+        })) => match python_default(&expr.to_token_stream()).as_str() {
+            "..." => "...".to_string(),
+            value => format!("-{}", value),
+        },
+        _ => "...".to_string(),
+    }
+}
+
 pub fn is_ref(name: &syn::Ident, ty: &syn::Type) -> bool {
     match ty {
         syn::Type::Reference(_) => return true,
@@ -298,6 +399,8 @@ fn parse_attributes(attrs: &mut Vec<syn::Attribute>) -> syn::Result<(FnType, Vec
                     res = Some(FnType::FnClass)
                 } else if name.is_ident("staticmethod") {
                     res = Some(FnType::FnStatic)
+                } else if name.is_ident("classattr") {
+                    res = Some(FnType::ClassAttribute)
                 } else if name.is_ident("setter") || name.is_ident("getter") {
                     if let syn::AttrStyle::Inner(_) = attr.style {
                         panic!("Inner style attribute is not supported for setter and getter");
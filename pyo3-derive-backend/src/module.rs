@@ -6,10 +6,37 @@ use crate::pyfunction;
 use crate::pyfunction::PyFunctionAttr;
 use crate::pymethod;
 use crate::pymethod::get_arg_names;
+use crate::stubs;
 use crate::utils;
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
-use syn::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, Token};
+
+/// The parsed arguments of the pymodule macro: an optional module name and `multi_phase`
+#[derive(Default)]
+pub struct PyModuleArgs {
+    pub name: Option<Ident>,
+    /// Whether the module uses multi-phase initialization (PEP 489)
+    pub multi_phase: bool,
+}
+
+impl Parse for PyModuleArgs {
+    fn parse(input: ParseStream) -> syn::parse::Result<Self> {
+        let mut slf = PyModuleArgs::default();
+        for ident in Punctuated::<Ident, Token![,]>::parse_terminated(input)? {
+            if ident == "multi_phase" {
+                slf.multi_phase = true;
+            } else if slf.name.is_none() {
+                slf.name = Some(ident);
+            } else {
+                return Err(syn::Error::new_spanned(ident, "Unsupported parameter"));
+            }
+        }
+        Ok(slf)
+    }
+}
 
 /// Generates the function that is called by the python interpreter to initialize the native
 /// module
@@ -27,6 +54,30 @@ pub fn py_init(fnname: &Ident, name: &Ident, doc: syn::Lit) -> TokenStream {
     }
 }
 
+/// Generates the function that is called by the python interpreter to initialize a module with
+/// multi-phase initialization (PEP 489). It returns the module definition, and the module
+/// itself is created by the interpreter and initialized in the `Py_mod_exec` slot.
+pub fn py_init_multi_phase(fnname: &Ident, name: &Ident, doc: syn::Lit) -> TokenStream {
+    let cb_name = Ident::new(&format!("PyInit_{}", name), Span::call_site());
+
+    quote! {
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        /// This autogenerated function is called by the python interpreter when importing
+        /// the module.
+        pub unsafe extern "C" fn #cb_name() -> *mut pyo3::ffi::PyObject {
+            unsafe extern "C" fn __pyo3_exec(
+                module: *mut pyo3::ffi::PyObject,
+            ) -> pyo3::libc::c_int {
+                pyo3::derive_utils::exec_module(module, #doc, #fnname)
+            }
+
+            static MODULE_DEF: pyo3::derive_utils::ModuleDef = pyo3::derive_utils::ModuleDef::new();
+            MODULE_DEF.init(concat!(stringify!(#name), "\0"), __pyo3_exec)
+        }
+    }
+}
+
 /// Finds and takes care of the #[pyfn(...)] in `#[pymodule]`
 pub fn process_functions_in_module(func: &mut syn::ItemFn) {
     let mut stmts: Vec<syn::Stmt> = Vec::new();
@@ -133,10 +184,15 @@ fn function_wrapper_ident(name: &Ident) -> Ident {
 /// Generates python wrapper over a function that allows adding it to a python module as a python
 /// function
 pub fn add_fn_to_module(
-    func: &syn::ItemFn,
+    func: &mut syn::ItemFn,
     python_name: &Ident,
     pyfn_attrs: Vec<pyfunction::Argument>,
 ) -> TokenStream {
+    let text_signature = match utils::take_text_signature_attr(&mut func.attrs) {
+        Ok(text_signature) => text_signature,
+        Err(err) => return err.to_compile_error(),
+    };
+
     let mut arguments = Vec::new();
 
     for input in func.sig.inputs.iter() {
@@ -149,6 +205,7 @@ pub fn add_fn_to_module(
 
     let spec = method::FnSpec {
         tp: method::FnType::Fn,
+        mutable_self: false,
         attrs: pyfn_attrs,
         args: arguments,
         output: ty,
@@ -158,11 +215,29 @@ pub fn add_fn_to_module(
 
     let wrapper = function_c_wrapper(&func.sig.ident, &spec);
     let doc = utils::get_doc(&func.attrs, true);
+    // Module level functions don't get `$self` in their signature
+    let static_spec = method::FnSpec {
+        tp: method::FnType::FnStatic,
+        ..spec.clone()
+    };
+    let doc = match static_spec.text_signature(&python_name.to_string(), text_signature.as_ref()) {
+        Ok(signature) => utils::add_text_signature(doc, signature),
+        Err(err) => return err.to_compile_error(),
+    };
+    let stub = stubs::function_stub(&python_name.to_string(), &spec, None);
 
     let tokens = quote! {
         fn #function_wrapper_ident(py: pyo3::Python) -> pyo3::PyObject {
             #wrapper
 
+            pyo3::inventory::submit! {
+                #![crate = pyo3]
+                pyo3::stubs::TypeStub::Function {
+                    wrapper: __wrap,
+                    function: #stub,
+                }
+            }
+
             let _def = pyo3::class::PyMethodDef {
                 ml_name: stringify!(#python_name),
                 ml_meth: pyo3::class::PyMethodType::PyCFunctionWithKeywords(__wrap),
@@ -206,13 +281,15 @@ fn function_c_wrapper(name: &Ident, spec: &method::FnSpec<'_>) -> TokenStream {
 
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
-            let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
+            pyo3::callback::handle_panic(_py, || {
+                let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
+                let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
 
-            #body
+                #body
 
-            pyo3::callback::cb_convert(
-                pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                pyo3::callback::cb_convert(
+                    pyo3::callback::PyObjectCallbackConverter, _py, _result)
+            })
         }
     }
 }
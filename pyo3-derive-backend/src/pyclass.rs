@@ -1,7 +1,12 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
 use crate::method::{FnArg, FnSpec, FnType};
-use crate::pymethod::{impl_py_getter_def, impl_py_setter_def, impl_wrap_getter, impl_wrap_setter};
+use crate::pymethod::{
+    impl_py_class_attribute_def, impl_py_getter_def, impl_py_setter_def, impl_wrap_getter,
+    impl_wrap_setter, impl_wrap_static,
+};
+use crate::pyproto::build_py_proto;
+use crate::stubs;
 use crate::utils;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
@@ -16,15 +21,49 @@ pub struct PyClassArgs {
     pub flags: Vec<syn::Expr>,
     pub base: syn::TypePath,
     pub module: Option<syn::LitStr>,
+    /// The python names and concrete types of a generic class, e.g. `MatrixF32 = Matrix<f32>`
+    pub instantiate: Vec<(syn::Ident, syn::Type)>,
+}
+
+/// A single `Name = Type<Args>` entry of `instantiate(...)`
+struct Instantiation {
+    name: syn::Ident,
+    ty: syn::Type,
+}
+
+impl Parse for Instantiation {
+    fn parse(input: ParseStream) -> syn::parse::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let ty = input.parse()?;
+        Ok(Instantiation { name, ty })
+    }
 }
 
 impl Parse for PyClassArgs {
     fn parse(input: ParseStream) -> syn::parse::Result<Self> {
         let mut slf = PyClassArgs::default();
 
-        let vars = Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
-        for expr in vars {
-            slf.add_expr(&expr)?;
+        while !input.is_empty() {
+            // `instantiate(...)` contains types, which can't be parsed as expressions
+            if input.peek(syn::Ident) && input.peek2(syn::token::Paren) {
+                let ident: syn::Ident = input.parse()?;
+                if ident != "instantiate" {
+                    return Err(syn::Error::new_spanned(ident, "Unsupported parameter"));
+                }
+                let content;
+                syn::parenthesized!(content in input);
+                let instantiations =
+                    Punctuated::<Instantiation, Token![,]>::parse_terminated(&content)?;
+                slf.instantiate
+                    .extend(instantiations.into_iter().map(|inst| (inst.name, inst.ty)));
+            } else {
+                let expr: Expr = input.parse()?;
+                slf.add_expr(&expr)?;
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
         }
         Ok(slf)
     }
@@ -40,6 +79,7 @@ impl Default for PyClassArgs {
             // are no other flags
             flags: vec![parse_quote! {0}],
             base: parse_quote! {pyo3::types::PyAny},
+            instantiate: Vec::new(),
         }
     }
 }
@@ -132,12 +172,6 @@ impl PyClassArgs {
                 parse_quote! {pyo3::type_object::PY_TYPE_FLAG_WEAKREF}
             }
             "subclass" => {
-                if cfg!(not(feature = "unsound-subclass")) {
-                    return Err(syn::Error::new_spanned(
-                        exp.path.clone(),
-                        "You need to activate the `unsound-subclass` feature if you want to use subclassing",
-                    ));
-                }
                 parse_quote! {pyo3::type_object::PY_TYPE_FLAG_BASETYPE}
             }
             "dict" => {
@@ -160,7 +194,11 @@ pub fn build_py_class(class: &mut syn::ItemStruct, attr: &PyClassArgs) -> syn::R
     let doc = utils::get_doc(&class.attrs, true);
     let mut descriptors = Vec::new();
 
-    check_generics(class)?;
+    if !attr.instantiate.is_empty() {
+        check_instantiate(class, attr)?;
+    } else {
+        check_generics(&class.generics)?;
+    }
     if let syn::Fields::Named(ref mut fields) = class.fields {
         for field in fields.named.iter_mut() {
             let field_descs = parse_descriptors(field)?;
@@ -175,7 +213,504 @@ pub fn build_py_class(class: &mut syn::ItemStruct, attr: &PyClassArgs) -> syn::R
         ));
     }
 
-    Ok(impl_class(&class.ident, &attr, doc, descriptors))
+    if attr.instantiate.is_empty() {
+        let cls = &class.ident;
+        let ty: syn::Type = parse_quote!(#cls);
+        let cls_name = get_class_python_name(cls, attr);
+        let class_stub = impl_class_stub(&ty, &cls_name, &descriptor_stubs(&ty, &descriptors));
+        let class = impl_class(&ty, cls, cls_name, &attr, doc, descriptors);
+        return Ok(quote! {
+            #class
+            #class_stub
+        });
+    }
+
+    // A generic class is a separate python class for every instantiation
+    let type_params: Vec<syn::Ident> = class
+        .generics
+        .type_params()
+        .map(|p| p.ident.clone())
+        .collect();
+    let (_, ty_generics, _) = class.generics.split_for_impl();
+    let cls = &class.ident;
+    let generic_ty: syn::Type = parse_quote!(#cls #ty_generics);
+
+    let mut tokens = TokenStream::new();
+    for (name, ty) in attr.instantiate.iter() {
+        let params = utils::match_type_params(&generic_ty, ty, &type_params)?;
+        let descriptors: Vec<_> = descriptors
+            .iter()
+            .map(|(field, descs)| {
+                let mut field = field.clone();
+                field.ty = utils::substitute_type_params(&field.ty, &params);
+                (field, descs.clone())
+            })
+            .collect();
+        tokens.extend(impl_class_stub(
+            ty,
+            &name.to_string(),
+            &descriptor_stubs(ty, &descriptors),
+        ));
+        tokens.extend(impl_class(
+            ty,
+            name,
+            name.to_string(),
+            &attr,
+            doc.clone(),
+            descriptors,
+        ));
+    }
+    Ok(tokens)
+}
+
+/// `instantiate(...)` requires type parameters and replaces the `name` argument
+fn check_instantiate(class: &syn::ItemStruct, attr: &PyClassArgs) -> syn::Result<()> {
+    if class.generics.type_params().next().is_none() {
+        return Err(syn::Error::new_spanned(
+            &class.ident,
+            "instantiate(...) can only be used on classes with type parameters",
+        ));
+    }
+    if let Some(param) = class.generics.params.iter().find(|param| match param {
+        syn::GenericParam::Type(_) => false,
+        _ => true,
+    }) {
+        return Err(syn::Error::new_spanned(
+            param,
+            "#[pyclass] can only have type parameters",
+        ));
+    }
+    if let Some(name) = &attr.name {
+        return Err(syn::Error::new_spanned(
+            name,
+            "the names of a generic class are set in instantiate(...)",
+        ));
+    }
+    Ok(())
+}
+
+pub fn build_py_enum(enum_: &mut syn::ItemEnum, attr: &PyClassArgs) -> syn::Result<TokenStream> {
+    let doc = utils::get_doc(&enum_.attrs, true);
+
+    check_generics(&enum_.generics)?;
+    if let Some((name, _)) = attr.instantiate.first() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "instantiate(...) can only be used on classes with type parameters",
+        ));
+    }
+    if enum_.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &enum_.ident,
+            "#[pyclass] can not be used on enums without variants",
+        ));
+    }
+
+    let cls = &enum_.ident;
+    let ty: syn::Type = parse_quote!(#cls);
+    let cls_name = get_class_python_name(cls, attr);
+    let variant_stubs: Vec<TokenStream> = enum_
+        .variants
+        .iter()
+        .filter(|variant| variant.fields == syn::Fields::Unit)
+        .map(|variant| {
+            let name = variant.ident.to_string();
+            quote! {
+                pyo3::stubs::AttributeStub {
+                    name: #name,
+                    kind: pyo3::stubs::AttributeKind::Class,
+                    annotation: <#cls as pyo3::stubs::TypeHint>::type_hint,
+                }
+            }
+        })
+        .collect();
+    let class_stub = impl_class_stub(&ty, &cls_name, &variant_stubs);
+    let class = impl_class(&ty, cls, cls_name, &attr, doc, Vec::new());
+    let variants = if enum_
+        .variants
+        .iter()
+        .all(|variant| variant.fields == syn::Fields::Unit)
+    {
+        impl_c_like_enum(
+            &enum_.ident,
+            &get_class_python_name(&enum_.ident, attr),
+            enum_,
+        )?
+    } else {
+        impl_data_enum(
+            &enum_.ident,
+            &get_class_python_name(&enum_.ident, attr),
+            enum_,
+        )?
+    };
+
+    Ok(quote! {
+        #class
+        #class_stub
+        #variants
+    })
+}
+
+/// Fieldless enums get one class attribute per variant, comparison with other instances
+/// and ints, hashing, `repr()` and `int()`
+fn impl_c_like_enum(
+    cls: &syn::Ident,
+    cls_name: &str,
+    enum_: &syn::ItemEnum,
+) -> syn::Result<TokenStream> {
+    let variants: Vec<&syn::Ident> = enum_.variants.iter().map(|v| &v.ident).collect();
+    let reprs: Vec<String> = variants
+        .iter()
+        .map(|variant| format!("{}.{}", cls_name, variant))
+        .collect();
+    let discriminant = |value: TokenStream| {
+        quote! {
+            match #value { #(#cls::#variants => #cls::#variants as isize),* }
+        }
+    };
+    let self_discriminant = discriminant(quote!(self));
+    let other_discriminant = discriminant(quote!(other));
+
+    let mut object_protocol: syn::ItemImpl = syn::parse_quote! {
+        impl pyo3::class::basic::PyObjectProtocol for #cls {
+            fn __repr__(&self) -> pyo3::PyResult<String> {
+                Ok(String::from(match self { #(#cls::#variants => #reprs),* }))
+            }
+
+            fn __hash__(&self) -> pyo3::PyResult<isize> {
+                Ok(#self_discriminant)
+            }
+
+            fn __richcmp__(
+                &self,
+                other: &pyo3::types::PyAny,
+                op: pyo3::class::basic::CompareOp,
+            ) -> pyo3::PyResult<pyo3::PyObject> {
+                let py = pyo3::PyNativeType::py(other);
+                let other = match <#cls as pyo3::PyTryFrom>::try_from(other) {
+                    Ok(other) => #other_discriminant,
+                    Err(_) => match pyo3::ObjectProtocol::extract::<isize>(other) {
+                        Ok(other) => other,
+                        Err(_) => return Ok(py.NotImplemented()),
+                    },
+                };
+                match op {
+                    pyo3::class::basic::CompareOp::Eq => {
+                        Ok(pyo3::IntoPy::into_py(#self_discriminant == other, py))
+                    }
+                    pyo3::class::basic::CompareOp::Ne => {
+                        Ok(pyo3::IntoPy::into_py(#self_discriminant != other, py))
+                    }
+                    _ => Ok(py.NotImplemented()),
+                }
+            }
+        }
+    };
+    let object_protocol_impl = build_py_proto(&mut object_protocol)?;
+
+    let mut number_protocol: syn::ItemImpl = syn::parse_quote! {
+        impl pyo3::class::number::PyNumberProtocol for #cls {
+            fn __int__(&self) -> pyo3::PyResult<isize> {
+                Ok(#self_discriminant)
+            }
+        }
+    };
+    let number_protocol_impl = build_py_proto(&mut number_protocol)?;
+
+    let class_attributes = impl_variant_class_attributes(cls, enum_);
+
+    Ok(quote! {
+        #object_protocol
+        #object_protocol_impl
+
+        #number_protocol
+        #number_protocol_impl
+
+        impl<'source> pyo3::FromPyObject<'source> for #cls {
+            fn extract(obj: &'source pyo3::types::PyAny) -> pyo3::PyResult<Self> {
+                let value: &#cls = pyo3::PyTryFrom::try_from(obj)?;
+                Ok(match value { #(#cls::#variants => #cls::#variants),* })
+            }
+        }
+
+        pyo3::inventory::submit! {
+            #![crate = pyo3] {
+                type ClsInventory = <#cls as pyo3::class::methods::PyMethodsInventoryDispatch>::InventoryType;
+                <ClsInventory as pyo3::class::methods::PyMethodsInventory>::new(&[#(#class_attributes),*])
+            }
+        }
+    })
+}
+
+/// Enums with fields get a static method per variant that constructs it from the fields,
+/// while the remaining unit variants become class attributes. The fields can be read through
+/// getters, and instances support `repr()` and comparison for (in)equality
+fn impl_data_enum(
+    cls: &syn::Ident,
+    cls_name: &str,
+    enum_: &syn::ItemEnum,
+) -> syn::Result<TokenStream> {
+    let ty: syn::Type = syn::parse_quote!(#cls);
+    let mut constructors = Vec::new();
+    let mut method_defs = impl_variant_class_attributes(cls, enum_);
+    let mut method_stubs = Vec::new();
+
+    let field_getters = impl_variant_field_getters(cls, enum_);
+    let variant_fields = impl_variant_fields(cls, enum_);
+    method_defs.extend(field_getters.iter().map(|(_, def)| def.clone()));
+    let getters = field_getters.into_iter().map(|(getter, _)| getter);
+
+    for variant in enum_.variants.iter() {
+        let ident = &variant.ident;
+        let (params, construct) = match &variant.fields {
+            syn::Fields::Unit => continue,
+            syn::Fields::Named(fields) => {
+                let names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
+                let types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
+                (
+                    quote!(#(#names: #types),*),
+                    quote!(#cls::#ident { #(#names),* }),
+                )
+            }
+            syn::Fields::Unnamed(fields) => {
+                let names: Vec<syn::Ident> = (0..fields.unnamed.len())
+                    .map(|index| syn::Ident::new(&format!("_{}", index), Span::call_site()))
+                    .collect();
+                let types: Vec<_> = fields.unnamed.iter().map(|f| &f.ty).collect();
+                (
+                    quote!(#(#names: #types),*),
+                    quote!(#cls::#ident(#(#names),*)),
+                )
+            }
+        };
+
+        let name = syn::Ident::new(&format!("__pyo3_variant_{}", ident), Span::call_site());
+        let constructor: syn::ImplItemMethod = syn::parse_quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            fn #name(#params) -> #cls {
+                #construct
+            }
+        };
+        let mut attrs = vec![syn::parse_quote!(#[staticmethod])];
+        let spec = FnSpec::parse(&name, &constructor.sig, &mut attrs)?;
+        let wrapper = impl_wrap_static(&ty, &name, &spec);
+        let doc = utils::get_doc(&variant.attrs, true);
+        let variant_name = ident.to_string();
+        method_stubs.extend(stubs::function_stub(&variant_name, &spec, Some(&ty)));
+
+        method_defs.push(quote! {
+            pyo3::class::PyMethodDefType::Static({
+                #wrapper
+
+                pyo3::class::PyMethodDef {
+                    ml_name: #variant_name,
+                    ml_meth: pyo3::class::PyMethodType::PyCFunctionWithKeywords(__wrap),
+                    ml_flags: pyo3::ffi::METH_VARARGS | pyo3::ffi::METH_KEYWORDS | pyo3::ffi::METH_STATIC,
+                    ml_doc: #doc,
+                }
+            })
+        });
+        constructors.push(constructor);
+    }
+
+    let stubs = stubs::submit_methods(&ty, &method_stubs);
+
+    let mut object_protocol: syn::ItemImpl = syn::parse_quote! {
+        impl pyo3::class::basic::PyObjectProtocol for #cls {
+            fn __repr__(&self) -> pyo3::PyResult<String> {
+                // `self` is borrowed from a python object, so the GIL is held like for the
+                // receiver of `PyNativeType::py`
+                let py = unsafe { pyo3::Python::assume_gil_acquired() };
+                let (variant, names, fields) = self.__pyo3_variant_fields(py);
+                let mut repr = format!("{}.{}", #cls_name, variant);
+                if !fields.is_empty() {
+                    let mut args = Vec::with_capacity(fields.len());
+                    for (name, field) in names.iter().zip(fields.iter()) {
+                        let field = pyo3::ObjectProtocol::repr(&*pyo3::AsPyRef::as_ref(field, py))?
+                            .to_string()?
+                            .into_owned();
+                        if name.starts_with('_') {
+                            args.push(field);
+                        } else {
+                            args.push(format!("{}={}", name, field));
+                        }
+                    }
+                    repr.push_str(&format!("({})", args.join(", ")));
+                }
+                Ok(repr)
+            }
+
+            fn __richcmp__(
+                &self,
+                other: &pyo3::types::PyAny,
+                op: pyo3::class::basic::CompareOp,
+            ) -> pyo3::PyResult<pyo3::PyObject> {
+                let py = pyo3::PyNativeType::py(other);
+                let other = match <#cls as pyo3::PyTryFrom>::try_from(other) {
+                    Ok(other) => other,
+                    Err(_) => return Ok(py.NotImplemented()),
+                };
+                let (variant, _, fields) = self.__pyo3_variant_fields(py);
+                let (other_variant, _, other_fields) = other.__pyo3_variant_fields(py);
+                let same_variant = variant == other_variant;
+                match op {
+                    pyo3::class::basic::CompareOp::Eq if !same_variant => {
+                        Ok(pyo3::IntoPy::into_py(false, py))
+                    }
+                    pyo3::class::basic::CompareOp::Ne if !same_variant => {
+                        Ok(pyo3::IntoPy::into_py(true, py))
+                    }
+                    pyo3::class::basic::CompareOp::Eq | pyo3::class::basic::CompareOp::Ne => {
+                        pyo3::ObjectProtocol::rich_compare(
+                            pyo3::types::PyTuple::new(py, &fields),
+                            pyo3::types::PyTuple::new(py, &other_fields),
+                            op,
+                        )
+                    }
+                    _ => Ok(py.NotImplemented()),
+                }
+            }
+        }
+    };
+    let object_protocol_impl = build_py_proto(&mut object_protocol)?;
+
+    Ok(quote! {
+        impl #cls {
+            #(#constructors)*
+
+            #variant_fields
+
+            #(#getters)*
+        }
+
+        #object_protocol
+        #object_protocol_impl
+
+        impl<'source> pyo3::FromPyObject<'source> for #cls {
+            fn extract(obj: &'source pyo3::types::PyAny) -> pyo3::PyResult<Self> {
+                let value: &#cls = pyo3::PyTryFrom::try_from(obj)?;
+                Ok(::std::clone::Clone::clone(value))
+            }
+        }
+
+        pyo3::inventory::submit! {
+            #![crate = pyo3] {
+                type ClsInventory = <#cls as pyo3::class::methods::PyMethodsInventoryDispatch>::InventoryType;
+                <ClsInventory as pyo3::class::methods::PyMethodsInventory>::new(&[#(#method_defs),*])
+            }
+        }
+
+        #stubs
+    })
+}
+
+/// The python names of the fields of a variant, `_0`, `_1`, ... for tuple variants
+fn variant_field_names(variant: &syn::Variant) -> Vec<(syn::Member, String)> {
+    variant
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => (syn::Member::Named(ident.clone()), ident.to_string()),
+            None => (syn::Member::Unnamed(index.into()), format!("_{}", index)),
+        })
+        .collect()
+}
+
+/// Returns the name of the variant, the names of its fields and the fields converted
+/// to python objects, which is used for `repr()` and comparisons
+fn impl_variant_fields(cls: &syn::Ident, enum_: &syn::ItemEnum) -> TokenStream {
+    let arms = enum_.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let variant_name = ident.to_string();
+        let (members, names): (Vec<_>, Vec<_>) = variant_field_names(variant).into_iter().unzip();
+        let bindings: Vec<syn::Ident> = (0..members.len())
+            .map(|index| syn::Ident::new(&format!("__field{}", index), Span::call_site()))
+            .collect();
+        quote! {
+            #cls::#ident { #(#members: #bindings),* } => (
+                #variant_name,
+                &[#(#names),*],
+                vec![#(pyo3::ToPyObject::to_object(#bindings, py)),*],
+            )
+        }
+    });
+
+    quote! {
+        #[doc(hidden)]
+        fn __pyo3_variant_fields(
+            &self,
+            py: pyo3::Python,
+        ) -> (&'static str, &'static [&'static str], Vec<pyo3::PyObject>) {
+            match self {
+                #(#arms),*
+            }
+        }
+    }
+}
+
+/// Every field name of any variant gets a getter, which raises an `AttributeError` for
+/// variants that don't have a field with that name
+fn impl_variant_field_getters(
+    cls: &syn::Ident,
+    enum_: &syn::ItemEnum,
+) -> Vec<(TokenStream, TokenStream)> {
+    let ty: syn::Type = syn::parse_quote!(#cls);
+    let mut field_names: Vec<String> = Vec::new();
+    for variant in enum_.variants.iter() {
+        for (_, name) in variant_field_names(variant) {
+            if !field_names.contains(&name) {
+                field_names.push(name);
+            }
+        }
+    }
+
+    field_names
+        .into_iter()
+        .map(|field_name| {
+            let arms = enum_.variants.iter().filter_map(|variant| {
+                let ident = &variant.ident;
+                let (member, _) = variant_field_names(variant)
+                    .into_iter()
+                    .find(|(_, name)| *name == field_name)?;
+                Some(quote! {
+                    #cls::#ident { #member: field, .. } => Ok(pyo3::ToPyObject::to_object(field, py))
+                })
+            });
+            let name = syn::Ident::new(&format!("__pyo3_field_{}", field_name), Span::call_site());
+            let getter = quote! {
+                #[doc(hidden)]
+                fn #name(&self, py: pyo3::Python) -> pyo3::PyResult<pyo3::PyObject> {
+                    match self {
+                        #(#arms,)*
+                        _ => Err(pyo3::exceptions::AttributeError::py_err(#field_name)),
+                    }
+                }
+            };
+            let doc = syn::Lit::from(syn::LitStr::new(&field_name, Span::call_site()));
+            let def = impl_py_getter_def(
+                &name,
+                doc,
+                &Some(field_name),
+                &impl_wrap_getter(&ty, &name, true, false),
+            );
+            (getter, def)
+        })
+        .collect()
+}
+
+/// Each unit variant becomes a class attribute holding an instance of the class
+fn impl_variant_class_attributes(cls: &syn::Ident, enum_: &syn::ItemEnum) -> Vec<TokenStream> {
+    enum_
+        .variants
+        .iter()
+        .filter(|variant| variant.fields == syn::Fields::Unit)
+        .map(|variant| {
+            let ident = &variant.ident;
+            impl_py_class_attribute_def(&ident.to_string(), &quote!(#cls::#ident))
+        })
+        .collect()
 }
 
 /// Parses `#[pyo3(get, set)]`
@@ -213,10 +748,10 @@ fn parse_descriptors(item: &mut syn::Field) -> syn::Result<Vec<FnType>> {
 
 /// The orphan rule disallows using a generic inventory struct, so we create the whole boilerplate
 /// once per class
-fn impl_inventory(cls: &syn::Ident) -> TokenStream {
+fn impl_inventory(cls: &syn::Type, ident: &syn::Ident) -> TokenStream {
     // Try to build a unique type that gives a hint about it's function when
     // it comes up in error messages
-    let name = cls.to_string() + "GeneratedPyo3Inventory";
+    let name = ident.to_string() + "GeneratedPyo3Inventory";
     let inventory_cls = syn::Ident::new(&name, Span::call_site());
 
     quote! {
@@ -245,17 +780,23 @@ fn impl_inventory(cls: &syn::Ident) -> TokenStream {
     }
 }
 
+fn get_class_python_name(cls: &syn::Ident, attr: &PyClassArgs) -> String {
+    match &attr.name {
+        Some(name) => quote! { #name }.to_string(),
+        None => cls.to_string(),
+    }
+}
+
+/// `cls` is the rust type and `ident` a unique name for it, which is the name of the struct
+/// for normal classes and the name of the instantiation for generic classes
 fn impl_class(
-    cls: &syn::Ident,
+    cls: &syn::Type,
+    ident: &syn::Ident,
+    cls_name: String,
     attr: &PyClassArgs,
     doc: syn::Lit,
     descriptors: Vec<(syn::Field, Vec<FnType>)>,
 ) -> TokenStream {
-    let cls_name = match &attr.name {
-        Some(name) => quote! { #name }.to_string(),
-        None => cls.to_string(),
-    };
-
     let extra = {
         if let Some(freelist) = &attr.freelist {
             quote! {
@@ -267,6 +808,7 @@ fn impl_class(
                             if FREELIST.is_null() {
                                 FREELIST = Box::into_raw(Box::new(
                                     pyo3::freelist::FreeList::with_capacity(#freelist)));
+                                pyo3::freelist::register_free_list(&mut *FREELIST);
 
                                 <#cls as pyo3::type_object::PyTypeObject>::init_type();
                             }
@@ -283,9 +825,7 @@ fn impl_class(
     };
 
     let extra = if !descriptors.is_empty() {
-        let path = syn::Path::from(syn::PathSegment::from(cls.clone()));
-        let ty = syn::Type::from(syn::TypePath { path, qself: None });
-        let desc_impls = impl_descriptors(&ty, descriptors);
+        let desc_impls = impl_descriptors(cls, descriptors);
         quote! {
             #desc_impls
             #extra
@@ -327,7 +867,7 @@ fn impl_class(
 
     // Enforce at compile time that PyGCProtocol is implemented
     let gc_impl = if has_gc {
-        let closure_name = format!("__assertion_closure_{}", cls.to_string());
+        let closure_name = format!("__assertion_closure_{}", ident);
         let closure_token = syn::Ident::new(&closure_name, Span::call_site());
         quote! {
             fn #closure_token() {
@@ -341,7 +881,7 @@ fn impl_class(
         quote! {}
     };
 
-    let inventory_impl = impl_inventory(&cls);
+    let inventory_impl = impl_inventory(cls, ident);
 
     let base = &attr.base;
     let flags = &attr.flags;
@@ -357,8 +897,11 @@ fn impl_class(
             const FLAGS: usize = #(#flags)|*;
 
             const SIZE: usize = {
-                Self::OFFSET as usize +
-                ::std::mem::size_of::<#cls>() + #weakref + #dict
+                // the rust value is followed by its borrow flag
+                pyo3::type_object::borrow_flag_offset(
+                    Self::OFFSET as usize,
+                    ::std::mem::size_of::<#cls>(),
+                ) + ::std::mem::size_of::<pyo3::type_object::BorrowFlag>() + #weakref + #dict
             };
             const OFFSET: isize = {
                 // round base_size up to next multiple of align
@@ -391,6 +934,49 @@ fn impl_class(
     }
 }
 
+/// Submits the `pyo3::stubs::ClassStub` of a class
+fn impl_class_stub(cls: &syn::Type, cls_name: &str, attributes: &[TokenStream]) -> TokenStream {
+    quote! {
+        pyo3::inventory::submit! {
+            #![crate = pyo3]
+            pyo3::stubs::TypeStub::Class(pyo3::stubs::ClassStub {
+                name: #cls_name,
+                type_object: <#cls as pyo3::type_object::PyTypeObject>::type_object,
+                attributes: vec![#(#attributes),*],
+            })
+        }
+    }
+}
+
+/// The `pyo3::stubs::AttributeStub`s of the fields with `#[pyo3(get, set)]`
+fn descriptor_stubs(
+    cls: &syn::Type,
+    descriptors: &[(syn::Field, Vec<FnType>)],
+) -> Vec<TokenStream> {
+    descriptors
+        .iter()
+        .map(|(field, fns)| {
+            let name = field.ident.as_ref().unwrap().to_string();
+            let kind = if fns.iter().any(|desc| match desc {
+                FnType::Setter(_) => true,
+                _ => false,
+            }) {
+                quote!(ReadWrite)
+            } else {
+                quote!(ReadOnly)
+            };
+            let annotation = stubs::type_hint(&field.ty, Some(cls));
+            quote! {
+                pyo3::stubs::AttributeStub {
+                    name: #name,
+                    kind: pyo3::stubs::AttributeKind::#kind,
+                    annotation: #annotation,
+                }
+            }
+        })
+        .collect()
+}
+
 fn impl_descriptors(cls: &syn::Type, descriptors: Vec<(syn::Field, Vec<FnType>)>) -> TokenStream {
     let methods: Vec<TokenStream> = descriptors
         .iter()
@@ -444,13 +1030,14 @@ fn impl_descriptors(cls: &syn::Type, descriptors: Vec<(syn::Field, Vec<FnType>)>
                             &name,
                             doc,
                             getter,
-                            &impl_wrap_getter(&cls, &name, false),
+                            &impl_wrap_getter(&cls, &name, false, false),
                         ),
                         FnType::Setter(ref setter) => {
                             let setter_name =
                                 syn::Ident::new(&format!("set_{}", name), Span::call_site());
                             let spec = FnSpec {
                                 tp: FnType::Setter(None),
+                                mutable_self: true,
                                 attrs: Vec::new(),
                                 args: vec![FnArg {
                                     name: &name,
@@ -489,12 +1076,12 @@ fn impl_descriptors(cls: &syn::Type, descriptors: Vec<(syn::Field, Vec<FnType>)>
     }
 }
 
-fn check_generics(class: &mut syn::ItemStruct) -> syn::Result<()> {
-    if class.generics.params.is_empty() {
+fn check_generics(generics: &syn::Generics) -> syn::Result<()> {
+    if generics.params.is_empty() {
         Ok(())
     } else {
         Err(syn::Error::new_spanned(
-            &class.generics,
+            generics,
             "#[pyclass] cannot have generic parameters",
         ))
     }
@@ -1,39 +1,159 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
 use crate::pymethod;
+use crate::slots;
+use crate::stubs;
+use crate::utils;
 use proc_macro2::TokenStream;
 use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::Token;
 
-pub fn build_py_methods(ast: &mut syn::ItemImpl) -> syn::Result<TokenStream> {
+/// The parsed arguments of the pymethods macro
+#[derive(Default)]
+pub struct PyMethodsArgs {
+    /// The concrete types a generic impl block is registered for
+    pub instantiate: Vec<syn::Type>,
+}
+
+impl Parse for PyMethodsArgs {
+    fn parse(input: ParseStream) -> syn::parse::Result<Self> {
+        if input.is_empty() {
+            return Ok(PyMethodsArgs::default());
+        }
+        let ident: syn::Ident = input.parse()?;
+        if ident != "instantiate" {
+            return Err(syn::Error::new_spanned(ident, "Unsupported parameter"));
+        }
+        let content;
+        syn::parenthesized!(content in input);
+        let types = Punctuated::<syn::Type, Token![,]>::parse_terminated(&content)?;
+        Ok(PyMethodsArgs {
+            instantiate: types.into_iter().collect(),
+        })
+    }
+}
+
+pub fn build_py_methods(ast: &mut syn::ItemImpl, attr: &PyMethodsArgs) -> syn::Result<TokenStream> {
     if let Some((_, ref path, _)) = ast.trait_ {
         Err(syn::Error::new_spanned(
             path,
             "#[pymethods] can not be used only with trait impl block",
         ))
+    } else if !attr.instantiate.is_empty() {
+        impl_generic_methods(ast, &attr.instantiate)
     } else if ast.generics != Default::default() {
         Err(syn::Error::new_spanned(
             ast.generics.clone(),
-            "#[pymethods] can not be used with lifetime parameters or generics",
+            "#[pymethods] can not be used with lifetime parameters or generics \
+             unless the concrete types are listed with instantiate(...)",
         ))
     } else {
         impl_methods(&ast.self_ty, &mut ast.items)
     }
 }
 
+/// Registers the methods of a generic impl block once for every concrete type, the same way
+/// as if there was a separate impl block for each of them
+fn impl_generic_methods(
+    ast: &mut syn::ItemImpl,
+    instantiate: &[syn::Type],
+) -> syn::Result<TokenStream> {
+    if let Some(param) = ast.generics.params.iter().find(|param| match param {
+        syn::GenericParam::Type(_) => false,
+        _ => true,
+    }) {
+        return Err(syn::Error::new_spanned(
+            param,
+            "#[pymethods] can only have type parameters",
+        ));
+    }
+    if ast.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.self_ty,
+            "instantiate(...) can only be used on impl blocks with type parameters",
+        ));
+    }
+    let type_params: Vec<syn::Ident> = ast
+        .generics
+        .type_params()
+        .map(|p| p.ident.clone())
+        .collect();
+
+    let mut tokens = TokenStream::new();
+    let mut stripped_items = None;
+    for ty in instantiate {
+        let params = utils::match_type_params(&ast.self_ty, ty, &type_params)?;
+        let mut items = ast.items.clone();
+        for item in items.iter_mut() {
+            if let syn::ImplItem::Method(ref mut meth) = item {
+                for input in meth.sig.inputs.iter_mut() {
+                    if let syn::FnArg::Typed(ref mut arg) = input {
+                        *arg.ty = utils::substitute_type_params(&arg.ty, &params);
+                    }
+                }
+                if let syn::ReturnType::Type(_, ref mut output) = meth.sig.output {
+                    **output = utils::substitute_type_params(output, &params);
+                }
+            }
+            if let syn::ImplItem::Const(ref mut konst) = item {
+                konst.ty = utils::substitute_type_params(&konst.ty, &params);
+            }
+        }
+        tokens.extend(impl_methods(ty, &mut items)?);
+        stripped_items = Some(items);
+    }
+
+    // The generic impl block itself must lose the pyo3 attributes just like a normal one
+    if let Some(stripped_items) = stripped_items {
+        for (item, stripped) in ast.items.iter_mut().zip(stripped_items) {
+            match (item, stripped) {
+                (syn::ImplItem::Method(meth), syn::ImplItem::Method(stripped)) => {
+                    meth.attrs = stripped.attrs
+                }
+                (syn::ImplItem::Const(konst), syn::ImplItem::Const(stripped)) => {
+                    konst.attrs = stripped.attrs
+                }
+                _ => (),
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
 pub fn impl_methods(ty: &syn::Type, impls: &mut Vec<syn::ImplItem>) -> syn::Result<TokenStream> {
     // get method names in impl block
     let mut methods = Vec::new();
+    let mut method_stubs = Vec::new();
+    let mut slot_methods = Vec::new();
     for iimpl in impls.iter_mut() {
-        if let syn::ImplItem::Method(ref mut meth) = iimpl {
-            let name = meth.sig.ident.clone();
-            methods.push(pymethod::gen_py_method(
-                ty,
-                &name,
-                &mut meth.sig,
-                &mut meth.attrs,
-            )?);
+        match iimpl {
+            syn::ImplItem::Method(ref mut meth) => {
+                if slots::is_slot_method(meth) {
+                    slot_methods.push(meth);
+                    continue;
+                }
+                let name = meth.sig.ident.clone();
+                let (method, stub) =
+                    pymethod::gen_py_method(ty, &name, &mut meth.sig, &mut meth.attrs)?;
+                methods.push(method);
+                method_stubs.extend(stub);
+            }
+            syn::ImplItem::Const(ref mut konst) => {
+                if let Some((attribute, stub)) = pymethod::gen_py_const(ty, konst) {
+                    methods.push(attribute);
+                    method_stubs.push(stub);
+                }
+            }
+            _ => (),
         }
     }
+    let (slot_defs, slot_stubs) = slots::impl_slots(ty, slot_methods)?;
+    methods.extend(slot_defs);
+    method_stubs.extend(slot_stubs);
+    let stubs = stubs::submit_methods(ty, &method_stubs);
 
     Ok(quote! {
        pyo3::inventory::submit! {
@@ -42,5 +162,7 @@ pub fn impl_methods(ty: &syn::Type, impls: &mut Vec<syn::ImplItem>) -> syn::Resu
                 <TyInventory as pyo3::class::methods::PyMethodsInventory>::new(&[#(#methods),*])
             }
         }
+
+        #stubs
     })
 }
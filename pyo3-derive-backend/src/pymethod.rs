@@ -1,21 +1,32 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 use crate::method::{FnArg, FnSpec, FnType};
+use crate::stubs;
 use crate::utils;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
+use syn::ext::IdentExt;
 
+/// Generates the method definition and, unless the method doesn't show up in stubs,
+/// the `pyo3::stubs::FunctionStub` of a method
 pub fn gen_py_method(
     cls: &syn::Type,
     name: &syn::Ident,
     sig: &mut syn::Signature,
     meth_attrs: &mut Vec<syn::Attribute>,
-) -> syn::Result<TokenStream> {
+) -> syn::Result<(TokenStream, Option<TokenStream>)> {
     check_generic(name, sig)?;
 
+    let text_signature = utils::take_text_signature_attr(meth_attrs)?;
     let doc = utils::get_doc(&meth_attrs, true);
     let spec = FnSpec::parse(name, sig, meth_attrs)?;
-
-    Ok(match spec.tp {
+    let python_name = name.unraw().to_string();
+    let doc = utils::add_text_signature(
+        doc,
+        spec.text_signature(&python_name, text_signature.as_ref())?,
+    );
+    let stub = stubs::function_stub(&python_name, &spec, Some(cls));
+
+    let method = match spec.tp {
         FnType::Fn => impl_py_method_def(name, doc, &spec, &impl_wrap(cls, name, &spec, true)),
         FnType::PySelf(ref self_ty) => impl_py_method_def(
             name,
@@ -30,6 +41,31 @@ pub fn gen_py_method(
         FnType::FnStatic => {
             impl_py_method_def_static(name, doc, &impl_wrap_static(cls, name, &spec))
         }
+        FnType::ClassAttribute => {
+            if let Some(arg) = sig.inputs.iter().find(|arg| match arg {
+                syn::FnArg::Receiver(_) => true,
+                syn::FnArg::Typed(_) => false,
+            }) {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    "#[classattr] can only be used on functions without self",
+                ));
+            }
+            let takes_py = match &*spec.args {
+                [] => false,
+                [arg] if arg.py => true,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        spec.args[0].ty,
+                        "#[classattr] can only have one argument of type pyo3::Python",
+                    ))
+                }
+            };
+            impl_py_class_attribute_def(
+                &python_name,
+                &impl_call_class_attribute(cls, name, takes_py),
+            )
+        }
         FnType::Getter(ref getter) => {
             let takes_py = match &*spec.args {
                 [] => false,
@@ -41,12 +77,37 @@ pub fn gen_py_method(
                     ))
                 }
             };
-            impl_py_getter_def(name, doc, getter, &impl_wrap_getter(cls, name, takes_py))
+            impl_py_getter_def(
+                name,
+                doc,
+                getter,
+                &impl_wrap_getter(cls, name, takes_py, spec.mutable_self),
+            )
         }
         FnType::Setter(ref setter) => {
             impl_py_setter_def(name, doc, setter, &impl_wrap_setter(cls, name, &spec))
         }
-    })
+    };
+    Ok((method, stub))
+}
+
+/// Generates the class attribute definition and the stub of an associated const with
+/// `#[classattr]`, or `None` for other consts
+pub fn gen_py_const(
+    cls: &syn::Type,
+    konst: &mut syn::ImplItemConst,
+) -> Option<(TokenStream, TokenStream)> {
+    let attrs = konst.attrs.len();
+    konst.attrs.retain(|attr| !attr.path.is_ident("classattr"));
+    if konst.attrs.len() == attrs {
+        return None;
+    }
+
+    let ident = &konst.ident;
+    let name = ident.unraw().to_string();
+    let def = impl_py_class_attribute_def(&name, &quote!(<#cls>::#ident));
+    let stub = stubs::class_attribute_stub(&name, &konst.ty, cls);
+    Some((def, stub))
 }
 
 fn check_generic(name: &syn::Ident, sig: &syn::Signature) -> syn::Result<()> {
@@ -78,7 +139,7 @@ pub fn impl_wrap(
     noargs: bool,
 ) -> TokenStream {
     let body = impl_call(cls, name, &spec);
-    let slf = impl_self(&quote! { &mut #cls });
+    let slf = impl_self(&self_ref_type(cls, spec), quote!(::std::ptr::null_mut()));
     impl_wrap_common(cls, name, spec, noargs, slf, body)
 }
 
@@ -91,9 +152,9 @@ pub fn impl_wrap_pyslf(
 ) -> TokenStream {
     let names = get_arg_names(spec);
     let body = quote! {
-        #cls::#name(_slf, #(#names),*)
+        <#cls>::#name(_slf, #(#names),*)
     };
-    let slf = impl_self(self_ty);
+    let slf = impl_self(self_ty, quote!(::std::ptr::null_mut()));
     impl_wrap_common(cls, name, spec, noargs, slf, body)
 }
 
@@ -115,13 +176,15 @@ fn impl_wrap_common(
                     stringify!(#cls), ".", stringify!(#name), "()");
                 let _py = pyo3::Python::assume_gil_acquired();
                 let _pool = pyo3::GILPool::new(_py);
-                #slf
-                let _result = {
-                    pyo3::derive_utils::IntoPyResult::into_py_result(#body)
-                };
-
-                pyo3::callback::cb_convert(
-                    pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                pyo3::callback::handle_panic(_py, || {
+                    #slf
+                    let _result = {
+                        pyo3::derive_utils::IntoPyResult::into_py_result(#body)
+                    };
+
+                    pyo3::callback::cb_convert(
+                        pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                })
             }
         }
     } else {
@@ -137,14 +200,16 @@ fn impl_wrap_common(
                     stringify!(#cls), ".", stringify!(#name), "()");
                 let _py = pyo3::Python::assume_gil_acquired();
                 let _pool = pyo3::GILPool::new(_py);
-                #slf
-                let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
-                let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
+                pyo3::callback::handle_panic(_py, || {
+                    #slf
+                    let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
+                    let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
 
-                #body
+                    #body
 
-                pyo3::callback::cb_convert(
-                    pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                    pyo3::callback::cb_convert(
+                        pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                })
             }
         }
     }
@@ -154,6 +219,7 @@ fn impl_wrap_common(
 pub fn impl_proto_wrap(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> TokenStream {
     let cb = impl_call(cls, name, &spec);
     let body = impl_arg_params(&spec, cb);
+    let slf = impl_self(&self_ref_type(cls, spec), quote!(::std::ptr::null_mut()));
 
     quote! {
         #[allow(unused_mut)]
@@ -165,14 +231,16 @@ pub fn impl_proto_wrap(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) ->
             const _LOCATION: &'static str = concat!(stringify!(#cls),".",stringify!(#name),"()");
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            let _slf = _py.mut_from_borrowed_ptr::<#cls>(_slf);
-            let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
-            let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
+            pyo3::callback::handle_panic(_py, || {
+                #slf
+                let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
+                let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
 
-            #body
+                #body
 
-            pyo3::callback::cb_convert(
-                pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                pyo3::callback::cb_convert(
+                    pyo3::callback::PyObjectCallbackConverter, _py, _result)
+            })
         }
     }
 }
@@ -180,7 +248,7 @@ pub fn impl_proto_wrap(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) ->
 /// Generate class method wrapper (PyCFunction, PyCFunctionWithKeywords)
 pub fn impl_wrap_new(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> TokenStream {
     let names: Vec<syn::Ident> = get_arg_names(&spec);
-    let cb = quote! { #cls::#name(&_obj, #(#names),*) };
+    let cb = quote! { <#cls>::#name(&_obj, #(#names),*) };
 
     let body = impl_arg_params(spec, cb);
 
@@ -196,26 +264,29 @@ pub fn impl_wrap_new(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> T
             const _LOCATION: &'static str = concat!(stringify!(#cls),".",stringify!(#name),"()");
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            match pyo3::type_object::PyRawObject::new(_py, #cls::type_object(), _cls) {
-                Ok(_obj) => {
-                    let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
-                    let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
-
-                    #body
-
-                    match _result {
-                        Ok(_) => pyo3::IntoPyPointer::into_ptr(_obj),
-                        Err(e) => {
-                            e.restore(_py);
-                            ::std::ptr::null_mut()
+            pyo3::callback::handle_panic(_py, || {
+                let _ptr = pyo3::type_object::alloc_object::<#cls>(_py, _cls, _args, _kwargs);
+                match pyo3::type_object::PyRawObject::new_with_ptr(_py, _ptr, <#cls>::type_object(), _cls) {
+                    Ok(_obj) => {
+                        let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
+                        let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
+
+                        #body
+
+                        match _result {
+                            Ok(_) => pyo3::IntoPyPointer::into_ptr(_obj),
+                            Err(e) => {
+                                e.restore(_py);
+                                ::std::ptr::null_mut()
+                            }
                         }
                     }
+                    Err(e) => {
+                        e.restore(_py);
+                        ::std::ptr::null_mut()
+                    }
                 }
-                Err(e) => {
-                    e.restore(_py);
-                    ::std::ptr::null_mut()
-                }
-            }
+            })
         }
     }
 }
@@ -231,6 +302,7 @@ fn impl_wrap_init(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> Toke
     }
 
     let body = impl_arg_params(&spec, cb);
+    let slf = impl_self(&self_ref_type(cls, spec), quote!(-1));
 
     quote! {
         #[allow(unused_mut)]
@@ -242,19 +314,21 @@ fn impl_wrap_init(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> Toke
             const _LOCATION: &'static str = concat!(stringify!(#cls),".",stringify!(#name),"()");
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            let _slf = _py.mut_from_borrowed_ptr::<#cls>(_slf);
-            let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
-            let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
+            pyo3::callback::handle_panic(_py, || {
+                #slf
+                let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
+                let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
 
-            #body
+                #body
 
-            match _result {
-                Ok(_) => 0,
-                Err(e) => {
-                    e.restore(_py);
-                    -1
+                match _result {
+                    Ok(_) => 0,
+                    Err(e) => {
+                        e.restore(_py);
+                        -1
+                    }
                 }
-            }
+            })
         }
     }
 }
@@ -262,7 +336,7 @@ fn impl_wrap_init(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> Toke
 /// Generate class method wrapper (PyCFunction, PyCFunctionWithKeywords)
 pub fn impl_wrap_class(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> TokenStream {
     let names: Vec<syn::Ident> = get_arg_names(&spec);
-    let cb = quote! { #cls::#name(&_cls, #(#names),*) };
+    let cb = quote! { <#cls>::#name(&_cls, #(#names),*) };
 
     let body = impl_arg_params(spec, cb);
 
@@ -276,14 +350,16 @@ pub fn impl_wrap_class(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) ->
             const _LOCATION: &'static str = concat!(stringify!(#cls),".",stringify!(#name),"()");
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            let _cls = pyo3::types::PyType::from_type_ptr(_py, _cls as *mut pyo3::ffi::PyTypeObject);
-            let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
-            let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
+            pyo3::callback::handle_panic(_py, || {
+                let _cls = pyo3::types::PyType::from_type_ptr(_py, _cls as *mut pyo3::ffi::PyTypeObject);
+                let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
+                let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
 
-            #body
+                #body
 
-            pyo3::callback::cb_convert(
-                pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                pyo3::callback::cb_convert(
+                    pyo3::callback::PyObjectCallbackConverter, _py, _result)
+            })
         }
     }
 }
@@ -291,7 +367,7 @@ pub fn impl_wrap_class(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) ->
 /// Generate static method wrapper (PyCFunction, PyCFunctionWithKeywords)
 pub fn impl_wrap_static(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -> TokenStream {
     let names: Vec<syn::Ident> = get_arg_names(&spec);
-    let cb = quote! { #cls::#name(#(#names),*) };
+    let cb = quote! { <#cls>::#name(#(#names),*) };
 
     let body = impl_arg_params(spec, cb);
 
@@ -305,24 +381,46 @@ pub fn impl_wrap_static(cls: &syn::Type, name: &syn::Ident, spec: &FnSpec<'_>) -
             const _LOCATION: &'static str = concat!(stringify!(#cls),".",stringify!(#name),"()");
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
-            let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
+            pyo3::callback::handle_panic(_py, || {
+                let _args = _py.from_borrowed_ptr::<pyo3::types::PyTuple>(_args);
+                let _kwargs: Option<&pyo3::types::PyDict> = _py.from_borrowed_ptr_or_opt(_kwargs);
 
-            #body
+                #body
 
-            pyo3::callback::cb_convert(
-                pyo3::callback::PyObjectCallbackConverter, _py, _result)
+                pyo3::callback::cb_convert(
+                    pyo3::callback::PyObjectCallbackConverter, _py, _result)
+            })
         }
     }
 }
 
+/// Calls a `#[classattr]` function
+fn impl_call_class_attribute(cls: &syn::Type, name: &syn::Ident, takes_py: bool) -> TokenStream {
+    if takes_py {
+        quote! { <#cls>::#name(py) }
+    } else {
+        quote! { <#cls>::#name() }
+    }
+}
+
 /// Generate functiona wrapper (PyCFunction, PyCFunctionWithKeywords)
-pub(crate) fn impl_wrap_getter(cls: &syn::Type, name: &syn::Ident, takes_py: bool) -> TokenStream {
+pub(crate) fn impl_wrap_getter(
+    cls: &syn::Type,
+    name: &syn::Ident,
+    takes_py: bool,
+    mutable_self: bool,
+) -> TokenStream {
     let fncall = if takes_py {
-        quote! { _slf.#name(_py) }
+        quote! { (*_slf).#name(_py) }
     } else {
-        quote! { _slf.#name() }
+        quote! { (*_slf).#name() }
     };
+    let self_ty = if mutable_self {
+        quote!(pyo3::PyRefMut<#cls>)
+    } else {
+        quote!(pyo3::PyRef<#cls>)
+    };
+    let slf = impl_self(&self_ty, quote!(::std::ptr::null_mut()));
     quote! {
         unsafe extern "C" fn __wrap(
             _slf: *mut pyo3::ffi::PyObject, _: *mut ::std::os::raw::c_void) -> *mut pyo3::ffi::PyObject
@@ -331,19 +429,21 @@ pub(crate) fn impl_wrap_getter(cls: &syn::Type, name: &syn::Ident, takes_py: boo
 
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            let _slf = _py.mut_from_borrowed_ptr::<#cls>(_slf);
+            pyo3::callback::handle_panic(_py, || {
+                #slf
 
-            let result = pyo3::derive_utils::IntoPyResult::into_py_result(#fncall);
+                let result = pyo3::derive_utils::IntoPyResult::into_py_result(#fncall);
 
-            match result {
-                Ok(val) => {
-                    pyo3::IntoPyPointer::into_ptr(pyo3::IntoPy::<PyObject>::into_py(val, _py))
-                }
-                Err(e) => {
-                    e.restore(_py);
-                    ::std::ptr::null_mut()
+                match result {
+                    Ok(val) => {
+                        pyo3::IntoPyPointer::into_ptr(pyo3::IntoPy::<PyObject>::into_py(val, _py))
+                    }
+                    Err(e) => {
+                        e.restore(_py);
+                        ::std::ptr::null_mut()
+                    }
                 }
-            }
+            })
         }
     }
 }
@@ -362,6 +462,7 @@ pub(crate) fn impl_wrap_setter(
         );
     }
     let val_ty = spec.args[0].ty;
+    let slf = impl_self(&self_ref_type(cls, spec), quote!(-1));
 
     quote! {
         #[allow(unused_mut)]
@@ -372,22 +473,24 @@ pub(crate) fn impl_wrap_setter(
             const _LOCATION: &'static str = concat!(stringify!(#cls),".",stringify!(#name),"()");
             let _py = pyo3::Python::assume_gil_acquired();
             let _pool = pyo3::GILPool::new(_py);
-            let _slf = _py.mut_from_borrowed_ptr::<#cls>(_slf);
-            let _value = _py.from_borrowed_ptr(_value);
+            pyo3::callback::handle_panic(_py, || {
+                #slf
+                let _value = _py.from_borrowed_ptr(_value);
 
-            let _result = match <#val_ty as pyo3::FromPyObject>::extract(_value) {
-                Ok(_val) => {
-                    pyo3::derive_utils::IntoPyResult::into_py_result(_slf.#name(_val))
-                }
-                Err(e) => Err(e)
-            };
-            match _result {
-                Ok(_) => 0,
-                Err(e) => {
-                    e.restore(_py);
-                    -1
+                let _result = match <#val_ty as pyo3::FromPyObject>::extract(_value) {
+                    Ok(_val) => {
+                        pyo3::derive_utils::IntoPyResult::into_py_result((*_slf).#name(_val))
+                    }
+                    Err(e) => Err(e)
+                };
+                match _result {
+                    Ok(_) => 0,
+                    Err(e) => {
+                        e.restore(_py);
+                        -1
+                    }
                 }
-            }
+            })
         }
     }
 }
@@ -403,12 +506,30 @@ pub fn get_arg_names(spec: &FnSpec) -> Vec<syn::Ident> {
 
 fn impl_call(_cls: &syn::Type, fname: &syn::Ident, spec: &FnSpec<'_>) -> TokenStream {
     let names = get_arg_names(spec);
-    quote! { _slf.#fname(#(#names),*) }
+    quote! { (*_slf).#fname(#(#names),*) }
+}
+
+/// The type that `self` is borrowed as: `PyRefMut` for `&mut self` and `PyRef` for `&self`
+pub(crate) fn self_ref_type(cls: &syn::Type, spec: &FnSpec<'_>) -> TokenStream {
+    if spec.mutable_self {
+        quote!(pyo3::PyRefMut<#cls>)
+    } else {
+        quote!(pyo3::PyRef<#cls>)
+    }
 }
 
-fn impl_self<T: quote::ToTokens>(self_ty: &T) -> TokenStream {
+/// Binds `_slf` to a borrow of the instance of type `self_ty`, which is `PyRef` or `PyRefMut`,
+/// returning `error` from the wrapper if the instance is already borrowed
+pub(crate) fn impl_self<T: quote::ToTokens>(self_ty: &T, error: TokenStream) -> TokenStream {
     quote! {
-        let _slf: #self_ty = pyo3::FromPyPointer::from_borrowed_ptr(_py, _slf);
+        #[allow(unused_mut)]
+        let mut _slf = match <#self_ty>::try_from_borrowed_ptr(_py, _slf) {
+            Ok(_slf) => _slf,
+            Err(e) => {
+                pyo3::PyErr::from(e).restore(_py);
+                return #error;
+            }
+        };
     }
 }
 
@@ -554,8 +675,11 @@ fn impl_arg_param(
             };
         }
     } else {
+        // A `&T` or `&mut T` of a `#[pyclass]` is borrowed until the wrapper returns
+        let borrow_name = syn::Ident::new(&format!("_borrow{}", idx), Span::call_site());
         quote! {
-            let #arg_name = #arg_value.unwrap().extract()?;
+            let (#arg_name, #borrow_name) =
+                pyo3::derive_utils::extract_argument(#arg_value.unwrap())?;
         }
     }
 }
@@ -721,6 +845,23 @@ pub(crate) fn impl_py_setter_def(
     }
 }
 
+/// A `PyClassAttributeDef` whose value is the result of `value`
+pub(crate) fn impl_py_class_attribute_def(name: &str, value: &TokenStream) -> TokenStream {
+    quote! {
+        pyo3::class::PyMethodDefType::ClassAttribute({
+            fn __wrap(py: pyo3::Python) -> pyo3::PyResult<pyo3::PyObject> {
+                pyo3::derive_utils::IntoPyResult::into_py_result(#value)
+                    .map(|value| pyo3::IntoPy::into_py(value, py))
+            }
+
+            pyo3::class::PyClassAttributeDef {
+                name: #name,
+                meth: __wrap,
+            }
+        })
+    }
+}
+
 pub(crate) fn impl_py_getter_def(
     name: &syn::Ident,
     doc: syn::Lit,
@@ -4,6 +4,7 @@ use crate::defs;
 use crate::func::impl_method_proto;
 use crate::method::FnSpec;
 use crate::pymethod;
+use crate::stubs;
 use proc_macro2::Span;
 use proc_macro2::TokenStream;
 use quote::quote;
@@ -61,11 +62,13 @@ fn impl_proto_impl(
 ) -> TokenStream {
     let mut tokens = TokenStream::new();
     let mut py_methods = Vec::new();
+    let mut method_stubs = Vec::new();
 
     for iimpl in impls.iter_mut() {
         if let syn::ImplItem::Method(ref mut met) = iimpl {
             for m in proto.methods {
                 if m == met.sig.ident.to_string().as_str() {
+                    method_stubs.extend(stubs::proto_method_stubs(ty, &met.sig));
                     impl_method_proto(ty, &mut met.sig, m).to_tokens(&mut tokens);
                 }
             }
@@ -102,9 +105,13 @@ fn impl_proto_impl(
         }
     }
 
+    let stubs = stubs::submit_methods(ty, &method_stubs);
+
     quote! {
         #tokens
 
         #(#py_methods)*
+
+        #stubs
     }
 }
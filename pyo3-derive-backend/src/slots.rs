@@ -0,0 +1,666 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! Dunder methods of `#[pymethods]` that are stored in the slots of the type object
+
+use crate::method::{FnSpec, FnType};
+use crate::{pymethod, stubs};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::ext::IdentExt;
+
+/// How a dunder method is called from its slot
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SlotKind {
+    /// `__repr__(self)` etc., returning any python object
+    Unary,
+    /// `__next__(self)`, where returning `None` raises `StopIteration`
+    IterNext,
+    Hash,
+    Bool,
+    Len,
+    /// `__getitem__(self, key)`
+    GetItem,
+    /// `__getattr__(self, name)`, which is only called if the normal lookup fails
+    GetAttr,
+    Contains,
+    /// `__setitem__(self, key, value)` and `__setattr__(self, name, value)`
+    Set,
+    /// `__delitem__(self, key)` and `__delattr__(self, name)`
+    Delete,
+    /// `__richcmp__(self, other, op)`
+    RichCompare,
+    /// `__eq__(self, other)` etc. with the name of their `CompareOp`
+    Compare(&'static str),
+    /// `__add__(self, other)` etc., where `self` is the left operand
+    Number,
+    /// `__radd__(self, other)` etc., where `self` is the right operand
+    Reflected,
+    /// `__iadd__(&mut self, other)` etc.
+    InPlace,
+}
+
+/// The `PySlotDef` variant and the kind of a dunder method
+fn slot_of(name: &str) -> Option<(&'static str, SlotKind)> {
+    use SlotKind::*;
+    Some(match name {
+        "__repr__" => ("Repr", Unary),
+        "__str__" => ("Str", Unary),
+        "__hash__" => ("Hash", Hash),
+        "__richcmp__" => ("RichCompare", RichCompare),
+        "__lt__" => ("RichCompare", Compare("Lt")),
+        "__le__" => ("RichCompare", Compare("Le")),
+        "__eq__" => ("RichCompare", Compare("Eq")),
+        "__ne__" => ("RichCompare", Compare("Ne")),
+        "__gt__" => ("RichCompare", Compare("Gt")),
+        "__ge__" => ("RichCompare", Compare("Ge")),
+        "__getattr__" => ("GetAttr", GetAttr),
+        "__setattr__" => ("SetAttr", Set),
+        "__delattr__" => ("SetAttr", Delete),
+        "__iter__" => ("Iter", Unary),
+        "__next__" => ("IterNext", IterNext),
+        "__len__" => ("Len", Len),
+        "__getitem__" => ("GetItem", GetItem),
+        "__setitem__" => ("SetItem", Set),
+        "__delitem__" => ("SetItem", Delete),
+        "__contains__" => ("Contains", Contains),
+        "__bool__" => ("Bool", Bool),
+        "__neg__" => ("Neg", Unary),
+        "__pos__" => ("Pos", Unary),
+        "__abs__" => ("Abs", Unary),
+        "__invert__" => ("Invert", Unary),
+        "__int__" => ("Int", Unary),
+        "__float__" => ("Float", Unary),
+        "__index__" => ("Index", Unary),
+        "__add__" => ("Add", Number),
+        "__radd__" => ("Add", Reflected),
+        "__sub__" => ("Sub", Number),
+        "__rsub__" => ("Sub", Reflected),
+        "__mul__" => ("Mul", Number),
+        "__rmul__" => ("Mul", Reflected),
+        "__matmul__" => ("MatMul", Number),
+        "__rmatmul__" => ("MatMul", Reflected),
+        "__truediv__" => ("TrueDiv", Number),
+        "__rtruediv__" => ("TrueDiv", Reflected),
+        "__floordiv__" => ("FloorDiv", Number),
+        "__rfloordiv__" => ("FloorDiv", Reflected),
+        "__mod__" => ("Mod", Number),
+        "__rmod__" => ("Mod", Reflected),
+        "__divmod__" => ("DivMod", Number),
+        "__rdivmod__" => ("DivMod", Reflected),
+        "__pow__" => ("Pow", Number),
+        "__rpow__" => ("Pow", Reflected),
+        "__lshift__" => ("LShift", Number),
+        "__rlshift__" => ("LShift", Reflected),
+        "__rshift__" => ("RShift", Number),
+        "__rrshift__" => ("RShift", Reflected),
+        "__and__" => ("And", Number),
+        "__rand__" => ("And", Reflected),
+        "__xor__" => ("Xor", Number),
+        "__rxor__" => ("Xor", Reflected),
+        "__or__" => ("Or", Number),
+        "__ror__" => ("Or", Reflected),
+        "__iadd__" => ("IAdd", InPlace),
+        "__isub__" => ("ISub", InPlace),
+        "__imul__" => ("IMul", InPlace),
+        "__imatmul__" => ("IMatMul", InPlace),
+        "__itruediv__" => ("ITrueDiv", InPlace),
+        "__ifloordiv__" => ("IFloorDiv", InPlace),
+        "__imod__" => ("IMod", InPlace),
+        "__ipow__" => ("IPow", InPlace),
+        "__ilshift__" => ("ILShift", InPlace),
+        "__irshift__" => ("IRShift", InPlace),
+        "__iand__" => ("IAnd", InPlace),
+        "__ixor__" => ("IXor", InPlace),
+        "__ior__" => ("IOr", InPlace),
+        _ => return None,
+    })
+}
+
+/// Attributes that make a method something else than a normal method
+const METHOD_TYPE_ATTRS: &[&str] = &[
+    "new",
+    "__new__",
+    "init",
+    "__init__",
+    "call",
+    "__call__",
+    "classmethod",
+    "staticmethod",
+    "classattr",
+    "getter",
+    "setter",
+];
+
+/// Whether the method is a dunder method that is stored in a slot instead of `tp_methods`
+pub fn is_slot_method(meth: &syn::ImplItemMethod) -> bool {
+    slot_of(&meth.sig.ident.unraw().to_string()).is_some()
+        && !meth.attrs.iter().any(|attr| {
+            METHOD_TYPE_ATTRS
+                .iter()
+                .any(|name| attr.path.is_ident(name))
+        })
+}
+
+struct SlotMethod<'a> {
+    name: &'a syn::Ident,
+    spec: FnSpec<'a>,
+    kind: SlotKind,
+}
+
+impl<'a> SlotMethod<'a> {
+    /// `arg0`, `arg1`, ... for the arguments that are passed from python
+    fn arg_names(&self) -> Vec<syn::Ident> {
+        (0..self.spec.args.iter().filter(|arg| !arg.py).count())
+            .map(|pos| syn::Ident::new(&format!("arg{}", pos), Span::call_site()))
+            .collect()
+    }
+
+    /// Binds `_slf` to a borrow of the object behind `ptr`, returning `error` from the wrapper
+    /// if the object is already borrowed
+    fn bind_slf(&self, cls: &syn::Type, ptr: &TokenStream, error: &TokenStream) -> TokenStream {
+        let self_ty = match self.spec.tp {
+            FnType::PySelf(ref self_ty) => quote!(#self_ty),
+            _ => pymethod::self_ref_type(cls, &self.spec),
+        };
+        let slf = pymethod::impl_self(&self_ty, error.clone());
+        quote! {
+            let _slf = #ptr;
+            #slf
+        }
+    }
+
+    /// Calls the method with `_slf` and the arguments from `arg_names`
+    fn call(&self, cls: &syn::Type) -> TokenStream {
+        let mut names = self.arg_names().into_iter();
+        let args: Vec<TokenStream> = self
+            .spec
+            .args
+            .iter()
+            .map(|arg| {
+                if arg.py {
+                    quote!(_py)
+                } else {
+                    let name = names.next();
+                    quote!(#name)
+                }
+            })
+            .collect();
+        let name = self.name;
+        match self.spec.tp {
+            FnType::PySelf(_) => quote!(<#cls>::#name(_slf, #(#args),*)),
+            _ => quote!((*_slf).#name(#(#args),*)),
+        }
+    }
+
+    /// Extracts the arguments from `ptrs`, returning `error` from the wrapper if that fails
+    fn extract_or_return(&self, ptrs: &[TokenStream], error: &TokenStream) -> TokenStream {
+        let names = self.arg_names();
+        quote! {
+            #(
+                let #names = match pyo3::FromPyObject::extract(
+                    _py.from_borrowed_ptr::<pyo3::types::PyAny>(#ptrs)
+                ) {
+                    Ok(arg) => arg,
+                    Err(e) => {
+                        e.restore(_py);
+                        return #error;
+                    }
+                };
+            )*
+        }
+    }
+
+    /// Runs `body` only if all arguments can be extracted from `ptrs`
+    fn extract_or_skip(&self, ptrs: &[TokenStream], body: TokenStream) -> TokenStream {
+        let names = &self.arg_names()[..ptrs.len()];
+        quote! {
+            if let (#(Ok(#names),)*) = (#(pyo3::FromPyObject::extract(
+                _py.from_borrowed_ptr::<pyo3::types::PyAny>(#ptrs)
+            ),)*) {
+                #body
+            }
+        }
+    }
+
+    /// Calls the method with `_slf` bound to `ptr` and returns the result as a python object
+    fn call_and_return(&self, cls: &syn::Type, ptr: &TokenStream) -> TokenStream {
+        let slf = self.bind_slf(cls, ptr, &quote!(::std::ptr::null_mut()));
+        let call = self.call(cls);
+        quote! {
+            #slf
+            let _result = pyo3::derive_utils::IntoPyResult::into_py_result(#call);
+            return pyo3::callback::cb_convert(
+                pyo3::callback::PyObjectCallbackConverter, _py, _result);
+        }
+    }
+
+    fn check_args(&self, sig: &syn::Signature, slot: &str) -> syn::Result<()> {
+        let count = self.arg_names().len();
+        let expected: &[usize] = match self.kind {
+            SlotKind::Unary
+            | SlotKind::IterNext
+            | SlotKind::Hash
+            | SlotKind::Bool
+            | SlotKind::Len => &[0],
+            SlotKind::Set | SlotKind::RichCompare => &[2],
+            SlotKind::Number | SlotKind::Reflected if slot == "Pow" => &[1, 2],
+            _ => &[1],
+        };
+        if expected.contains(&count) {
+            Ok(())
+        } else {
+            let expected: Vec<String> = expected.iter().map(|n| n.to_string()).collect();
+            Err(syn::Error::new_spanned(
+                sig,
+                format!(
+                    "{} must take {} argument(s) besides self",
+                    self.name.unraw(),
+                    expected.join(" or ")
+                ),
+            ))
+        }
+    }
+}
+
+/// Generates the `PyMethodDefType::Slot` definitions of the dunder methods of a
+/// `#[pymethods]` block, together with their stubs
+///
+/// Methods that share a slot, e.g. `__add__` and `__radd__` or `__setitem__` and
+/// `__delitem__`, are combined into a single wrapper.
+pub fn impl_slots(
+    cls: &syn::Type,
+    methods: Vec<&mut syn::ImplItemMethod>,
+) -> syn::Result<(Vec<TokenStream>, Vec<TokenStream>)> {
+    let mut slot_methods: Vec<(&'static str, SlotMethod)> = Vec::new();
+    let mut method_stubs = Vec::new();
+    for meth in methods {
+        let (slot, kind) =
+            slot_of(&meth.sig.ident.unraw().to_string()).expect("Checked by is_slot_method");
+        let spec = FnSpec::parse(&meth.sig.ident, &meth.sig, &mut meth.attrs)?;
+        let method = SlotMethod {
+            name: &meth.sig.ident,
+            spec,
+            kind,
+        };
+        method.check_args(&meth.sig, slot)?;
+        method_stubs.extend(stubs::proto_method_stubs(cls, &meth.sig));
+        slot_methods.push((slot, method));
+    }
+
+    let mut slots: Vec<&'static str> = Vec::new();
+    for (slot, _) in slot_methods.iter() {
+        if !slots.contains(slot) {
+            slots.push(slot);
+        }
+    }
+
+    let mut defs = Vec::new();
+    for slot in slots {
+        let group: Vec<&SlotMethod> = slot_methods
+            .iter()
+            .filter(|(s, _)| *s == slot)
+            .map(|(_, method)| method)
+            .collect();
+        let wrapper = match group[0].kind {
+            SlotKind::Set | SlotKind::Delete => impl_set_slot(cls, slot, &group),
+            SlotKind::RichCompare | SlotKind::Compare(_) => impl_richcompare_slot(cls, &group)?,
+            SlotKind::Number | SlotKind::Reflected => impl_number_slot(cls, slot, &group),
+            SlotKind::InPlace => impl_inplace_slot(cls, slot, group[0]),
+            SlotKind::GetAttr => impl_getattr_slot(cls, group[0]),
+            _ => impl_simple_slot(cls, group[0]),
+        };
+        let slot = syn::Ident::new(slot, Span::call_site());
+        defs.push(quote! {
+            pyo3::class::PyMethodDefType::Slot({
+                #wrapper
+
+                pyo3::class::PySlotDef::#slot(__wrap)
+            })
+        });
+    }
+    Ok((defs, method_stubs))
+}
+
+fn location(cls: &syn::Type, name: &syn::Ident) -> TokenStream {
+    quote! {
+        const _LOCATION: &'static str = concat!(stringify!(#cls), ".", stringify!(#name), "()");
+    }
+}
+
+/// Slots that call a single method with `self` and at most one argument
+fn impl_simple_slot(cls: &syn::Type, method: &SlotMethod) -> TokenStream {
+    let (converter, output, error) = match method.kind {
+        SlotKind::IterNext => (
+            quote!(IterNextConverter),
+            quote!(*mut pyo3::ffi::PyObject),
+            quote!(::std::ptr::null_mut()),
+        ),
+        SlotKind::Hash => (
+            quote!(HashConverter),
+            quote!(pyo3::ffi::Py_hash_t),
+            quote!(-1),
+        ),
+        SlotKind::Bool | SlotKind::Contains => (
+            quote!(BoolCallbackConverter),
+            quote!(pyo3::libc::c_int),
+            quote!(-1),
+        ),
+        SlotKind::Len => (
+            quote!(LenResultConverter),
+            quote!(pyo3::ffi::Py_ssize_t),
+            quote!(-1),
+        ),
+        _ => (
+            quote!(PyObjectCallbackConverter),
+            quote!(*mut pyo3::ffi::PyObject),
+            quote!(::std::ptr::null_mut()),
+        ),
+    };
+    let (params, ptrs) = match method.kind {
+        SlotKind::GetItem | SlotKind::Contains => (
+            quote!(_obj: *mut pyo3::ffi::PyObject, _arg: *mut pyo3::ffi::PyObject),
+            vec![quote!(_arg)],
+        ),
+        _ => (quote!(_obj: *mut pyo3::ffi::PyObject), vec![]),
+    };
+    let location = location(cls, method.name);
+    let args = method.extract_or_return(&ptrs, &error);
+    let slf = method.bind_slf(cls, &quote!(_obj), &error);
+    let call = method.call(cls);
+
+    quote! {
+        unsafe extern "C" fn __wrap(#params) -> #output {
+            #location
+            let _py = pyo3::Python::assume_gil_acquired();
+            let _pool = pyo3::GILPool::new(_py);
+            pyo3::callback::handle_panic(_py, || {
+                #args
+                #slf
+                let _result = pyo3::derive_utils::IntoPyResult::into_py_result(#call);
+                pyo3::callback::cb_convert(pyo3::callback::#converter, _py, _result)
+            })
+        }
+    }
+}
+
+/// `__getattr__`, which python only calls for attributes that don't exist otherwise
+fn impl_getattr_slot(cls: &syn::Type, method: &SlotMethod) -> TokenStream {
+    let location = location(cls, method.name);
+    let error = quote!(::std::ptr::null_mut());
+    let args = method.extract_or_return(&[quote!(_name)], &error);
+    let slf = method.bind_slf(cls, &quote!(_obj), &error);
+    let call = method.call(cls);
+
+    quote! {
+        unsafe extern "C" fn __wrap(
+            _obj: *mut pyo3::ffi::PyObject,
+            _name: *mut pyo3::ffi::PyObject,
+        ) -> *mut pyo3::ffi::PyObject {
+            #location
+            let _py = pyo3::Python::assume_gil_acquired();
+            let _pool = pyo3::GILPool::new(_py);
+            pyo3::callback::handle_panic(_py, || {
+                let _attr = pyo3::ffi::PyObject_GenericGetAttr(_obj, _name);
+                if !_attr.is_null()
+                    || pyo3::ffi::PyErr_ExceptionMatches(pyo3::ffi::PyExc_AttributeError) == 0
+                {
+                    return _attr;
+                }
+                pyo3::ffi::PyErr_Clear();
+                #args
+                #slf
+                let _result = pyo3::derive_utils::IntoPyResult::into_py_result(#call);
+                pyo3::callback::cb_convert(pyo3::callback::PyObjectCallbackConverter, _py, _result)
+            })
+        }
+    }
+}
+
+/// `__setitem__` with `__delitem__` and `__setattr__` with `__delattr__`, where python
+/// passes a null value for deletion
+fn impl_set_slot(cls: &syn::Type, slot: &str, group: &[&SlotMethod]) -> TokenStream {
+    let error = quote!(-1);
+    let branch = |kind: SlotKind, ptrs: &[TokenStream], missing: TokenStream| match group
+        .iter()
+        .find(|method| method.kind == kind)
+    {
+        Some(method) => {
+            let args = method.extract_or_return(ptrs, &error);
+            let slf = method.bind_slf(cls, &quote!(_obj), &error);
+            let call = method.call(cls);
+            quote! {{
+                #args
+                #slf
+                pyo3::derive_utils::IntoPyResult::into_py_result(#call)
+            }}
+        }
+        None => missing,
+    };
+    let (missing_set, missing_delete) = if slot == "SetAttr" {
+        let generic = quote!(return pyo3::ffi::PyObject_GenericSetAttr(_obj, _key, _value));
+        (generic.clone(), generic)
+    } else {
+        (
+            quote! {
+                Err(pyo3::exceptions::NotImplementedError::py_err(
+                    concat!("Subscript assignment not supported by ", stringify!(#cls))
+                ))
+            },
+            quote! {
+                Err(pyo3::exceptions::NotImplementedError::py_err(
+                    concat!("Subscript deletion not supported by ", stringify!(#cls))
+                ))
+            },
+        )
+    };
+    let set = branch(SlotKind::Set, &[quote!(_key), quote!(_value)], missing_set);
+    let delete = branch(SlotKind::Delete, &[quote!(_key)], missing_delete);
+    let location = location(cls, group[0].name);
+
+    quote! {
+        unsafe extern "C" fn __wrap(
+            _obj: *mut pyo3::ffi::PyObject,
+            _key: *mut pyo3::ffi::PyObject,
+            _value: *mut pyo3::ffi::PyObject,
+        ) -> pyo3::libc::c_int {
+            #location
+            let _py = pyo3::Python::assume_gil_acquired();
+            let _pool = pyo3::GILPool::new(_py);
+            pyo3::callback::handle_panic(_py, || {
+                let _result = if _value.is_null() {
+                    #delete
+                } else {
+                    #set
+                };
+                pyo3::callback::cb_convert(pyo3::callback::UnitCallbackConverter, _py, _result)
+            })
+        }
+    }
+}
+
+/// `__richcmp__` or the separate comparison methods, returning `NotImplemented` for
+/// missing methods and operands of other types
+fn impl_richcompare_slot(cls: &syn::Type, group: &[&SlotMethod]) -> syn::Result<TokenStream> {
+    let body = if let Some(method) = group.iter().find(|m| m.kind == SlotKind::RichCompare) {
+        if let Some(other) = group.iter().find(|m| m.kind != SlotKind::RichCompare) {
+            return Err(syn::Error::new_spanned(
+                other.name,
+                "__richcmp__ can't be combined with the separate comparison methods",
+            ));
+        }
+        let call = method.call_and_return(cls, &quote!(_obj));
+        method.extract_or_skip(
+            &[quote!(_other)],
+            quote! {
+                let arg1 = _op;
+                #call
+            },
+        )
+    } else {
+        let arms = group.iter().map(|method| {
+            let op = match method.kind {
+                SlotKind::Compare(op) => syn::Ident::new(op, Span::call_site()),
+                _ => unreachable!(),
+            };
+            let call = method.call_and_return(cls, &quote!(_obj));
+            let body = method.extract_or_skip(&[quote!(_other)], call);
+            quote! {
+                pyo3::class::basic::CompareOp::#op => #body
+            }
+        });
+        quote! {
+            match _op {
+                #(#arms)*
+                _ => (),
+            }
+        }
+    };
+    let location = location(cls, group[0].name);
+
+    Ok(quote! {
+        unsafe extern "C" fn __wrap(
+            _obj: *mut pyo3::ffi::PyObject,
+            _other: *mut pyo3::ffi::PyObject,
+            _op: pyo3::libc::c_int,
+        ) -> *mut pyo3::ffi::PyObject {
+            #location
+            let _py = pyo3::Python::assume_gil_acquired();
+            let _pool = pyo3::GILPool::new(_py);
+            pyo3::callback::handle_panic(_py, || {
+                let _op = match pyo3::class::basic::extract_op(_op) {
+                    Ok(op) => op,
+                    Err(e) => {
+                        e.restore(_py);
+                        return ::std::ptr::null_mut();
+                    }
+                };
+                #body
+                pyo3::IntoPyPointer::into_ptr(_py.NotImplemented())
+            })
+        }
+    })
+}
+
+/// A binary number operator together with its reflected version
+///
+/// Python calls the slot with the operands in their original order, so the operator is
+/// tried if the left operand is an instance of the class and the reflected version if
+/// the right operand is.
+fn impl_number_slot(cls: &syn::Type, slot: &str, group: &[&SlotMethod]) -> TokenStream {
+    let ternary = slot == "Pow";
+    let operand = |kind: SlotKind, slf: TokenStream, other: TokenStream| {
+        group
+            .iter()
+            .find(|method| method.kind == kind)
+            .map(|method| {
+                let ptrs = [other, quote!(_modulo)];
+                let body = method.extract_or_skip(
+                    &ptrs[..method.arg_names().len()],
+                    method.call_and_return(cls, &slf),
+                );
+                // `pow(a, b, c)` must not silently ignore `c` if the method can't take it
+                let check_modulo = if ternary && method.arg_names().len() == 1 {
+                    let msg = format!("{}() takes no modulo argument", method.name.unraw());
+                    quote! {
+                        if _modulo != pyo3::ffi::Py_None() {
+                            pyo3::exceptions::TypeError::py_err(#msg).restore(_py);
+                            return ::std::ptr::null_mut();
+                        }
+                    }
+                } else {
+                    quote!()
+                };
+                quote! {
+                    if <#cls as pyo3::type_object::PyTypeInfo>::is_instance(
+                        _py.from_borrowed_ptr::<pyo3::types::PyAny>(#slf)
+                    ) {
+                        #check_modulo
+                        #body
+                    }
+                }
+            })
+    };
+    let forward = operand(SlotKind::Number, quote!(_lhs), quote!(_rhs));
+    let reflected = operand(SlotKind::Reflected, quote!(_rhs), quote!(_lhs));
+    let modulo = if ternary {
+        quote!(, _modulo: *mut pyo3::ffi::PyObject)
+    } else {
+        quote!()
+    };
+    let location = location(cls, group[0].name);
+
+    quote! {
+        unsafe extern "C" fn __wrap(
+            _lhs: *mut pyo3::ffi::PyObject,
+            _rhs: *mut pyo3::ffi::PyObject
+            #modulo
+        ) -> *mut pyo3::ffi::PyObject {
+            #location
+            let _py = pyo3::Python::assume_gil_acquired();
+            let _pool = pyo3::GILPool::new(_py);
+            pyo3::callback::handle_panic(_py, || {
+                #forward
+                #reflected
+                pyo3::IntoPyPointer::into_ptr(_py.NotImplemented())
+            })
+        }
+    }
+}
+
+/// An in-place operator, which modifies `self` and returns it
+fn impl_inplace_slot(cls: &syn::Type, slot: &str, method: &SlotMethod) -> TokenStream {
+    // The in-place operator only takes the other operand, so like in `impl_number_slot`, a
+    // modulo must not be silently ignored
+    let (modulo, check_modulo) = if slot == "IPow" {
+        let msg = format!("{}() takes no modulo argument", method.name.unraw());
+        (
+            quote!(, _modulo: *mut pyo3::ffi::PyObject),
+            quote! {
+                if _modulo != pyo3::ffi::Py_None() {
+                    pyo3::exceptions::TypeError::py_err(#msg).restore(_py);
+                    return ::std::ptr::null_mut();
+                }
+            },
+        )
+    } else {
+        (quote!(), quote!())
+    };
+    let slf = method.bind_slf(cls, &quote!(_obj), &quote!(::std::ptr::null_mut()));
+    let call = method.call(cls);
+    let body = method.extract_or_skip(
+        &[quote!(_other)],
+        quote! {
+            #slf
+            let _result: pyo3::PyResult<()> =
+                pyo3::derive_utils::IntoPyResult::into_py_result(#call);
+            return match _result {
+                Ok(()) => {
+                    pyo3::ffi::Py_INCREF(_obj);
+                    _obj
+                }
+                Err(e) => {
+                    e.restore(_py);
+                    ::std::ptr::null_mut()
+                }
+            };
+        },
+    );
+    let location = location(cls, method.name);
+
+    quote! {
+        unsafe extern "C" fn __wrap(
+            _obj: *mut pyo3::ffi::PyObject,
+            _other: *mut pyo3::ffi::PyObject
+            #modulo
+        ) -> *mut pyo3::ffi::PyObject {
+            #location
+            let _py = pyo3::Python::assume_gil_acquired();
+            let _pool = pyo3::GILPool::new(_py);
+            pyo3::callback::handle_panic(_py, || {
+                #check_modulo
+                #body
+                pyo3::IntoPyPointer::into_ptr(_py.NotImplemented())
+            })
+        }
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! The type information that is collected for `pyo3::stubs`
+
+use crate::method::{FnSpec, FnType};
+use crate::utils;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+
+/// A function pointer that returns the python annotation of `ty`
+///
+/// References are wrapped in `pyo3::stubs::Ref` and lifetimes are replaced with `'static`,
+/// since neither changes the annotation. `Self` is replaced by the class, while types that
+/// can't be named outside of their impl block, e.g. `Self::Item` or `impl Trait`, are
+/// annotated as `Any`.
+pub fn type_hint(ty: &syn::Type, cls: Option<&syn::Type>) -> TokenStream {
+    match normalize_type(ty, cls) {
+        Some(ty) => quote!(<#ty as pyo3::stubs::TypeHint>::type_hint),
+        None => quote!(<pyo3::PyObject as pyo3::stubs::TypeHint>::type_hint),
+    }
+}
+
+fn normalize_type(ty: &syn::Type, cls: Option<&syn::Type>) -> Option<syn::Type> {
+    Some(match ty {
+        syn::Type::Reference(reference) => {
+            let elem = normalize_type(&reference.elem, cls)?;
+            syn::parse_quote!(pyo3::stubs::Ref<#elem>)
+        }
+        syn::Type::Path(path) if path.qself.is_none() => {
+            if path.path.is_ident("Self") {
+                return cls.cloned();
+            }
+            let mut path = path.clone();
+            for segment in path.path.segments.iter_mut() {
+                if segment.ident == "Self" {
+                    return None;
+                }
+                match segment.arguments {
+                    syn::PathArguments::AngleBracketed(ref mut args) => {
+                        for arg in args.args.iter_mut() {
+                            match arg {
+                                syn::GenericArgument::Type(ty) => *ty = normalize_type(ty, cls)?,
+                                syn::GenericArgument::Lifetime(lifetime) => {
+                                    *lifetime = syn::parse_quote!('static)
+                                }
+                                _ => return None,
+                            }
+                        }
+                    }
+                    syn::PathArguments::Parenthesized(_) => return None,
+                    syn::PathArguments::None => (),
+                }
+            }
+            syn::Type::Path(path)
+        }
+        syn::Type::Tuple(tuple) => {
+            let mut tuple = tuple.clone();
+            for elem in tuple.elems.iter_mut() {
+                *elem = normalize_type(elem, cls)?;
+            }
+            syn::Type::Tuple(tuple)
+        }
+        syn::Type::Slice(slice) => {
+            let elem = normalize_type(&slice.elem, cls)?;
+            syn::parse_quote!([#elem])
+        }
+        syn::Type::Paren(paren) => normalize_type(&paren.elem, cls)?,
+        syn::Type::Group(group) => normalize_type(&group.elem, cls)?,
+        _ => return None,
+    })
+}
+
+/// The `pyo3::stubs::FunctionStub` of a function or method, or `None` for the kinds of methods
+/// that aren't shown in stubs
+pub fn function_stub(
+    name: &str,
+    spec: &FnSpec<'_>,
+    cls: Option<&syn::Type>,
+) -> Option<TokenStream> {
+    let kind = match spec.tp {
+        FnType::Fn | FnType::PySelf(_) if cls.is_none() => quote!(Function),
+        FnType::Fn | FnType::PySelf(_) => quote!(Method),
+        FnType::FnClass => quote!(ClassMethod),
+        FnType::FnStatic => quote!(StaticMethod),
+        FnType::FnNew => quote!(New),
+        FnType::FnCall => quote!(Call),
+        FnType::Getter(_) => quote!(Getter),
+        FnType::Setter(_) => quote!(Setter),
+        FnType::ClassAttribute => quote!(ClassAttribute),
+        FnType::FnInit => return None,
+    };
+    let name = match spec.tp {
+        FnType::Getter(Some(ref name)) | FnType::Setter(Some(ref name)) => name.clone(),
+        FnType::Getter(None) if name.starts_with("get_") => name[4..].to_string(),
+        FnType::Setter(None) if name.starts_with("set_") => name[4..].to_string(),
+        _ => name.to_string(),
+    };
+
+    let mut arguments = Vec::new();
+    for arg in spec.args.iter() {
+        if arg.py {
+            continue;
+        }
+        let kind = if spec.is_args(arg.name) {
+            quote!(VarArgs)
+        } else if spec.is_kwargs(arg.name) {
+            quote!(KeywordArgs)
+        } else if spec.is_kw_only(arg.name) {
+            quote!(KeywordOnly)
+        } else {
+            quote!(Positional)
+        };
+        let arg_name = arg.name.to_string();
+        let annotation = type_hint(arg.ty, cls);
+        let has_default = arg.optional.is_some() || spec.default_value(arg.name).is_some();
+        arguments.push(quote! {
+            pyo3::stubs::ArgumentStub {
+                name: #arg_name,
+                kind: pyo3::stubs::ArgumentKind::#kind,
+                annotation: #annotation,
+                has_default: #has_default,
+            }
+        });
+    }
+
+    let output = match spec.output {
+        syn::Type::Infer(_) => quote!(<() as pyo3::stubs::TypeHint>::type_hint),
+        ref output => type_hint(output, cls),
+    };
+
+    Some(quote! {
+        pyo3::stubs::FunctionStub {
+            name: #name,
+            kind: pyo3::stubs::FunctionKind::#kind,
+            arguments: vec![#(#arguments),*],
+            output: #output,
+        }
+    })
+}
+
+/// The `pyo3::stubs::FunctionStub` of a `#[classattr]` associated const
+pub fn class_attribute_stub(name: &str, ty: &syn::Type, cls: &syn::Type) -> TokenStream {
+    let output = type_hint(ty, Some(cls));
+    quote! {
+        pyo3::stubs::FunctionStub {
+            name: #name,
+            kind: pyo3::stubs::FunctionKind::ClassAttribute,
+            arguments: Vec::new(),
+            output: #output,
+        }
+    }
+}
+
+/// The stubs of a `#[pyproto]` method
+///
+/// Binary operators receive the left operand instead of `self`, which is shown as `self`
+/// nonetheless, and `__richcmp__` is shown as the comparison methods python actually calls.
+pub fn proto_method_stubs(cls: &syn::Type, sig: &syn::Signature) -> Vec<TokenStream> {
+    let name = sig.ident.to_string();
+    if !name.starts_with("__") || name == "__traverse__" || name == "__clear__" {
+        return Vec::new();
+    }
+
+    let mut has_self = false;
+    let mut arguments = Vec::new();
+    for input in sig.inputs.iter() {
+        match input {
+            syn::FnArg::Receiver(_) => has_self = true,
+            // The receiver of e.g. `__iter__(slf: PyRefMut<Self>)` or the left operand
+            syn::FnArg::Typed(_) if !has_self => has_self = true,
+            syn::FnArg::Typed(arg) => {
+                if utils::if_type_is_python(&arg.ty) {
+                    continue;
+                }
+                let arg_name = match *arg.pat {
+                    syn::Pat::Ident(ref pat) => pat.ident.unraw().to_string(),
+                    _ => "arg".to_string(),
+                };
+                let annotation = type_hint(&arg.ty, Some(cls));
+                arguments.push(quote! {
+                    pyo3::stubs::ArgumentStub {
+                        name: #arg_name,
+                        kind: pyo3::stubs::ArgumentKind::Positional,
+                        annotation: #annotation,
+                        has_default: false,
+                    }
+                });
+            }
+        }
+    }
+
+    let output = match sig.output {
+        syn::ReturnType::Default => quote!(<() as pyo3::stubs::TypeHint>::type_hint),
+        syn::ReturnType::Type(_, ref output) => type_hint(output, Some(cls)),
+    };
+
+    let names = if name == "__richcmp__" {
+        // The last argument is the `CompareOp`
+        arguments.pop();
+        vec!["__eq__", "__ne__", "__lt__", "__le__", "__gt__", "__ge__"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    } else {
+        vec![name]
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            quote! {
+                pyo3::stubs::FunctionStub {
+                    name: #name,
+                    kind: pyo3::stubs::FunctionKind::Method,
+                    arguments: vec![#(#arguments),*],
+                    output: #output,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Submits the stubs of the methods of a `#[pymethods]` or `#[pyproto]` block
+pub fn submit_methods(cls: &syn::Type, methods: &[TokenStream]) -> TokenStream {
+    quote! {
+        pyo3::inventory::submit! {
+            #![crate = pyo3]
+            pyo3::stubs::TypeStub::Methods(pyo3::stubs::MethodsStub {
+                type_object: <#cls as pyo3::type_object::PyTypeObject>::type_object,
+                methods: vec![#(#methods),*],
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::normalize_type;
+    use syn::parse_quote;
+
+    fn normalize(ty: syn::Type) -> Option<syn::Type> {
+        normalize_type(&ty, Some(&parse_quote!(MyClass)))
+    }
+
+    #[test]
+    fn test_normalize_type() {
+        assert_eq!(
+            normalize(parse_quote!(&'a str)),
+            Some(parse_quote!(pyo3::stubs::Ref<str>))
+        );
+        assert_eq!(
+            normalize(parse_quote!(Option<Vec<(&mut PyAny, &[u8])>>)),
+            Some(parse_quote!(
+                Option<Vec<(pyo3::stubs::Ref<PyAny>, pyo3::stubs::Ref<[u8]>)>>
+            ))
+        );
+        assert_eq!(
+            normalize(parse_quote!(PyRef<'p, Self>)),
+            Some(parse_quote!(PyRef<'static, MyClass>))
+        );
+        assert_eq!(normalize(parse_quote!(Self::Item)), None);
+        assert_eq!(normalize(parse_quote!(impl Iterator<Item = u8>)), None);
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
-use proc_macro2::Span;
-use proc_macro2::TokenStream;
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::ToTokens;
 
 pub fn print_err(msg: String, t: TokenStream) {
     println!("Error: {} in '{}'", msg, t.to_string());
@@ -52,3 +52,239 @@ pub fn get_doc(attrs: &[syn::Attribute], null_terminated: bool) -> syn::Lit {
 
     syn::Lit::Str(syn::LitStr::new(&docstr, Span::call_site()))
 }
+
+/// The `#[text_signature = "(a, b)"]` or `#[text_signature = false]` attribute of a function
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextSignatureAttr {
+    /// Don't generate a signature
+    Disabled,
+    /// Use the given signature instead of the generated one
+    Override(syn::LitStr),
+}
+
+/// Removes `#[text_signature = ...]` from the attributes and parses it
+pub fn take_text_signature_attr(
+    attrs: &mut Vec<syn::Attribute>,
+) -> syn::Result<Option<TextSignatureAttr>> {
+    let mut text_signature = None;
+    let mut new_attrs = Vec::new();
+    for attr in attrs.drain(..) {
+        if !attr.path.is_ident("text_signature") {
+            new_attrs.push(attr);
+            continue;
+        }
+        let parsed = match attr.parse_meta()? {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(ref lit),
+                ..
+            }) if lit.value().starts_with('(') && lit.value().ends_with(')') => {
+                TextSignatureAttr::Override(lit.clone())
+            }
+            syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Bool(ref lit),
+                ..
+            }) if !lit.value => TextSignatureAttr::Disabled,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected #[text_signature = \"(...)\"] or #[text_signature = false]",
+                ))
+            }
+        };
+        if text_signature.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[text_signature] can only be used once",
+            ));
+        }
+        text_signature = Some(parsed);
+    }
+    *attrs = new_attrs;
+    Ok(text_signature)
+}
+
+/// Prepends `name(signature)\n--\n\n` to a docstring, which CPython parses
+/// into `__text_signature__`. Docstrings that already start with a signature are kept as they are.
+pub fn add_text_signature(doc: syn::Lit, text_signature: Option<String>) -> syn::Lit {
+    match (doc, text_signature) {
+        (syn::Lit::Str(ref doc), Some(text_signature)) => {
+            let name = &text_signature[..text_signature.find('(').unwrap_or(0) + 1];
+            let value = doc.value();
+            if value.starts_with(name) && value.contains(")\n--\n\n") {
+                return syn::Lit::Str(doc.clone());
+            }
+            syn::Lit::Str(syn::LitStr::new(
+                &format!("{}\n--\n\n{}", text_signature, value),
+                doc.span(),
+            ))
+        }
+        (doc, _) => doc,
+    }
+}
+
+/// Replaces the generic type parameters in `ty` with the concrete types of an instantiation
+pub fn substitute_type_params(ty: &syn::Type, params: &[(syn::Ident, syn::Type)]) -> syn::Type {
+    let tokens = substitute_tokens(ty.to_token_stream(), params);
+    syn::parse2(tokens).expect("Substituting type parameters must produce a valid type")
+}
+
+fn substitute_tokens(tokens: TokenStream, params: &[(syn::Ident, syn::Type)]) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Ident(ref ident) => match params.iter().find(|(param, _)| param == ident) {
+                Some((_, ty)) => ty.to_token_stream(),
+                None => token.into(),
+            },
+            TokenTree::Group(ref group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), substitute_tokens(group.stream(), params));
+                new_group.set_span(group.span());
+                TokenTree::Group(new_group).into()
+            }
+            _ => token.into(),
+        })
+        .collect()
+}
+
+/// Matches the generic arguments of `concrete`, e.g. `Matrix<f32>`, with the type parameters
+/// in `generic`, e.g. `Matrix<T>`
+pub fn match_type_params(
+    generic: &syn::Type,
+    concrete: &syn::Type,
+    type_params: &[syn::Ident],
+) -> syn::Result<Vec<(syn::Ident, syn::Type)>> {
+    let err = || {
+        syn::Error::new_spanned(
+            concrete,
+            format!(
+                "expected an instantiation of `{}` with concrete types",
+                generic.to_token_stream()
+            ),
+        )
+    };
+
+    let (generic_ident, generic_args) = split_type_args(generic).ok_or_else(err)?;
+    let (concrete_ident, concrete_args) = split_type_args(concrete).ok_or_else(err)?;
+    if generic_ident != concrete_ident || generic_args.len() != concrete_args.len() {
+        return Err(err());
+    }
+
+    let mut params = Vec::new();
+    for (generic_arg, concrete_arg) in generic_args.into_iter().zip(concrete_args) {
+        match generic_arg {
+            syn::Type::Path(ref path) if path.qself.is_none() => match path.path.get_ident() {
+                Some(ident) if type_params.contains(ident) => {
+                    params.push((ident.clone(), concrete_arg))
+                }
+                _ => return Err(err()),
+            },
+            _ => return Err(err()),
+        }
+    }
+
+    for param in type_params {
+        if !params.iter().any(|(ident, _)| ident == param) {
+            return Err(syn::Error::new_spanned(
+                param,
+                "every type parameter must appear in the instantiated type",
+            ));
+        }
+    }
+    Ok(params)
+}
+
+/// Splits `Name<A, B>` into `Name` and `[A, B]`
+fn split_type_args(ty: &syn::Type) -> Option<(syn::Ident, Vec<syn::Type>)> {
+    let segment = match ty {
+        syn::Type::Path(ref path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    let args = match segment.arguments {
+        syn::PathArguments::AngleBracketed(ref args) => args
+            .args
+            .iter()
+            .map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    Some((segment.ident.clone(), args))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        add_text_signature, match_type_params, substitute_type_params, take_text_signature_attr,
+        TextSignatureAttr,
+    };
+    use syn::parse_quote;
+
+    #[test]
+    fn test_text_signature_attr() {
+        let item: syn::ItemFn = parse_quote! {
+            #[text_signature = "(a, b=1)"]
+            #[inline]
+            fn f() {}
+        };
+        let mut attrs = item.attrs;
+        assert_eq!(
+            take_text_signature_attr(&mut attrs).unwrap(),
+            Some(TextSignatureAttr::Override(parse_quote!("(a, b=1)")))
+        );
+        assert_eq!(attrs.len(), 1);
+
+        let item: syn::ItemFn = parse_quote! {
+            #[text_signature = false]
+            fn f() {}
+        };
+        let mut attrs = item.attrs;
+        assert_eq!(
+            take_text_signature_attr(&mut attrs).unwrap(),
+            Some(TextSignatureAttr::Disabled)
+        );
+
+        let item: syn::ItemFn = parse_quote! {
+            #[text_signature = "a, b"]
+            fn f() {}
+        };
+        let mut attrs = item.attrs;
+        assert!(take_text_signature_attr(&mut attrs).is_err());
+    }
+
+    #[test]
+    fn test_add_text_signature() {
+        let signature = Some("add(a, b)".to_string());
+        let doc = add_text_signature(parse_quote!("Adds\0"), signature.clone());
+        assert_eq!(doc, parse_quote!("add(a, b)\n--\n\nAdds\0"));
+
+        let written = parse_quote!("add(a, b, /)\n--\n\nAdds\0");
+        assert_eq!(
+            add_text_signature(written, signature),
+            parse_quote!("add(a, b, /)\n--\n\nAdds\0")
+        );
+    }
+
+    #[test]
+    fn test_match_and_substitute() {
+        let generic: syn::Type = parse_quote!(Matrix<T, U>);
+        let concrete: syn::Type = parse_quote!(Matrix<f32, Vec<u8>>);
+        let params =
+            match_type_params(&generic, &concrete, &[parse_quote!(T), parse_quote!(U)]).unwrap();
+
+        let field: syn::Type = parse_quote!(Option<(T, Vec<U>)>);
+        let expected: syn::Type = parse_quote!(Option<(f32, Vec<Vec<u8>>)>);
+        assert_eq!(substitute_type_params(&field, &params), expected);
+    }
+
+    #[test]
+    fn test_match_errs() {
+        let generic: syn::Type = parse_quote!(Matrix<T>);
+        let params = [parse_quote!(T)];
+        assert!(match_type_params(&generic, &parse_quote!(Other<f32>), &params).is_err());
+        assert!(match_type_params(&generic, &parse_quote!(Matrix<f32, f64>), &params).is_err());
+        assert!(match_type_params(&generic, &parse_quote!(Matrix), &params).is_err());
+    }
+}
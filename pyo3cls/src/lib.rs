@@ -6,8 +6,9 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use pyo3_derive_backend::{
-    add_fn_to_module, build_py_class, build_py_methods, build_py_proto, get_doc,
-    process_functions_in_module, py_init, PyClassArgs, PyFunctionAttr,
+    add_fn_to_module, build_derive_from_pyobject, build_derive_into_pyobject, build_py_class,
+    build_py_enum, build_py_methods, build_py_proto, get_doc, process_functions_in_module, py_init,
+    py_init_multi_phase, PyClassArgs, PyFunctionAttr, PyMethodsArgs, PyModuleArgs,
 };
 use quote::quote;
 use syn::ext::IdentExt;
@@ -19,15 +20,17 @@ use syn::parse_macro_input;
 pub fn pymodule(attr: TokenStream, input: TokenStream) -> TokenStream {
     let mut ast = parse_macro_input!(input as syn::ItemFn);
 
-    let modname = if attr.is_empty() {
-        ast.sig.ident.clone()
-    } else {
-        parse_macro_input!(attr as syn::Ident)
-    };
+    let args = parse_macro_input!(attr as PyModuleArgs);
+    let modname = args.name.unwrap_or_else(|| ast.sig.ident.clone());
 
     process_functions_in_module(&mut ast);
 
-    let expanded = py_init(&ast.sig.ident, &modname, get_doc(&ast.attrs, false));
+    let doc = get_doc(&ast.attrs, false);
+    let expanded = if args.multi_phase {
+        py_init_multi_phase(&ast.sig.ident, &modname, doc)
+    } else {
+        py_init(&ast.sig.ident, &modname, doc)
+    };
 
     quote!(
         #ast
@@ -50,9 +53,17 @@ pub fn pyproto(_: TokenStream, input: TokenStream) -> TokenStream {
 
 #[proc_macro_attribute]
 pub fn pyclass(attr: TokenStream, input: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(input as syn::ItemStruct);
+    let mut ast = parse_macro_input!(input as syn::Item);
     let args = parse_macro_input!(attr as PyClassArgs);
-    let expanded = build_py_class(&mut ast, &args).unwrap_or_else(|e| e.to_compile_error());
+    let expanded = match ast {
+        syn::Item::Struct(ref mut class) => build_py_class(class, &args),
+        syn::Item::Enum(ref mut enum_) => build_py_enum(enum_, &args),
+        _ => Err(syn::Error::new_spanned(
+            &ast,
+            "#[pyclass] can only be used on structs and enums",
+        )),
+    }
+    .unwrap_or_else(|e| e.to_compile_error());
 
     quote!(
         #ast
@@ -62,9 +73,10 @@ pub fn pyclass(attr: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn pymethods(_: TokenStream, input: TokenStream) -> TokenStream {
+pub fn pymethods(attr: TokenStream, input: TokenStream) -> TokenStream {
     let mut ast = parse_macro_input!(input as syn::ItemImpl);
-    let expanded = build_py_methods(&mut ast).unwrap_or_else(|e| e.to_compile_error());
+    let args = parse_macro_input!(attr as PyMethodsArgs);
+    let expanded = build_py_methods(&mut ast, &args).unwrap_or_else(|e| e.to_compile_error());
 
     quote!(
         #ast
@@ -75,11 +87,11 @@ pub fn pymethods(_: TokenStream, input: TokenStream) -> TokenStream {
 
 #[proc_macro_attribute]
 pub fn pyfunction(attr: TokenStream, input: TokenStream) -> TokenStream {
-    let ast = parse_macro_input!(input as syn::ItemFn);
+    let mut ast = parse_macro_input!(input as syn::ItemFn);
     let args = parse_macro_input!(attr as PyFunctionAttr);
 
     let python_name = syn::Ident::new(&ast.sig.ident.unraw().to_string(), Span::call_site());
-    let expanded = add_fn_to_module(&ast, &python_name, args.arguments);
+    let expanded = add_fn_to_module(&mut ast, &python_name, args.arguments);
 
     quote!(
         #ast
@@ -87,3 +99,25 @@ pub fn pyfunction(attr: TokenStream, input: TokenStream) -> TokenStream {
     )
     .into()
 }
+
+#[proc_macro_derive(FromPyObject, attributes(pyo3))]
+pub fn derive_from_py_object(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    let expanded = build_derive_from_pyobject(&ast).unwrap_or_else(|e| e.to_compile_error());
+
+    quote!(
+        #expanded
+    )
+    .into()
+}
+
+#[proc_macro_derive(IntoPyObject, attributes(pyo3))]
+pub fn derive_into_py_object(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    let expanded = build_derive_into_pyobject(&ast).unwrap_or_else(|e| e.to_compile_error());
+
+    quote!(
+        #expanded
+    )
+    .into()
+}
@@ -3,11 +3,12 @@
 //! Utilities for a Python callable object that invokes a Rust function.
 
 use crate::err::PyResult;
-use crate::exceptions::OverflowError;
+use crate::exceptions::{OverflowError, PanicException};
 use crate::ffi::{self, Py_hash_t};
 use crate::IntoPyPointer;
 use crate::{IntoPy, PyObject, Python};
 use std::os::raw::c_int;
+use std::panic::{self, AssertUnwindSafe};
 use std::{isize, ptr};
 
 pub trait CallbackConverter<S> {
@@ -87,6 +88,31 @@ impl CallbackConverter<()> for UnitCallbackConverter {
     }
 }
 
+/// Converts the result of `__next__`, where `None` raises `StopIteration`
+pub struct IterNextConverter;
+
+impl<T> CallbackConverter<Option<T>> for IterNextConverter
+where
+    T: IntoPy<PyObject>,
+{
+    type R = *mut ffi::PyObject;
+
+    fn convert(val: Option<T>, py: Python) -> *mut ffi::PyObject {
+        match val {
+            Some(val) => val.into_py(py).into_ptr(),
+            None => unsafe {
+                ffi::PyErr_SetNone(ffi::PyExc_StopIteration);
+                ptr::null_mut()
+            },
+        }
+    }
+
+    #[inline]
+    fn error_value() -> *mut ffi::PyObject {
+        ptr::null_mut()
+    }
+}
+
 pub trait WrappingCastTo<T> {
     fn wrapping_cast(self) -> T;
 }
@@ -149,3 +175,40 @@ where
         }
     }
 }
+
+/// The return type of a function called by python, which has a value that signals that an
+/// exception was raised
+pub trait PyCallbackOutput: Copy {
+    const ERR_VALUE: Self;
+}
+
+impl PyCallbackOutput for *mut ffi::PyObject {
+    const ERR_VALUE: Self = ptr::null_mut();
+}
+
+impl PyCallbackOutput for c_int {
+    const ERR_VALUE: Self = -1;
+}
+
+impl PyCallbackOutput for ffi::Py_ssize_t {
+    const ERR_VALUE: Self = -1;
+}
+
+/// Runs the body of a function called by python.
+///
+/// Unwinding into the interpreter is undefined behavior, so a panic in `body` is caught and
+/// raised as a `PanicException` with the panic message instead.
+#[inline]
+pub fn handle_panic<F, R>(py: Python, body: F) -> R
+where
+    F: FnOnce() -> R,
+    R: PyCallbackOutput,
+{
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            PanicException::from_panic_payload(payload).restore(py);
+            R::ERR_VALUE
+        }
+    }
+}
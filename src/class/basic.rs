@@ -12,6 +12,7 @@ use crate::callback::{BoolCallbackConverter, HashConverter, PyObjectCallbackConv
 use crate::class::methods::PyMethodDef;
 use crate::err::{PyErr, PyResult};
 use crate::ffi;
+use crate::instance::PyRef;
 use crate::objectprotocol::ObjectProtocol;
 use crate::type_object::PyTypeInfo;
 use crate::types::PyAny;
@@ -217,25 +218,29 @@ where
         {
             let py = Python::assume_gil_acquired();
             let _pool = crate::GILPool::new(py);
+            crate::callback::handle_panic(py, || {
+                // Behave like python's __getattr__ (as opposed to __getattribute__) and check
+                // for existing fields and methods first
+                let existing = ffi::PyObject_GenericGetAttr(slf, arg);
+                if existing.is_null() {
+                    // PyObject_HasAttr also tries to get an object and clears the error if it fails
+                    ffi::PyErr_Clear();
+                } else {
+                    return existing;
+                }
 
-            // Behave like python's __getattr__ (as opposed to __getattribute__) and check
-            // for existing fields and methods first
-            let existing = ffi::PyObject_GenericGetAttr(slf, arg);
-            if existing.is_null() {
-                // PyObject_HasAttr also tries to get an object and clears the error if it fails
-                ffi::PyErr_Clear();
-            } else {
-                return existing;
-            }
-
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-            let arg = py.from_borrowed_ptr::<crate::types::PyAny>(arg);
-
-            let result = match arg.extract() {
-                Ok(arg) => slf.__getattr__(arg).into(),
-                Err(e) => Err(e),
-            };
-            crate::callback::cb_convert(PyObjectCallbackConverter, py, result)
+                let slf = PyRef::<T>::try_from_borrowed_ptr(py, slf);
+                let arg = py.from_borrowed_ptr::<crate::types::PyAny>(arg);
+
+                let result = match slf {
+                    Ok(ref slf) => match arg.extract() {
+                        Ok(arg) => slf.__getattr__(arg).into(),
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                crate::callback::cb_convert(PyObjectCallbackConverter, py, result)
+            })
         }
         Some(wrap::<T>)
     }
@@ -452,29 +457,36 @@ where
         {
             let py = Python::assume_gil_acquired();
             let _pool = crate::GILPool::new(py);
-            let slf = py.from_borrowed_ptr::<T>(slf);
-            let arg = py.from_borrowed_ptr::<PyAny>(arg);
-
-            let res = match extract_op(op) {
-                Ok(op) => match arg.extract() {
-                    Ok(arg) => slf.__richcmp__(arg, op).into(),
-                    Err(e) => Err(e),
-                },
-                Err(e) => Err(e),
-            };
-            match res {
-                Ok(val) => val.into_py(py).into_ptr(),
-                Err(e) => {
-                    e.restore(py);
-                    ptr::null_mut()
+            crate::callback::handle_panic(py, || {
+                let slf = PyRef::<T>::try_from_borrowed_ptr(py, slf);
+                let arg = py.from_borrowed_ptr::<PyAny>(arg);
+
+                let res = match slf {
+                    Ok(ref slf) => match extract_op(op) {
+                        Ok(op) => match arg.extract() {
+                            Ok(arg) => slf.__richcmp__(arg, op).into(),
+                            Err(e) => Err(e),
+                        },
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                match res {
+                    Ok(val) => val.into_py(py).into_ptr(),
+                    Err(e) => {
+                        e.restore(py);
+                        ptr::null_mut()
+                    }
                 }
-            }
+            })
         }
         Some(wrap::<T>)
     }
 }
 
-fn extract_op(op: c_int) -> PyResult<CompareOp> {
+/// Converts the `op` argument of `tp_richcompare`
+#[doc(hidden)]
+pub fn extract_op(op: c_int) -> PyResult<CompareOp> {
     match op {
         ffi::Py_LT => Ok(CompareOp::Lt),
         ffi::Py_LE => Ok(CompareOp::Le),
@@ -7,6 +7,7 @@
 use crate::callback::UnitCallbackConverter;
 use crate::err::PyResult;
 use crate::ffi;
+use crate::instance::PyRef;
 use crate::type_object::PyTypeInfo;
 use std::os::raw::c_int;
 
@@ -87,10 +88,15 @@ where
         {
             let py = crate::Python::assume_gil_acquired();
             let _pool = crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
+            crate::callback::handle_panic(py, || {
+                let slf = PyRef::<T>::try_from_borrowed_ptr(py, slf);
 
-            let result = slf.bf_getbuffer(arg1, arg2).into();
-            crate::callback::cb_convert(UnitCallbackConverter, py, result)
+                let result = match slf {
+                    Ok(ref slf) => slf.bf_getbuffer(arg1, arg2).into(),
+                    Err(e) => Err(e.into()),
+                };
+                crate::callback::cb_convert(UnitCallbackConverter, py, result)
+            })
         }
         Some(wrap::<T>)
     }
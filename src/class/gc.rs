@@ -3,11 +3,15 @@
 //! Python GC support
 //!
 
+use crate::exceptions::PanicException;
 use crate::ffi;
+use crate::instance::{PyRef, PyRefMut};
 use crate::type_object::PyTypeInfo;
 use crate::AsPyPointer;
 use crate::Python;
 use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
 
 #[repr(transparent)]
 pub struct PyTraverseError(c_int);
@@ -87,17 +91,36 @@ where
         {
             let py = Python::assume_gil_acquired();
             let _pool = crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-
-            let visit = PyVisit {
-                visit,
-                arg,
-                _py: py,
-            };
-            match slf.__traverse__(visit) {
-                Ok(()) => 0,
-                Err(PyTraverseError(code)) => code,
-            }
+            // The garbage collector doesn't expect `tp_traverse` to fail with an exception, so
+            // a panic is reported as unraisable and the instance is treated as fully visited
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                // An instance that is mutably borrowed is skipped, as its references can't be
+                // read while they may be changing
+                let slf = match PyRef::<T>::try_from_borrowed_ptr(py, slf) {
+                    Ok(slf) => slf,
+                    Err(_) => return 0,
+                };
+
+                let visit = PyVisit {
+                    visit,
+                    arg,
+                    _py: py,
+                };
+                match slf.__traverse__(visit) {
+                    Ok(()) => 0,
+                    Err(PyTraverseError(code)) => code,
+                }
+            }));
+            result.unwrap_or_else(|payload| {
+                // An exception that is currently being raised must survive the collection
+                let (mut ptype, mut pvalue, mut ptraceback) =
+                    (ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+                ffi::PyErr_Fetch(&mut ptype, &mut pvalue, &mut ptraceback);
+                PanicException::from_panic_payload(payload).restore(py);
+                ffi::PyErr_WriteUnraisable(slf);
+                ffi::PyErr_Restore(ptype, pvalue, ptraceback);
+                0
+            })
         }
 
         Some(tp_traverse::<T>)
@@ -124,10 +147,13 @@ where
         {
             let py = Python::assume_gil_acquired();
             let _pool = crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-
-            slf.__clear__();
-            0
+            crate::callback::handle_panic(py, || {
+                // A borrowed instance is in use, so it is left for a later collection
+                if let Ok(mut slf) = PyRefMut::<T>::try_from_borrowed_ptr(py, slf) {
+                    slf.__clear__();
+                }
+                0
+            })
         }
         Some(tp_clear::<T>)
     }
@@ -2,14 +2,11 @@
 //! Python Iterator Interface.
 //! Trait and support implementation for implementing iterators
 
-use crate::callback::{CallbackConverter, PyObjectCallbackConverter};
+use crate::callback::{IterNextConverter, PyObjectCallbackConverter};
 use crate::err::PyResult;
 use crate::instance::PyRefMut;
 use crate::type_object::PyTypeInfo;
-use crate::IntoPyPointer;
-use crate::Python;
-use crate::{ffi, IntoPy, PyObject};
-use std::ptr;
+use crate::{ffi, PyObject};
 
 /// Python Iterator Interface.
 ///
@@ -105,27 +102,3 @@ where
         )
     }
 }
-
-struct IterNextConverter;
-
-impl<T> CallbackConverter<Option<T>> for IterNextConverter
-where
-    T: IntoPy<PyObject>,
-{
-    type R = *mut ffi::PyObject;
-
-    fn convert(val: Option<T>, py: Python) -> *mut ffi::PyObject {
-        match val {
-            Some(val) => val.into_py(py).into_ptr(),
-            None => unsafe {
-                ffi::PyErr_SetNone(ffi::PyExc_StopIteration);
-                ptr::null_mut()
-            },
-        }
-    }
-
-    #[inline]
-    fn error_value() -> *mut ffi::PyObject {
-        ptr::null_mut()
-    }
-}
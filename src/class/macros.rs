@@ -19,9 +19,38 @@ macro_rules! py_unary_func {
         {
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-            let res = slf.$f().into();
-            $crate::callback::cb_convert($conv, py, res.map(|x| x))
+            $crate::callback::handle_panic(py, || {
+                let slf = $crate::PyRef::<T>::try_from_borrowed_ptr(py, slf);
+                let res = match slf {
+                    Ok(ref slf) => slf.$f().into(),
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, res.map(|x| x))
+            })
+        }
+        Some(wrap::<$class>)
+    }};
+}
+
+/// Like `py_unary_func`, but for methods that take `&mut self`
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_unary_refmut_func {
+    ($trait:ident, $class:ident :: $f:ident, $res_type:ty, $conv:expr) => {{
+        unsafe extern "C" fn wrap<T>(slf: *mut $crate::ffi::PyObject) -> *mut $crate::ffi::PyObject
+        where
+            T: for<'p> $trait<'p>,
+        {
+            let py = $crate::Python::assume_gil_acquired();
+            let _pool = $crate::GILPool::new(py);
+            $crate::callback::handle_panic(py, || {
+                let mut slf = $crate::PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+                let res = match slf {
+                    Ok(ref mut slf) => slf.$f().into(),
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, res.map(|x| x))
+            })
         }
         Some(wrap::<$class>)
     }};
@@ -35,12 +64,15 @@ macro_rules! py_unary_pyref_func {
         where
             T: for<'p> $trait<'p>,
         {
-            use $crate::instance::PyRefMut;
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-            let res = $class::$f(PyRefMut::from_mut(slf)).into();
-            $crate::callback::cb_convert($conv, py, res)
+            $crate::callback::handle_panic(py, || {
+                let res = match $crate::PyRefMut::<T>::try_from_borrowed_ptr(py, slf) {
+                    Ok(slf) => $class::$f(slf).into(),
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, res)
+            })
         }
         Some(wrap::<$class>)
     }};
@@ -56,10 +88,15 @@ macro_rules! py_len_func {
         {
             let py = Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
+            $crate::callback::handle_panic(py, || {
+                let slf = $crate::PyRef::<T>::try_from_borrowed_ptr(py, slf);
 
-            let result = slf.$f().into();
-            $crate::callback::cb_convert($conv, py, result)
+                let result = match slf {
+                    Ok(ref slf) => slf.$f().into(),
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
         }
         Some(wrap::<$class>)
     }};
@@ -86,14 +123,52 @@ macro_rules! py_binary_func {
             use $crate::ObjectProtocol;
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-            let arg = py.from_borrowed_ptr::<$crate::types::PyAny>(arg);
-
-            let result = match arg.extract() {
-                Ok(arg) => slf.$f(arg).into(),
-                Err(e) => Err(e.into()),
-            };
-            $crate::callback::cb_convert($conv, py, result)
+            $crate::callback::handle_panic(py, || {
+                let slf = $crate::PyRef::<T>::try_from_borrowed_ptr(py, slf);
+                let arg = py.from_borrowed_ptr::<$crate::types::PyAny>(arg);
+
+                let result = match slf {
+                    Ok(ref slf) => match arg.extract() {
+                        Ok(arg) => slf.$f(arg).into(),
+                        Err(e) => Err(e.into()),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
+        }
+        Some(wrap::<$class>)
+    }};
+}
+
+/// Like `py_binary_func`, but for methods that take `&mut self`
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_binary_refmut_func {
+    ($trait:ident, $class:ident :: $f:ident, $res_type:ty, $conv:expr) => {{
+        unsafe extern "C" fn wrap<T>(
+            slf: *mut ffi::PyObject,
+            arg: *mut ffi::PyObject,
+        ) -> *mut $crate::ffi::PyObject
+        where
+            T: for<'p> $trait<'p>,
+        {
+            use $crate::ObjectProtocol;
+            let py = $crate::Python::assume_gil_acquired();
+            let _pool = $crate::GILPool::new(py);
+            $crate::callback::handle_panic(py, || {
+                let mut slf = $crate::PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+                let arg = py.from_borrowed_ptr::<$crate::types::PyAny>(arg);
+
+                let result = match slf {
+                    Ok(ref mut slf) => match arg.extract() {
+                        Ok(arg) => slf.$f(arg).into(),
+                        Err(e) => Err(e.into()),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
         }
         Some(wrap::<$class>)
     }};
@@ -114,17 +189,19 @@ macro_rules! py_binary_num_func {
             use $crate::ObjectProtocol;
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let lhs = py.from_borrowed_ptr::<$crate::types::PyAny>(lhs);
-            let rhs = py.from_borrowed_ptr::<$crate::types::PyAny>(rhs);
+            $crate::callback::handle_panic(py, || {
+                let lhs = py.from_borrowed_ptr::<$crate::types::PyAny>(lhs);
+                let rhs = py.from_borrowed_ptr::<$crate::types::PyAny>(rhs);
 
-            let result = match lhs.extract() {
-                Ok(lhs) => match rhs.extract() {
-                    Ok(rhs) => $class::$f(lhs, rhs).into(),
+                let result = match lhs.extract() {
+                    Ok(lhs) => match rhs.extract() {
+                        Ok(rhs) => $class::$f(lhs, rhs).into(),
+                        Err(e) => Err(e.into()),
+                    },
                     Err(e) => Err(e.into()),
-                },
-                Err(e) => Err(e.into()),
-            };
-            $crate::callback::cb_convert($conv, py, result)
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
         }
         Some(wrap::<$class>)
     }};
@@ -146,23 +223,28 @@ macro_rules! py_binary_self_func {
 
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf1 = py.mut_from_borrowed_ptr::<T>(slf);
-            let arg = py.from_borrowed_ptr::<$crate::types::PyAny>(arg);
-
-            let result = match arg.extract() {
-                Ok(arg) => slf1.$f(arg).into(),
-                Err(e) => Err(e.into()),
-            };
-            match result {
-                Ok(_) => {
-                    ffi::Py_INCREF(slf);
-                    slf
-                }
-                Err(e) => {
-                    e.restore(py);
-                    std::ptr::null_mut()
+            $crate::callback::handle_panic(py, || {
+                let mut slf1 = $crate::PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+                let arg = py.from_borrowed_ptr::<$crate::types::PyAny>(arg);
+
+                let result = match slf1 {
+                    Ok(ref mut slf1) => match arg.extract() {
+                        Ok(arg) => slf1.$f(arg).into(),
+                        Err(e) => Err(e.into()),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                match result {
+                    Ok(_) => {
+                        ffi::Py_INCREF(slf);
+                        slf
+                    }
+                    Err(e) => {
+                        e.restore(py);
+                        std::ptr::null_mut()
+                    }
                 }
-            }
+            })
         }
         Some(wrap::<$class>)
     }};
@@ -182,9 +264,41 @@ macro_rules! py_ssizearg_func {
         {
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-            let result = slf.$f(arg.into()).into();
-            $crate::callback::cb_convert($conv, py, result)
+            $crate::callback::handle_panic(py, || {
+                let slf = $crate::PyRef::<T>::try_from_borrowed_ptr(py, slf);
+                let result = match slf {
+                    Ok(ref slf) => slf.$f(arg.into()).into(),
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
+        }
+        Some(wrap::<$class>)
+    }};
+}
+
+/// Like `py_ssizearg_func`, but for methods that take `&mut self`
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_ssizearg_refmut_func {
+    ($trait:ident, $class:ident :: $f:ident, $res_type:ty, $conv:expr) => {{
+        unsafe extern "C" fn wrap<T>(
+            slf: *mut ffi::PyObject,
+            arg: $crate::ffi::Py_ssize_t,
+        ) -> *mut $crate::ffi::PyObject
+        where
+            T: for<'p> $trait<'p>,
+        {
+            let py = $crate::Python::assume_gil_acquired();
+            let _pool = $crate::GILPool::new(py);
+            $crate::callback::handle_panic(py, || {
+                let mut slf = $crate::PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+                let result = match slf {
+                    Ok(ref mut slf) => slf.$f(arg.into()).into(),
+                    Err(e) => Err(e.into()),
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
         }
         Some(wrap::<$class>)
     }};
@@ -215,18 +329,23 @@ macro_rules! py_ternary_func {
 
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-            let arg1 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg1);
-            let arg2 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg2);
-
-            let result = match arg1.extract() {
-                Ok(arg1) => match arg2.extract() {
-                    Ok(arg2) => slf.$f(arg1, arg2).into(),
+            $crate::callback::handle_panic(py, || {
+                let slf = $crate::PyRef::<T>::try_from_borrowed_ptr(py, slf);
+                let arg1 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg1);
+                let arg2 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg2);
+
+                let result = match slf {
+                    Ok(ref slf) => match arg1.extract() {
+                        Ok(arg1) => match arg2.extract() {
+                            Ok(arg2) => slf.$f(arg1, arg2).into(),
+                            Err(e) => Err(e.into()),
+                        },
+                        Err(e) => Err(e.into()),
+                    },
                     Err(e) => Err(e.into()),
-                },
-                Err(e) => Err(e.into()),
-            };
-            $crate::callback::cb_convert($conv, py, result)
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
         }
 
         Some(wrap::<T>)
@@ -249,21 +368,23 @@ macro_rules! py_ternary_num_func {
 
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let arg1 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg1);
-            let arg2 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg2);
-            let arg3 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg3);
-
-            let result = match arg1.extract() {
-                Ok(arg1) => match arg2.extract() {
-                    Ok(arg2) => match arg3.extract() {
-                        Ok(arg3) => $class::$f(arg1, arg2, arg3).into(),
+            $crate::callback::handle_panic(py, || {
+                let arg1 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg1);
+                let arg2 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg2);
+                let arg3 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg3);
+
+                let result = match arg1.extract() {
+                    Ok(arg1) => match arg2.extract() {
+                        Ok(arg2) => match arg3.extract() {
+                            Ok(arg3) => $class::$f(arg1, arg2, arg3).into(),
+                            Err(e) => Err(e.into()),
+                        },
                         Err(e) => Err(e.into()),
                     },
                     Err(e) => Err(e.into()),
-                },
-                Err(e) => Err(e.into()),
-            };
-            $crate::callback::cb_convert($conv, py, result)
+                };
+                $crate::callback::cb_convert($conv, py, result)
+            })
         }
 
         Some(wrap::<T>)
@@ -286,25 +407,30 @@ macro_rules! py_ternary_self_func {
 
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf1 = py.mut_from_borrowed_ptr::<T>(slf);
-            let arg1 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg1);
-            let arg2 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg2);
-
-            let result = match arg1.extract() {
-                Ok(arg1) => match arg2.extract() {
-                    Ok(arg2) => slf1.$f(arg1, arg2).into(),
+            $crate::callback::handle_panic(py, || {
+                let mut slf1 = $crate::PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+                let arg1 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg1);
+                let arg2 = py.from_borrowed_ptr::<$crate::types::PyAny>(arg2);
+
+                let result = match slf1 {
+                    Ok(ref mut slf1) => match arg1.extract() {
+                        Ok(arg1) => match arg2.extract() {
+                            Ok(arg2) => slf1.$f(arg1, arg2).into(),
+                            Err(e) => Err(e.into()),
+                        },
+                        Err(e) => Err(e.into()),
+                    },
                     Err(e) => Err(e.into()),
-                },
-                Err(e) => Err(e.into()),
-            };
-
-            match result {
-                Ok(_) => slf,
-                Err(e) => {
-                    e.restore(py);
-                    std::ptr::null_mut()
+                };
+
+                match result {
+                    Ok(_) => slf,
+                    Err(e) => {
+                        e.restore(py);
+                        std::ptr::null_mut()
+                    }
                 }
-            }
+            })
         }
         Some(wrap::<T>)
     }};
@@ -325,33 +451,38 @@ macro_rules! py_func_set {
 
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<$generic>(slf);
-
-            let result = if value.is_null() {
-                Err($crate::PyErr::new::<exceptions::NotImplementedError, _>(
-                    format!(
-                        "Subscript deletion not supported by {:?}",
-                        stringify!($generic)
-                    ),
-                ))
-            } else {
-                let name = py.mut_from_borrowed_ptr::<$crate::types::PyAny>(name);
-                let value = py.from_borrowed_ptr::<$crate::types::PyAny>(value);
-                match name.extract() {
-                    Ok(name) => match value.extract() {
-                        Ok(value) => slf.$fn_set(name, value).into(),
+            $crate::callback::handle_panic(py, || {
+                let slf = $crate::PyRefMut::<$generic>::try_from_borrowed_ptr(py, slf);
+
+                let result = if value.is_null() {
+                    Err($crate::PyErr::new::<exceptions::NotImplementedError, _>(
+                        format!(
+                            "Subscript deletion not supported by {:?}",
+                            stringify!($generic)
+                        ),
+                    ))
+                } else {
+                    let name = py.mut_from_borrowed_ptr::<$crate::types::PyAny>(name);
+                    let value = py.from_borrowed_ptr::<$crate::types::PyAny>(value);
+                    match slf {
+                        Ok(mut slf) => match name.extract() {
+                            Ok(name) => match value.extract() {
+                                Ok(value) => slf.$fn_set(name, value).into(),
+                                Err(e) => Err(e.into()),
+                            },
+                            Err(e) => Err(e.into()),
+                        },
                         Err(e) => Err(e.into()),
-                    },
-                    Err(e) => Err(e.into()),
+                    }
+                };
+                match result {
+                    Ok(_) => 0,
+                    Err(e) => {
+                        e.restore(py);
+                        -1
+                    }
                 }
-            };
-            match result {
-                Ok(_) => 0,
-                Err(e) => {
-                    e.restore(py);
-                    -1
-                }
-            }
+            })
         }
 
         Some(wrap::<$generic>)
@@ -373,27 +504,32 @@ macro_rules! py_func_del {
 
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
+            $crate::callback::handle_panic(py, || {
 
-            let result = if value.is_null() {
-                let slf = py.mut_from_borrowed_ptr::<U>(slf);
-                let name = py.from_borrowed_ptr::<$crate::types::PyAny>(name);
+                let result = if value.is_null() {
+                    let slf = $crate::PyRefMut::<U>::try_from_borrowed_ptr(py, slf);
+                    let name = py.from_borrowed_ptr::<$crate::types::PyAny>(name);
 
-                match name.extract() {
-                    Ok(name) => slf.$fn_del(name).into(),
-                    Err(e) => Err(e.into()),
-                }
-            } else {
-                Err(PyErr::new::<exceptions::NotImplementedError, _>(
-                    "Subscript assignment not supported",
-                ))
-            };
-            match result {
-                Ok(_) => 0,
-                Err(e) => {
-                    e.restore(py);
-                    -1
+                    match slf {
+                        Ok(mut slf) => match name.extract() {
+                            Ok(name) => slf.$fn_del(name).into(),
+                            Err(e) => Err(e.into()),
+                        },
+                        Err(e) => Err(e.into()),
+                    }
+                } else {
+                    Err(PyErr::new::<exceptions::NotImplementedError, _>(
+                        "Subscript assignment not supported",
+                    ))
+                };
+                match result {
+                    Ok(_) => 0,
+                    Err(e) => {
+                        e.restore(py);
+                        -1
+                    }
                 }
-            }
+            })
         }
 
         Some(wrap::<$generic>)
@@ -415,31 +551,38 @@ macro_rules! py_func_set_del {
 
             let py = $crate::Python::assume_gil_acquired();
             let _pool = $crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<$generic>(slf);
-            let name = py.from_borrowed_ptr::<$crate::types::PyAny>(name);
+            $crate::callback::handle_panic(py, || {
+                let slf = $crate::PyRefMut::<$generic>::try_from_borrowed_ptr(py, slf);
+                let name = py.from_borrowed_ptr::<$crate::types::PyAny>(name);
 
-            let result = if value.is_null() {
-                match name.extract() {
-                    Ok(name) => slf.$fn_del(name).into(),
+                let result = match slf {
+                    Ok(mut slf) => {
+                        if value.is_null() {
+                            match name.extract() {
+                                Ok(name) => slf.$fn_del(name).into(),
+                                Err(e) => Err(e.into()),
+                            }
+                        } else {
+                            let value = py.from_borrowed_ptr::<$crate::types::PyAny>(value);
+                            match name.extract() {
+                                Ok(name) => match value.extract() {
+                                    Ok(value) => slf.$fn_set(name, value).into(),
+                                    Err(e) => Err(e.into()),
+                                },
+                                Err(e) => Err(e.into()),
+                            }
+                        }
+                    }
                     Err(e) => Err(e.into()),
+                };
+                match result {
+                    Ok(_) => 0,
+                    Err(e) => {
+                        e.restore(py);
+                        -1
+                    }
                 }
-            } else {
-                let value = py.from_borrowed_ptr::<$crate::types::PyAny>(value);
-                match name.extract() {
-                    Ok(name) => match value.extract() {
-                        Ok(value) => slf.$fn_set(name, value).into(),
-                        Err(e) => Err(e.into()),
-                    },
-                    Err(e) => Err(e.into()),
-                }
-            };
-            match result {
-                Ok(_) => 0,
-                Err(e) => {
-                    e.restore(py);
-                    -1
-                }
-            }
+            })
         }
         Some(wrap::<$generic>)
     }};
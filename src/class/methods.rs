@@ -1,8 +1,9 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
-use crate::ffi;
+use crate::{ffi, PyObject, PyResult, Python};
 use libc::c_int;
 use std::ffi::CString;
+use std::fmt;
 
 /// `PyMethodDefType` represents different types of python callable objects.
 /// It is used by `#[pymethods]` and `#[pyproto]` annotations.
@@ -24,6 +25,11 @@ pub enum PyMethodDefType {
     Getter(PyGetterDef),
     /// Represents setter descriptor, used by `#[setter]`
     Setter(PySetterDef),
+    /// Represents an attribute of the class itself, used by `#[classattr]` and the variants of
+    /// a `#[pyclass]` enum
+    ClassAttribute(PyClassAttributeDef),
+    /// Represents a dunder method of `#[pymethods]` that is stored in a type object slot
+    Slot(PySlotDef),
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -57,6 +63,68 @@ pub struct PySetterDef {
     pub doc: &'static str,
 }
 
+#[derive(Copy, Clone)]
+pub struct PyClassAttributeDef {
+    pub name: &'static str,
+    pub meth: fn(Python) -> PyResult<PyObject>,
+}
+
+/// The type object slot of a dunder method, e.g. `tp_repr` for `__repr__`
+///
+/// Binary number slots are shared by an operator and its reflected version, e.g. `Add`
+/// is used for both `__add__` and `__radd__`.
+#[derive(Copy, Clone, Debug)]
+pub enum PySlotDef {
+    Repr(ffi::reprfunc),
+    Str(ffi::reprfunc),
+    Hash(ffi::hashfunc),
+    RichCompare(ffi::richcmpfunc),
+    GetAttr(ffi::getattrofunc),
+    SetAttr(ffi::setattrofunc),
+    Iter(ffi::getiterfunc),
+    IterNext(ffi::iternextfunc),
+    /// Stored in both the mapping and the sequence slots
+    Len(ffi::lenfunc),
+    GetItem(ffi::binaryfunc),
+    SetItem(ffi::objobjargproc),
+    Contains(ffi::objobjproc),
+    Bool(ffi::inquiry),
+    Neg(ffi::unaryfunc),
+    Pos(ffi::unaryfunc),
+    Abs(ffi::unaryfunc),
+    Invert(ffi::unaryfunc),
+    Int(ffi::unaryfunc),
+    Float(ffi::unaryfunc),
+    Index(ffi::unaryfunc),
+    Add(ffi::binaryfunc),
+    Sub(ffi::binaryfunc),
+    Mul(ffi::binaryfunc),
+    MatMul(ffi::binaryfunc),
+    TrueDiv(ffi::binaryfunc),
+    FloorDiv(ffi::binaryfunc),
+    Mod(ffi::binaryfunc),
+    DivMod(ffi::binaryfunc),
+    Pow(ffi::ternaryfunc),
+    LShift(ffi::binaryfunc),
+    RShift(ffi::binaryfunc),
+    And(ffi::binaryfunc),
+    Xor(ffi::binaryfunc),
+    Or(ffi::binaryfunc),
+    IAdd(ffi::binaryfunc),
+    ISub(ffi::binaryfunc),
+    IMul(ffi::binaryfunc),
+    IMatMul(ffi::binaryfunc),
+    ITrueDiv(ffi::binaryfunc),
+    IFloorDiv(ffi::binaryfunc),
+    IMod(ffi::binaryfunc),
+    IPow(ffi::ternaryfunc),
+    ILShift(ffi::binaryfunc),
+    IRShift(ffi::binaryfunc),
+    IAnd(ffi::binaryfunc),
+    IXor(ffi::binaryfunc),
+    IOr(ffi::binaryfunc),
+}
+
 unsafe impl Sync for PyMethodDef {}
 
 unsafe impl Sync for ffi::PyMethodDef {}
@@ -89,6 +157,15 @@ impl PyMethodDef {
     }
 }
 
+// Manual implementation because `meth` is a higher-ranked function pointer
+impl fmt::Debug for PyClassAttributeDef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PyClassAttributeDef")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
 impl PyGetterDef {
     /// Copy descriptor information to `ffi::PyGetSetDef`
     pub fn copy_to(&self, dst: &mut ffi::PyGetSetDef) {
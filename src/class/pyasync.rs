@@ -222,7 +222,7 @@ mod anext {
     {
         #[inline]
         fn am_anext() -> Option<ffi::unaryfunc> {
-            py_unary_func!(
+            py_unary_refmut_func!(
                 PyAsyncAnextProtocol,
                 T::__anext__,
                 Option<T::Success>,
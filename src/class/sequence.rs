@@ -6,6 +6,7 @@
 use crate::callback::{BoolCallbackConverter, LenResultConverter, PyObjectCallbackConverter};
 use crate::err::{PyErr, PyResult};
 use crate::ffi;
+use crate::instance::PyRefMut;
 use crate::objectprotocol::ObjectProtocol;
 use crate::type_object::PyTypeInfo;
 use crate::types::PyAny;
@@ -223,27 +224,32 @@ where
         {
             let py = Python::assume_gil_acquired();
             let _pool = crate::GILPool::new(py);
-            let slf = py.mut_from_borrowed_ptr::<T>(slf);
-
-            let result = if value.is_null() {
-                Err(PyErr::new::<exceptions::NotImplementedError, _>(format!(
-                    "Item deletion not supported by {:?}",
-                    stringify!(T)
-                )))
-            } else {
-                let value = py.from_borrowed_ptr::<PyAny>(value);
-                match value.extract() {
-                    Ok(value) => slf.__setitem__(key.into(), value).into(),
-                    Err(e) => Err(e),
-                }
-            };
-            match result {
-                Ok(_) => 0,
-                Err(e) => {
-                    e.restore(py);
-                    -1
+            crate::callback::handle_panic(py, || {
+                let slf = PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+
+                let result = if value.is_null() {
+                    Err(PyErr::new::<exceptions::NotImplementedError, _>(format!(
+                        "Item deletion not supported by {:?}",
+                        stringify!(T)
+                    )))
+                } else {
+                    let value = py.from_borrowed_ptr::<PyAny>(value);
+                    match slf {
+                        Ok(mut slf) => match value.extract() {
+                            Ok(value) => slf.__setitem__(key.into(), value).into(),
+                            Err(e) => Err(e),
+                        },
+                        Err(e) => Err(e.into()),
+                    }
+                };
+                match result {
+                    Ok(_) => 0,
+                    Err(e) => {
+                        e.restore(py);
+                        -1
+                    }
                 }
-            }
+            })
         }
         Some(wrap::<T>)
     }
@@ -297,24 +303,29 @@ mod sq_ass_item_impl {
             {
                 let py = Python::assume_gil_acquired();
                 let _pool = crate::GILPool::new(py);
-                let slf = py.mut_from_borrowed_ptr::<T>(slf);
-
-                let result = if value.is_null() {
-                    slf.__delitem__(key.into()).into()
-                } else {
-                    Err(PyErr::new::<exceptions::NotImplementedError, _>(format!(
-                        "Item assignment not supported by {:?}",
-                        stringify!(T)
-                    )))
-                };
-
-                match result {
-                    Ok(_) => 0,
-                    Err(e) => {
-                        e.restore(py);
-                        -1
+                crate::callback::handle_panic(py, || {
+                    let slf = PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+
+                    let result = if value.is_null() {
+                        match slf {
+                            Ok(mut slf) => slf.__delitem__(key.into()).into(),
+                            Err(e) => Err(e.into()),
+                        }
+                    } else {
+                        Err(PyErr::new::<exceptions::NotImplementedError, _>(format!(
+                            "Item assignment not supported by {:?}",
+                            stringify!(T)
+                        )))
+                    };
+
+                    match result {
+                        Ok(_) => 0,
+                        Err(e) => {
+                            e.restore(py);
+                            -1
+                        }
                     }
-                }
+                })
             }
             Some(wrap::<T>)
         }
@@ -343,24 +354,31 @@ mod sq_ass_item_impl {
             {
                 let py = Python::assume_gil_acquired();
                 let _pool = crate::GILPool::new(py);
-                let slf = py.mut_from_borrowed_ptr::<T>(slf);
-
-                let result = if value.is_null() {
-                    slf.__delitem__(key.into()).into()
-                } else {
-                    let value = py.from_borrowed_ptr::<PyAny>(value);
-                    match value.extract() {
-                        Ok(value) => slf.__setitem__(key.into(), value).into(),
-                        Err(e) => Err(e),
+                crate::callback::handle_panic(py, || {
+                    let slf = PyRefMut::<T>::try_from_borrowed_ptr(py, slf);
+
+                    let result = match slf {
+                        Ok(mut slf) => {
+                            if value.is_null() {
+                                slf.__delitem__(key.into()).into()
+                            } else {
+                                let value = py.from_borrowed_ptr::<PyAny>(value);
+                                match value.extract() {
+                                    Ok(value) => slf.__setitem__(key.into(), value).into(),
+                                    Err(e) => Err(e),
+                                }
+                            }
+                        }
+                        Err(e) => Err(e.into()),
+                    };
+                    match result {
+                        Ok(_) => 0,
+                        Err(e) => {
+                            e.restore(py);
+                            -1
+                        }
                     }
-                };
-                match result {
-                    Ok(_) => 0,
-                    Err(e) => {
-                        e.restore(py);
-                        -1
-                    }
-                }
+                })
             }
             Some(wrap::<T>)
         }
@@ -447,7 +465,7 @@ where
     T: for<'p> PySequenceInplaceConcatProtocol<'p>,
 {
     fn sq_inplace_concat() -> Option<ffi::binaryfunc> {
-        py_binary_func!(
+        py_binary_refmut_func!(
             PySequenceInplaceConcatProtocol,
             T::__inplace_concat__,
             T,
@@ -469,7 +487,7 @@ where
     T: for<'p> PySequenceInplaceRepeatProtocol<'p>,
 {
     fn sq_inplace_repeat() -> Option<ffi::ssizeargfunc> {
-        py_ssizearg_func!(
+        py_ssizearg_refmut_func!(
             PySequenceInplaceRepeatProtocol,
             T::__inplace_repeat__,
             T,
@@ -3,7 +3,7 @@
 //! Conversions between various states of rust and python types and their wrappers.
 use crate::err::{self, PyDowncastError, PyResult};
 use crate::object::PyObject;
-use crate::type_object::PyTypeInfo;
+use crate::type_object::{BorrowGuard, PyTypeInfo};
 use crate::types::PyAny;
 use crate::types::PyTuple;
 use crate::{ffi, gil, Py, Python};
@@ -259,6 +259,36 @@ where
     }
 }
 
+/// Extract a shared reference to a `#[pyclass]` instance from `PyObject`.
+///
+/// This fails if the instance is mutably borrowed, but the reference doesn't hold a borrow.
+/// Extract a [PyRef](crate::PyRef) to keep the instance borrowed while it's in use.
+impl<'a, T> FromPyObject<'a> for &'a T
+where
+    T: PyTypeInfo,
+{
+    fn extract(ob: &'a PyAny) -> PyResult<&'a T> {
+        let value = T::try_from(ob)?;
+        unsafe { BorrowGuard::try_borrow::<T>(ob.as_ptr())? };
+        Ok(value)
+    }
+}
+
+/// Extract a mutable reference to a `#[pyclass]` instance from `PyObject`.
+///
+/// This fails if the instance is borrowed, but the reference doesn't hold a borrow.
+/// Extract a [PyRefMut](crate::PyRefMut) to keep the instance borrowed while it's in use.
+impl<'a, T> FromPyObject<'a> for &'a mut T
+where
+    T: PyTypeInfo,
+{
+    fn extract(ob: &'a PyAny) -> PyResult<&'a mut T> {
+        let value = T::try_from_mut(ob)?;
+        unsafe { BorrowGuard::try_borrow_mut::<T>(ob.as_ptr())? };
+        Ok(value)
+    }
+}
+
 impl<'a, T> FromPyObject<'a> for Option<T>
 where
     T: FromPyObject<'a>,
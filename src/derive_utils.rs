@@ -4,15 +4,22 @@
 
 //! Functionality for the code generated by the derive backend
 
-use crate::err::PyResult;
-use crate::exceptions::TypeError;
+use crate::err::{PyErr, PyResult};
+use crate::exceptions::{ImportError, PanicException, TypeError};
 use crate::init_once;
 use crate::instance::PyNativeType;
-use crate::types::{PyAny, PyDict, PyModule, PyTuple};
+use crate::objectprotocol::ObjectProtocol;
+use crate::type_object::{BorrowGuard, PyTypeInfo};
+use crate::types::{PyAny, PyDict, PyModule, PySequence, PyTuple};
 use crate::GILPool;
 use crate::Python;
-use crate::{ffi, IntoPy, PyObject};
-use std::ptr;
+use crate::{ffi, AsPyPointer, FromPyObject, IntoPy, IntoPyPointer, PyObject, PyTryFrom};
+use std::any::Any;
+use std::cell::UnsafeCell;
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::{mem, ptr};
 
 /// Description of a python parameter; used for `parse_args()`.
 #[derive(Debug)]
@@ -129,6 +136,26 @@ pub unsafe fn make_module(
     #[cfg(not(Py_3_7))]
     ffi::PyEval_InitThreads();
 
+    // The module and its classes are created once per process, so they can't be imported into
+    // a second interpreter
+    let interpreter = crate::gil::current_interpreter() as usize;
+    {
+        let mut interpreters = MODULE_INTERPRETERS.lock();
+        match interpreters.iter().find(|(module, _)| module == name) {
+            Some(&(_, owner)) if owner != interpreter => {
+                ImportError::py_err(format!(
+                    "Module {} was already initialized in another interpreter; \
+                     use #[pymodule(multi_phase)] to support sub-interpreters",
+                    name.trim_end_matches('\0')
+                ))
+                .restore(Python::assume_gil_acquired());
+                return ptr::null_mut();
+            }
+            Some(_) => {}
+            None => interpreters.push((name.to_string(), interpreter)),
+        }
+    }
+
     static mut MODULE_DEF: ffi::PyModuleDef = ffi::PyModuleDef_INIT;
     // We can't convert &'static str to *const c_char within a static initializer,
     // so we'll do it here in the module initialization:
@@ -141,24 +168,155 @@ pub unsafe fn make_module(
 
     let py = Python::assume_gil_acquired();
     let _pool = GILPool::new(py);
-    let module = match py.from_owned_ptr_or_err::<PyModule>(module) {
-        Ok(m) => m,
-        Err(e) => {
-            e.restore(py);
-            return ptr::null_mut();
+    crate::callback::handle_panic(py, || {
+        let module = match py.from_owned_ptr_or_err::<PyModule>(module) {
+            Ok(m) => m,
+            Err(e) => {
+                e.restore(py);
+                return ptr::null_mut();
+            }
+        };
+
+        module
+            .add("__doc__", doc)
+            .expect("Failed to add doc for module");
+        match initializer(py, module) {
+            Ok(_) => module.into_ptr(),
+            Err(e) => {
+                e.restore(py);
+                ptr::null_mut()
+            }
         }
-    };
+    })
+}
+
+/// The interpreters that the modules with single-phase initialization were created in, by name
+static MODULE_INTERPRETERS: spin::Mutex<Vec<(String, usize)>> = spin::Mutex::new(Vec::new());
+
+/// Forgets the interpreters of the modules after the interpreter was finalized, so that the
+/// modules can be created in the next interpreter
+pub(crate) fn reset_module_interpreters() {
+    MODULE_INTERPRETERS.lock().clear();
+}
+
+/// The function of the `Py_mod_exec` slot of a module definition
+pub type ModuleExec = unsafe extern "C" fn(*mut ffi::PyObject) -> c_int;
+
+/// The per-module state of a module with multi-phase initialization, which is set with
+/// `PyModule::set_state`. It is null until the state is set.
+pub(crate) type ModuleState = *mut Box<dyn Any>;
+
+/// The definition of a module with multi-phase initialization (PEP 489), which lives in a
+/// static. Used for `#[pymodule(multi_phase)]`.
+pub struct ModuleDef {
+    def: UnsafeCell<ffi::PyModuleDef>,
+    slots: UnsafeCell<[ffi::PyModuleDef_Slot; 2]>,
+}
+
+// The definition is only accessed while holding the GIL
+unsafe impl Sync for ModuleDef {}
+
+impl ModuleDef {
+    pub const fn new() -> Self {
+        ModuleDef {
+            def: UnsafeCell::new(ffi::PyModuleDef_INIT),
+            slots: UnsafeCell::new(
+                [ffi::PyModuleDef_Slot {
+                    slot: 0,
+                    value: ptr::null_mut(),
+                }; 2],
+            ),
+        }
+    }
+
+    /// Fills in the definition and returns it to the interpreter, which creates the module and
+    /// then calls `exec` on it.
+    ///
+    /// Unlike a module with single-phase initialization, the module can be created any number of
+    /// times, e.g. once in every sub-interpreter.
+    pub unsafe fn init(&'static self, name: &'static str, exec: ModuleExec) -> *mut ffi::PyObject {
+        init_once();
+
+        #[cfg(py_sys_config = "WITH_THREAD")]
+        // > Changed in version 3.7: This function is now called by Py_Initialize(), so you don’t have
+        // > to call it yourself anymore.
+        #[cfg(not(Py_3_7))]
+        ffi::PyEval_InitThreads();
+
+        let slots = &mut *self.slots.get();
+        slots[0] = ffi::PyModuleDef_Slot {
+            slot: ffi::Py_mod_exec,
+            value: exec as *mut c_void,
+        };
 
-    module
-        .add("__doc__", doc)
-        .expect("Failed to add doc for module");
-    match initializer(py, module) {
-        Ok(_) => module.into_ptr(),
-        Err(e) => {
-            e.restore(py);
-            ptr::null_mut()
+        let def = &mut *self.def.get();
+        def.m_name = name.as_ptr() as *const _;
+        def.m_size = mem::size_of::<ModuleState>() as ffi::Py_ssize_t;
+        def.m_slots = slots.as_mut_ptr();
+        // The state is a `Box<dyn Any>`, whose python objects can't be visited, so there is no
+        // `m_traverse` or `m_clear`
+        def.m_free = Some(free_module_state);
+        ffi::PyModuleDef_Init(def)
+    }
+}
+
+/// Initializes a module with multi-phase initialization in its `Py_mod_exec` slot.
+/// Used for `#[pymodule(multi_phase)]`.
+pub unsafe fn exec_module(
+    module: *mut ffi::PyObject,
+    doc: &str,
+    initializer: impl Fn(Python, &PyModule) -> PyResult<()>,
+) -> c_int {
+    let py = Python::assume_gil_acquired();
+    let _pool = GILPool::new(py);
+    crate::callback::handle_panic(py, || {
+        let module = py.from_borrowed_ptr::<PyModule>(module);
+        let result = module
+            .add("__doc__", doc)
+            .and_then(|_| initializer(py, module));
+        match result {
+            Ok(_) => 0,
+            Err(e) => {
+                e.restore(py);
+                -1
+            }
         }
+    })
+}
+
+/// Drops the state of a module when it is deallocated
+pub(crate) unsafe extern "C" fn free_module_state(module: *mut c_void) {
+    let state = ffi::PyModule_GetState(module as *mut ffi::PyObject) as *mut ModuleState;
+    if state.is_null() || (*state).is_null() {
+        return;
+    }
+    let value = Box::from_raw(*state);
+    *state = ptr::null_mut();
+    // A deallocator can't raise an exception, so a panic in `drop` is reported as unraisable
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| drop(value))) {
+        let py = Python::assume_gil_acquired();
+        PanicException::from_panic_payload(payload).restore(py);
+        ffi::PyErr_WriteUnraisable(ptr::null_mut());
+    }
+}
+
+/// Turns the result of a `PyInit_*` function into a module. A module with multi-phase
+/// initialization returns its (static) definition, so the module is created and executed here.
+/// Used for `wrap_pymodule!`.
+pub unsafe fn module_from_init(py: Python, init: *mut ffi::PyObject) -> PyResult<PyObject> {
+    if init.is_null() || ffi::PyObject_TypeCheck(init, &mut ffi::PyModuleDef_Type) == 0 {
+        return PyObject::from_owned_ptr_or_err(py, init);
+    }
+    let def = init as *mut ffi::PyModuleDef;
+    let name = CStr::from_ptr((*def).m_name).to_string_lossy();
+    let machinery = py.import("importlib.machinery")?;
+    let spec = machinery.call1("ModuleSpec", (name.as_ref(), py.None()))?;
+    let module =
+        PyObject::from_owned_ptr_or_err(py, ffi::PyModule_FromDefAndSpec(def, spec.as_ptr()))?;
+    if ffi::PyModule_ExecDef(module.as_ptr(), def) != 0 {
+        return Err(PyErr::fetch(py));
     }
+    Ok(module)
 }
 
 /// This trait wraps a T: IntoPy<PyObject> into PyResult<T> while PyResult<T> remains PyResult<T>.
@@ -181,3 +339,144 @@ impl<T: IntoPy<PyObject>> IntoPyResult<T> for PyResult<T> {
         self
     }
 }
+
+/// The borrow of a `&T` or `&mut T` argument of a `#[pyclass]`, which is released when it is
+/// dropped after the call.
+pub struct ArgumentBorrow(BorrowGuard);
+
+/// Extracts a required argument of a wrapped function. A `&T` or `&mut T` of a `#[pyclass]`
+/// stays borrowed until the returned `ArgumentBorrow` is dropped.
+pub fn extract_argument<'a, T>(ob: &'a PyAny) -> PyResult<(T, ArgumentBorrow)>
+where
+    T: FromPyObject<'a>,
+{
+    let value = T::extract(ob)?;
+    let borrow = <T as BorrowArgument>::borrow_argument(ob)?;
+    Ok((value, ArgumentBorrow(borrow)))
+}
+
+trait BorrowArgument {
+    fn borrow_argument(ob: &PyAny) -> PyResult<BorrowGuard>;
+}
+
+impl<T> BorrowArgument for T {
+    default fn borrow_argument(_ob: &PyAny) -> PyResult<BorrowGuard> {
+        Ok(BorrowGuard::unused())
+    }
+}
+
+impl<'a, T: PyTypeInfo> BorrowArgument for &'a T {
+    fn borrow_argument(ob: &PyAny) -> PyResult<BorrowGuard> {
+        // `ob` has been extracted as a `T`
+        Ok(unsafe { BorrowGuard::try_borrow::<T>(ob.as_ptr())? })
+    }
+}
+
+impl<'a, T: PyTypeInfo> BorrowArgument for &'a mut T {
+    fn borrow_argument(ob: &PyAny) -> PyResult<BorrowGuard> {
+        Ok(unsafe { BorrowGuard::try_borrow_mut::<T>(ob.as_ptr())? })
+    }
+}
+
+/// Formats an error as `ExceptionType: message` for embedding it in another error message
+pub(crate) fn error_description(py: Python, err: PyErr) -> String {
+    use crate::instance::AsPyRef;
+
+    let type_name = err.ptype.as_ref(py).name().into_owned();
+    let instance: PyObject = err.into_py(py);
+    match instance.as_ref(py).str() {
+        Ok(message) => format!("{}: {}", type_name, message.to_string_lossy()),
+        Err(_) => type_name,
+    }
+}
+
+/// Creates a `TypeError` with `message`, which has `cause` as its `__cause__`
+fn extraction_error(py: Python, message: String, cause: PyErr) -> PyErr {
+    use crate::instance::AsPyRef;
+
+    let error: PyObject = TypeError::py_err(message).into_py(py);
+    let cause: PyObject = cause.into_py(py);
+    unsafe { ffi::PyException_SetCause(error.as_ptr(), cause.into_ptr()) };
+    PyErr::from_instance(&error.as_ref(py))
+}
+
+/// Creates the error raised by `#[derive(FromPyObject)]` when a single field of `type_name`
+/// could not be extracted.
+///
+/// The error is a `TypeError`, which has `err` as its `__cause__`.
+pub fn failed_to_extract_field(py: Python, err: PyErr, type_name: &str, field: &str) -> PyErr {
+    let message = format!(
+        "failed to extract field {}.{} ({})",
+        type_name,
+        field,
+        error_description(py, err.clone_ref(py))
+    );
+    extraction_error(py, message, err)
+}
+
+/// Creates the error raised when an item of a collection could not be extracted, e.g. by the
+/// `FromPyObject` implementation of `HashMap`. `item` describes the position of `obj`.
+///
+/// Like [failed_to_extract_field], the error is a `TypeError`, which has `err` as its
+/// `__cause__`.
+pub(crate) fn failed_to_extract_item(obj: &PyAny, err: PyErr, item: &str) -> PyErr {
+    let py = obj.py();
+    let repr = match obj.repr() {
+        Ok(repr) => repr.to_string_lossy().into_owned(),
+        Err(_) => format!("<{} object>", obj.get_type().name()),
+    };
+    let message = format!(
+        "failed to extract {} {} ({})",
+        item,
+        repr,
+        error_description(py, err.clone_ref(py))
+    );
+    extraction_error(py, message, err)
+}
+
+/// Creates the error raised by `#[derive(FromPyObject)]` when none of the variants of an enum
+/// could be extracted. `errors` holds the error of every variant, in declaration order.
+pub fn failed_to_extract_enum(
+    py: Python,
+    type_name: &str,
+    variants: &[&str],
+    errors: Vec<PyErr>,
+) -> PyErr {
+    let mut message = format!(
+        "failed to extract enum {} ('{}')",
+        type_name,
+        variants.join(" | ")
+    );
+    for (variant, err) in variants.iter().zip(errors) {
+        message.push_str(&format!(
+            "\n- variant {}: {}",
+            variant,
+            error_description(py, err)
+        ));
+    }
+    TypeError::py_err(message)
+}
+
+/// Checks that `obj` is a sequence of exactly `len` items. Used by `#[derive(FromPyObject)]`
+/// for tuple structs.
+pub fn extract_sequence_of_len<'p>(
+    obj: &'p PyAny,
+    type_name: &str,
+    len: usize,
+) -> PyResult<&'p PySequence> {
+    let seq = <PySequence as PyTryFrom>::try_from(obj).map_err(|_| {
+        TypeError::py_err(format!(
+            "failed to extract {}: '{}' object is not a sequence",
+            type_name,
+            obj.get_type().name()
+        ))
+    })?;
+    let actual = seq.len()? as usize;
+    if actual != len {
+        return Err(TypeError::py_err(format!(
+            "failed to extract {}: expected a sequence of length {} (got {})",
+            type_name, len, actual
+        )));
+    }
+    Ok(seq)
+}
@@ -46,6 +46,79 @@ pub type PyResult<T> = Result<T, PyErr>;
 /// Marker type that indicates an error while downcasting
 pub struct PyDowncastError;
 
+/// Error returned when a `#[pyclass]` instance can't be borrowed, because it is already
+/// mutably borrowed or, for a mutable borrow, borrowed at all.
+///
+/// It is converted to a python `RuntimeError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PyBorrowError {
+    mutable: bool,
+}
+
+impl PyBorrowError {
+    /// A shared borrow failed because the instance is mutably borrowed
+    pub(crate) fn already_mutably_borrowed() -> Self {
+        PyBorrowError { mutable: false }
+    }
+
+    /// A mutable borrow failed because the instance is borrowed
+    pub(crate) fn already_borrowed() -> Self {
+        PyBorrowError { mutable: true }
+    }
+}
+
+impl std::fmt::Display for PyBorrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.mutable {
+            f.write_str("Already borrowed")
+        } else {
+            f.write_str("Already mutably borrowed")
+        }
+    }
+}
+
+impl std::error::Error for PyBorrowError {}
+
+/// Error returned by [Python::finalize](crate::Python::finalize).
+///
+/// It is converted to a python `RuntimeError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizeError {
+    /// The interpreter isn't initialized
+    NotInitialized,
+    /// The current thread holds the GIL
+    GILHeld,
+    /// Another thread uses the interpreter, or a sub-interpreter is alive
+    InUse,
+    /// The interpreter doesn't support finalization, which is the case for PyPy
+    Unsupported,
+    /// The interpreter was finalized, but this number of `PyObject` and `Py<T>` handles is still
+    /// alive. They must be dropped before the interpreter is initialized again.
+    LeakedHandles(usize),
+}
+
+impl std::fmt::Display for FinalizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FinalizeError::NotInitialized => f.write_str("The interpreter isn't initialized"),
+            FinalizeError::GILHeld => {
+                f.write_str("The interpreter can't be finalized while the GIL is held")
+            }
+            FinalizeError::InUse => {
+                f.write_str("The interpreter is used by another thread or by a sub-interpreter")
+            }
+            FinalizeError::Unsupported => f.write_str("The interpreter can't be finalized"),
+            FinalizeError::LeakedHandles(handles) => write!(
+                f,
+                "The interpreter was finalized, but {} PyObject or Py<T> handles are still alive",
+                handles
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FinalizeError {}
+
 /// Helper conversion trait that allows to use custom arguments for exception constructor.
 pub trait PyErrArguments {
     /// Arguments for exception
@@ -476,6 +549,8 @@ impl<W: Send + std::fmt::Debug> PyErrArguments for std::io::IntoInnerError<W> {
     }
 }
 
+impl_to_pyerr!(PyBorrowError, exceptions::RuntimeError);
+impl_to_pyerr!(FinalizeError, exceptions::RuntimeError);
 impl_to_pyerr!(std::num::ParseIntError, exceptions::ValueError);
 impl_to_pyerr!(std::num::ParseFloatError, exceptions::ValueError);
 impl_to_pyerr!(std::string::ParseError, exceptions::ValueError);
@@ -3,11 +3,12 @@
 //! Exception types defined by python.
 
 use crate::err::{PyErr, PyResult};
-use crate::ffi;
-use crate::type_object::PyTypeObject;
+use crate::ffi::{self, PyBaseExceptionObject};
+use crate::type_object::{PyTypeInfo, PyTypeObject};
 use crate::types::{PyAny, PyTuple};
 use crate::Python;
 use crate::{AsPyPointer, ToPyObject};
+use std::any::Any;
 use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::{self, ops};
@@ -90,30 +91,21 @@ macro_rules! import_exception_type_object {
     ($module: expr, $name: ident) => {
         unsafe impl $crate::type_object::PyTypeObject for $name {
             fn init_type() -> std::ptr::NonNull<$crate::ffi::PyTypeObject> {
-                // We can't use lazy_static here because raw pointers aren't Send
-                static TYPE_OBJECT_ONCE: ::std::sync::Once = ::std::sync::Once::new();
-                static mut TYPE_OBJECT: *mut $crate::ffi::PyTypeObject = ::std::ptr::null_mut();
-
-                TYPE_OBJECT_ONCE.call_once(|| {
-                    let gil = $crate::Python::acquire_gil();
-                    let py = gil.python();
-
-                    unsafe {
-                        let imp = py
-                            .import(stringify!($module))
-                            .expect(concat!("Can not import module: ", stringify!($module)));
-                        let cls = imp.get(stringify!($name)).expect(concat!(
-                            "Can not load exception class: {}.{}",
-                            stringify!($module),
-                            ".",
-                            stringify!($name)
-                        ));
-                        TYPE_OBJECT =
-                            $crate::IntoPyPointer::into_ptr(cls) as *mut $crate::ffi::PyTypeObject;
-                    }
-                });
-
-                unsafe { std::ptr::NonNull::new_unchecked(TYPE_OBJECT) }
+                static TYPE_OBJECT: $crate::type_object::LazyTypeObject =
+                    $crate::type_object::LazyTypeObject::new();
+
+                TYPE_OBJECT.get_or_init(|py| {
+                    let imp = py
+                        .import(stringify!($module))
+                        .expect(concat!("Can not import module: ", stringify!($module)));
+                    let cls = imp.get(stringify!($name)).expect(concat!(
+                        "Can not load exception class: {}.{}",
+                        stringify!($module),
+                        ".",
+                        stringify!($name)
+                    ));
+                    $crate::IntoPyPointer::into_ptr(cls) as *mut $crate::ffi::PyTypeObject
+                })
             }
         }
     };
@@ -175,25 +167,17 @@ macro_rules! create_exception_type_object {
     ($module: ident, $name: ident, $base: ty) => {
         unsafe impl $crate::type_object::PyTypeObject for $name {
             fn init_type() -> std::ptr::NonNull<$crate::ffi::PyTypeObject> {
-                // We can't use lazy_static here because raw pointers aren't Send
-                static TYPE_OBJECT_ONCE: ::std::sync::Once = ::std::sync::Once::new();
-                static mut TYPE_OBJECT: *mut $crate::ffi::PyTypeObject = ::std::ptr::null_mut();
-
-                TYPE_OBJECT_ONCE.call_once(|| {
-                    let gil = $crate::Python::acquire_gil();
-                    let py = gil.python();
-
-                    unsafe {
-                        TYPE_OBJECT = $crate::PyErr::new_type(
-                            py,
-                            concat!(stringify!($module), ".", stringify!($name)),
-                            Some(py.get_type::<$base>()),
-                            None,
-                        );
-                    }
-                });
-
-                unsafe { std::ptr::NonNull::new_unchecked(TYPE_OBJECT) }
+                static TYPE_OBJECT: $crate::type_object::LazyTypeObject =
+                    $crate::type_object::LazyTypeObject::new();
+
+                TYPE_OBJECT.get_or_init(|py| {
+                    $crate::PyErr::new_type(
+                        py,
+                        concat!(stringify!($module), ".", stringify!($name)),
+                        Some(py.get_type::<$base>()),
+                        None,
+                    )
+                })
             }
         }
     };
@@ -227,48 +211,95 @@ macro_rules! impl_native_exception (
             }
         }
     );
+    // Exceptions with a known instance layout can be extended by a `#[pyclass]`
+    ($name:ident, $exc_name:ident, $layout:path) => (
+        impl_native_exception!($name, $exc_name);
+
+        impl PyTypeInfo for $name {
+            type Type = ();
+            type BaseType = PyAny;
+
+            const NAME: &'static str = stringify!($name);
+            const MODULE: Option<&'static str> = Some("builtins");
+            const SIZE: usize = std::mem::size_of::<$layout>();
+            const OFFSET: isize = 0;
+
+            #[inline]
+            unsafe fn type_object() -> &'static mut ffi::PyTypeObject {
+                &mut *(ffi::$exc_name as *mut ffi::PyTypeObject)
+            }
+        }
+    );
 );
 
-impl_native_exception!(BaseException, PyExc_BaseException);
-impl_native_exception!(Exception, PyExc_Exception);
-impl_native_exception!(StopAsyncIteration, PyExc_StopAsyncIteration);
+impl_native_exception!(BaseException, PyExc_BaseException, PyBaseExceptionObject);
+impl_native_exception!(Exception, PyExc_Exception, PyBaseExceptionObject);
+impl_native_exception!(
+    StopAsyncIteration,
+    PyExc_StopAsyncIteration,
+    PyBaseExceptionObject
+);
 impl_native_exception!(StopIteration, PyExc_StopIteration);
-impl_native_exception!(GeneratorExit, PyExc_GeneratorExit);
-impl_native_exception!(ArithmeticError, PyExc_ArithmeticError);
-impl_native_exception!(LookupError, PyExc_LookupError);
-
-impl_native_exception!(AssertionError, PyExc_AssertionError);
-impl_native_exception!(AttributeError, PyExc_AttributeError);
-impl_native_exception!(BufferError, PyExc_BufferError);
-impl_native_exception!(EOFError, PyExc_EOFError);
-impl_native_exception!(FloatingPointError, PyExc_FloatingPointError);
+impl_native_exception!(GeneratorExit, PyExc_GeneratorExit, PyBaseExceptionObject);
+impl_native_exception!(
+    ArithmeticError,
+    PyExc_ArithmeticError,
+    PyBaseExceptionObject
+);
+impl_native_exception!(LookupError, PyExc_LookupError, PyBaseExceptionObject);
+
+impl_native_exception!(AssertionError, PyExc_AssertionError, PyBaseExceptionObject);
+impl_native_exception!(AttributeError, PyExc_AttributeError, PyBaseExceptionObject);
+impl_native_exception!(BufferError, PyExc_BufferError, PyBaseExceptionObject);
+impl_native_exception!(EOFError, PyExc_EOFError, PyBaseExceptionObject);
+impl_native_exception!(
+    FloatingPointError,
+    PyExc_FloatingPointError,
+    PyBaseExceptionObject
+);
 impl_native_exception!(OSError, PyExc_OSError);
 impl_native_exception!(ImportError, PyExc_ImportError);
 
 #[cfg(Py_3_6)]
 impl_native_exception!(ModuleNotFoundError, PyExc_ModuleNotFoundError);
 
-impl_native_exception!(IndexError, PyExc_IndexError);
-impl_native_exception!(KeyError, PyExc_KeyError);
-impl_native_exception!(KeyboardInterrupt, PyExc_KeyboardInterrupt);
-impl_native_exception!(MemoryError, PyExc_MemoryError);
-impl_native_exception!(NameError, PyExc_NameError);
-impl_native_exception!(OverflowError, PyExc_OverflowError);
-impl_native_exception!(RuntimeError, PyExc_RuntimeError);
-impl_native_exception!(RecursionError, PyExc_RecursionError);
-impl_native_exception!(NotImplementedError, PyExc_NotImplementedError);
+impl_native_exception!(IndexError, PyExc_IndexError, PyBaseExceptionObject);
+impl_native_exception!(KeyError, PyExc_KeyError, PyBaseExceptionObject);
+impl_native_exception!(
+    KeyboardInterrupt,
+    PyExc_KeyboardInterrupt,
+    PyBaseExceptionObject
+);
+impl_native_exception!(MemoryError, PyExc_MemoryError, PyBaseExceptionObject);
+impl_native_exception!(NameError, PyExc_NameError, PyBaseExceptionObject);
+impl_native_exception!(OverflowError, PyExc_OverflowError, PyBaseExceptionObject);
+impl_native_exception!(RuntimeError, PyExc_RuntimeError, PyBaseExceptionObject);
+impl_native_exception!(RecursionError, PyExc_RecursionError, PyBaseExceptionObject);
+impl_native_exception!(
+    NotImplementedError,
+    PyExc_NotImplementedError,
+    PyBaseExceptionObject
+);
 impl_native_exception!(SyntaxError, PyExc_SyntaxError);
-impl_native_exception!(ReferenceError, PyExc_ReferenceError);
-impl_native_exception!(SystemError, PyExc_SystemError);
+impl_native_exception!(ReferenceError, PyExc_ReferenceError, PyBaseExceptionObject);
+impl_native_exception!(SystemError, PyExc_SystemError, PyBaseExceptionObject);
 impl_native_exception!(SystemExit, PyExc_SystemExit);
-impl_native_exception!(TypeError, PyExc_TypeError);
-impl_native_exception!(UnboundLocalError, PyExc_UnboundLocalError);
+impl_native_exception!(TypeError, PyExc_TypeError, PyBaseExceptionObject);
+impl_native_exception!(
+    UnboundLocalError,
+    PyExc_UnboundLocalError,
+    PyBaseExceptionObject
+);
 impl_native_exception!(UnicodeError, PyExc_UnicodeError);
 impl_native_exception!(UnicodeDecodeError, PyExc_UnicodeDecodeError);
 impl_native_exception!(UnicodeEncodeError, PyExc_UnicodeEncodeError);
 impl_native_exception!(UnicodeTranslateError, PyExc_UnicodeTranslateError);
-impl_native_exception!(ValueError, PyExc_ValueError);
-impl_native_exception!(ZeroDivisionError, PyExc_ZeroDivisionError);
+impl_native_exception!(ValueError, PyExc_ValueError, PyBaseExceptionObject);
+impl_native_exception!(
+    ZeroDivisionError,
+    PyExc_ZeroDivisionError,
+    PyBaseExceptionObject
+);
 
 impl_native_exception!(BlockingIOError, PyExc_BlockingIOError);
 impl_native_exception!(BrokenPipeError, PyExc_BrokenPipeError);
@@ -340,6 +371,27 @@ impl StopIteration {
     }
 }
 
+/// Raised when a rust function called from python panics.
+///
+/// It derives from `BaseException`, so that it isn't caught by a plain `except Exception:`.
+pub struct PanicException;
+
+impl_exception_boilerplate!(PanicException);
+create_exception_type_object!(pyo3_runtime, PanicException, BaseException);
+
+impl PanicException {
+    /// Creates the exception for the payload of a caught panic, keeping its message
+    pub(crate) fn from_panic_payload(payload: Box<dyn Any + Send + 'static>) -> PyErr {
+        if let Some(message) = payload.downcast_ref::<String>() {
+            PanicException::py_err(message.clone())
+        } else if let Some(message) = payload.downcast_ref::<&str>() {
+            PanicException::py_err(message.to_string())
+        } else {
+            PanicException::py_err("panic from rust code")
+        }
+    }
+}
+
 /// Exceptions defined in `asyncio` module
 pub mod asyncio {
     import_exception!(asyncio, CancelledError);
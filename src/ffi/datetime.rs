@@ -17,7 +17,6 @@ use crate::ffi::{PyObject_TypeCheck, Py_TYPE};
 use std::ops::Deref;
 use std::os::raw::{c_char, c_int, c_uchar};
 use std::ptr;
-use std::sync::Once;
 #[cfg(not(PyPy))]
 use {crate::ffi::PyCapsule_Import, std::ffi::CString};
 
@@ -215,11 +214,15 @@ pub struct PyDateTime_Delta {
 // object, making the call_once lock superfluous. As such, we can weaken
 // the guarantees of the cache, such that PyDateTime_IMPORT can be called
 // until __PY_DATETIME_API_UNSAFE_CACHE is populated, which will happen exactly
-// one time. So long as PyDateTime_IMPORT has no side effects (it should not),
+// one time per interpreter. So long as PyDateTime_IMPORT has no side effects (it should not),
 // this will be at most a slight waste of resources.
-static PY_DATETIME_API_ONCE: Once = Once::new();
 static mut PY_DATETIME_API_UNSAFE_CACHE: *const PyDateTime_CAPI = ptr::null();
 
+/// Clears the cache after the interpreter was finalized, as the capsule belongs to it
+pub(crate) unsafe fn reset_datetime_api() {
+    PY_DATETIME_API_UNSAFE_CACHE = ptr::null();
+}
+
 #[derive(Debug)]
 pub struct PyDateTimeAPI {
     __private_field: (),
@@ -265,9 +268,10 @@ pub unsafe fn PyDateTime_IMPORT() -> &'static PyDateTime_CAPI {
         PyCapsule_Import(PyDateTime_CAPSULE_NAME.as_ptr(), 1) as *const PyDateTime_CAPI
     };
 
-    PY_DATETIME_API_ONCE.call_once(move || {
+    // The GIL is held, so only one thread can populate the cache
+    if PY_DATETIME_API_UNSAFE_CACHE.is_null() {
         PY_DATETIME_API_UNSAFE_CACHE = py_datetime_c_api;
-    });
+    }
 
     &(*PY_DATETIME_API_UNSAFE_CACHE)
 }
@@ -2,6 +2,21 @@ use crate::ffi::object::*;
 use crate::ffi::pyport::Py_ssize_t;
 use std::os::raw::{c_char, c_int};
 
+/// Opaque storage of the keys of a dict
+#[cfg(not(Py_LIMITED_API))]
+pub enum PyDictKeysObject {}
+
+#[cfg(not(Py_LIMITED_API))]
+#[repr(C)]
+pub struct PyDictObject {
+    pub ob_base: PyObject,
+    pub ma_used: Py_ssize_t,
+    #[cfg(Py_3_6)]
+    pub ma_version_tag: u64,
+    pub ma_keys: *mut PyDictKeysObject,
+    pub ma_values: *mut *mut PyObject,
+}
+
 #[cfg_attr(windows, link(name = "pythonXY"))]
 extern "C" {
     pub static mut PyDict_Type: PyTypeObject;
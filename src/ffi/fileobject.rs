@@ -26,4 +26,6 @@ extern "C" {
     #[cfg(Py_3_6)]
     pub static mut Py_FileSystemDefaultEncodeErrors: *const c_char;
     pub static mut Py_HasFileSystemDefaultEncoding: c_int;
+    #[cfg(Py_3_7)]
+    pub static mut Py_UTF8Mode: c_int;
 }
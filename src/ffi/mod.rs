@@ -21,6 +21,8 @@ pub use self::floatobject::*;
 pub use self::frameobject::PyFrameObject;
 pub use self::genobject::*;
 pub use self::import::*;
+#[cfg(all(Py_3_8, not(Py_LIMITED_API), not(PyPy)))]
+pub use self::initconfig::*;
 pub use self::intrcheck::*;
 pub use self::iterobject::*;
 pub use self::listobject::*;
@@ -116,6 +118,8 @@ mod pyarena {}
 mod modsupport; // TODO supports PEP-384 only; needs adjustment for Python 3.3 and 3.5
 #[cfg(not(Py_LIMITED_API))]
 mod pyarena; // TODO: incomplete
+#[cfg(all(Py_3_8, not(Py_LIMITED_API), not(PyPy)))]
+mod initconfig;
 mod pythonrun; // TODO some functions need to be moved to pylifecycle
                //mod pylifecycle; // TODO new in 3.5
 mod ceval; // TODO supports PEP-384 only; needs adjustment for Python 3.3 and 3.5
@@ -6,6 +6,20 @@ use crate::ffi::pyport::Py_ssize_t;
 use std::ffi::CStr;
 use std::os::raw::{c_char, c_int};
 
+#[cfg(not(Py_LIMITED_API))]
+#[repr(C)]
+pub struct PyBaseExceptionObject {
+    pub ob_base: PyObject,
+    pub dict: *mut PyObject,
+    pub args: *mut PyObject,
+    #[cfg(Py_3_11)]
+    pub notes: *mut PyObject,
+    pub traceback: *mut PyObject,
+    pub context: *mut PyObject,
+    pub cause: *mut PyObject,
+    pub suppress_context: c_char,
+}
+
 #[cfg_attr(windows, link(name = "pythonXY"))]
 extern "C" {
     #[cfg_attr(PyPy, link_name = "PyPyErr_SetNone")]
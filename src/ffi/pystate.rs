@@ -18,7 +18,10 @@ pub struct PyInterpreterState {
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct PyThreadState {
-    pub ob_base: PyObject,
+    #[cfg(not(PyPy))]
+    pub prev: *mut PyThreadState,
+    #[cfg(not(PyPy))]
+    pub next: *mut PyThreadState,
     pub interp: *mut PyInterpreterState,
 }
 
@@ -27,6 +30,8 @@ extern "C" {
     pub fn PyInterpreterState_New() -> *mut PyInterpreterState;
     pub fn PyInterpreterState_Clear(arg1: *mut PyInterpreterState) -> ();
     pub fn PyInterpreterState_Delete(arg1: *mut PyInterpreterState) -> ();
+    #[cfg(all(Py_3_9, not(PyPy)))]
+    pub fn PyInterpreterState_Get() -> *mut PyInterpreterState;
     //fn _PyState_AddModule(arg1: *mut PyObject,
     //                      arg2: *mut PyModuleDef) -> c_int;
     pub fn PyState_FindModule(arg1: *mut PyModuleDef) -> *mut PyObject;
@@ -48,6 +53,8 @@ extern "C" {
     pub fn PyThreadState_Swap(arg1: *mut PyThreadState) -> *mut PyThreadState;
     #[cfg_attr(PyPy, link_name = "PyPyThreadState_GetDict")]
     pub fn PyThreadState_GetDict() -> *mut PyObject;
+    #[cfg(all(Py_3_9, not(PyPy)))]
+    pub fn PyThreadState_GetInterpreter(tstate: *mut PyThreadState) -> *mut PyInterpreterState;
     pub fn PyThreadState_SetAsyncExc(arg1: c_long, arg2: *mut PyObject) -> c_int;
 }
 
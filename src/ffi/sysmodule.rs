@@ -1,11 +1,10 @@
 use crate::ffi::object::PyObject;
-use crate::ffi::pyport::Py_ssize_t;
-use libc::wchar_t;
+use libc::{size_t, wchar_t};
 use std::os::raw::{c_char, c_int};
 
 #[cfg_attr(windows, link(name = "pythonXY"))]
 extern "C" {
-    pub fn Py_DecodeLocale(arg1: *const c_char, arg2: Py_ssize_t) -> *mut wchar_t;
+    pub fn Py_DecodeLocale(arg1: *const c_char, size: *mut size_t) -> *mut wchar_t;
     #[cfg_attr(PyPy, link_name = "PyPySys_GetObject")]
     pub fn PySys_GetObject(arg1: *const c_char) -> *mut PyObject;
     #[cfg_attr(PyPy, link_name = "PyPySys_SetObject")]
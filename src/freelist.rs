@@ -3,10 +3,9 @@
 //! Free allocation list
 
 use crate::ffi;
-use crate::type_object::{pytype_drop, PyObjectAlloc, PyTypeInfo};
+use crate::type_object::{free_object, pytype_drop, PyObjectAlloc, PyTypeInfo};
 use crate::Python;
 use std::mem;
-use std::os::raw::c_void;
 
 /// Implementing this trait for custom class adds free allocation list to class.
 /// The performance improvement applies to types that are often created and deleted in a row,
@@ -67,6 +66,27 @@ impl<T> FreeList<T> {
     }
 }
 
+/// The free lists of the classes with `#[pyclass(freelist = N)]`, as addresses
+static FREE_LISTS: spin::Mutex<Vec<usize>> = spin::Mutex::new(Vec::new());
+
+/// Registers a free list, so that its objects are discarded when the interpreter is finalized.
+/// Used by `#[pyclass(freelist = N)]`.
+#[doc(hidden)]
+pub fn register_free_list(list: &'static mut FreeList<*mut ffi::PyObject>) {
+    FREE_LISTS
+        .lock()
+        .push(list as *mut FreeList<*mut ffi::PyObject> as usize);
+}
+
+/// Forgets the objects of the free lists after the interpreter was finalized, as they belong to
+/// it and can't be freed anymore
+pub(crate) unsafe fn reset_free_lists() {
+    for &list in FREE_LISTS.lock().iter() {
+        let list = &mut *(list as *mut FreeList<*mut ffi::PyObject>);
+        while list.pop().is_some() {}
+    }
+}
+
 impl<T> PyObjectAlloc for T
 where
     T: PyObjectWithFreeList,
@@ -81,30 +101,19 @@ where
     }
 
     unsafe fn dealloc(py: Python, obj: *mut ffi::PyObject) {
-        pytype_drop::<Self>(py, obj);
-
         if ffi::PyObject_CallFinalizerFromDealloc(obj) < 0 {
             return;
         }
 
+        pytype_drop::<Self>(py, obj);
+
+        // Instances of Python subclasses have a different size and type, so they can't be reused
+        if ffi::Py_TYPE(obj) != <Self as PyTypeInfo>::type_object() as *mut _ {
+            return free_object(obj);
+        }
+
         if let Some(obj) = <Self as PyObjectWithFreeList>::get_free_list().insert(obj) {
-            match Self::type_object().tp_free {
-                Some(free) => free(obj as *mut c_void),
-                None => {
-                    let ty = ffi::Py_TYPE(obj);
-                    if ffi::PyType_IS_GC(ty) != 0 {
-                        ffi::PyObject_GC_Del(obj as *mut c_void);
-                    } else {
-                        ffi::PyObject_Free(obj as *mut c_void);
-                    }
-
-                    // For heap types, PyType_GenericAlloc calls INCREF on the type objects,
-                    // so we need to call DECREF here:
-                    if ffi::PyType_HasFeature(ty, ffi::Py_TPFLAGS_HEAPTYPE) != 0 {
-                        ffi::Py_DECREF(ty as *mut ffi::PyObject);
-                    }
-                }
-            }
+            free_object(obj);
         }
     }
 }
@@ -2,15 +2,29 @@
 
 //! Interaction with python's global interpreter lock
 
+use crate::err::{FinalizeError, PyResult};
+use crate::exceptions::RuntimeError;
 use crate::ffi;
 use crate::types::PyAny;
 use crate::Python;
-use spin;
-use std::ptr::NonNull;
+use libc::wchar_t;
+use std::cell::Cell;
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::mem;
+use std::os::raw::c_int;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
 use std::{any, marker, rc, sync};
 
-static START: sync::Once = sync::Once::new();
+/// Whether [prepare_freethreaded_python] ran since the interpreter was last finalized
+static PREPARED: AtomicBool = AtomicBool::new(false);
+/// Serializes the initialization and the finalization of the interpreter
+static START: spin::Mutex<()> = spin::Mutex::new(());
 static START_PYO3: sync::Once = sync::Once::new();
+/// Set by [finalize], until the interpreter is initialized again
+static FINALIZED: AtomicBool = AtomicBool::new(false);
+/// Set while [finalize] runs, so that other threads wait before they acquire the GIL
+static FINALIZING: AtomicBool = AtomicBool::new(false);
 
 /// Prepares the use of Python in a free-threaded context.
 ///
@@ -21,7 +35,7 @@ static START_PYO3: sync::Once = sync::Once::new();
 /// the thread that initializes the Python interpreter.
 ///
 /// If both the Python interpreter and Python threading are already initialized,
-/// this function has no effect.
+/// this function has no effect. After [Python::finalize], it initializes a new interpreter.
 ///
 /// # Panic
 /// If the Python interpreter is initialized but Python threading is not,
@@ -30,15 +44,30 @@ static START_PYO3: sync::Once = sync::Once::new();
 /// thread (the thread which originally initialized Python) also initializes
 /// threading.
 ///
+/// It also panics if `PyObject` or `Py<T>` handles of a finalized interpreter are still alive.
+///
 /// When writing an extension module, the `#[pymodule]` macro
 /// will ensure that Python threading is initialized.
 ///
 pub fn prepare_freethreaded_python() {
+    if PREPARED.load(Ordering::Acquire) {
+        return;
+    }
     // Protect against race conditions when Python is not yet initialized
     // and multiple threads concurrently call 'prepare_freethreaded_python()'.
     // Note that we do not protect against concurrent initialization of the Python runtime
     // by other users of the Python C API.
-    START.call_once(|| unsafe {
+    let _start = START.lock();
+    if PREPARED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Err(handles) = release_finalized_handles() {
+        panic!(
+            "{} PyObject or Py<T> handles of the finalized interpreter are still alive",
+            handles
+        );
+    }
+    unsafe {
         if ffi::Py_IsInitialized() != 0 {
             // If Python is already initialized, we expect Python threading to also be initialized,
             // as we can't make the existing Python main thread acquire the GIL.
@@ -74,7 +103,8 @@ pub fn prepare_freethreaded_python() {
         }
 
         init_once();
-    });
+    }
+    PREPARED.store(true, Ordering::Release);
 }
 
 #[doc(hidden)]
@@ -85,8 +115,431 @@ pub fn init_once() {
     });
 }
 
+/// Finalizes the interpreter, see [Python::finalize]
+pub(crate) fn finalize() -> Result<(), FinalizeError> {
+    if cfg!(PyPy) {
+        return Err(FinalizeError::Unsupported);
+    }
+    if gil_is_acquired() {
+        return Err(FinalizeError::GILHeld);
+    }
+    let _start = START.lock();
+    if !PREPARED.load(Ordering::Acquire) || unsafe { ffi::Py_IsInitialized() } == 0 {
+        return Err(FinalizeError::NotInitialized);
+    }
+
+    // A thread that starts to acquire the GIL after this point waits until the interpreter is
+    // finalized, while the finalization is refused if a thread already started
+    FINALIZING.store(true, Ordering::SeqCst);
+    if ACTIVE_THREADS.load(Ordering::SeqCst) != 0 || SUB_INTERPRETERS.load(Ordering::SeqCst) != 0 {
+        FINALIZING.store(false, Ordering::SeqCst);
+        return Err(FinalizeError::InUse);
+    }
+
+    unsafe {
+        ffi::PyGILState_Ensure();
+        flush_decrefs();
+        // Runs the `atexit` handlers. The thread state is destroyed, so the GIL isn't released.
+        #[cfg(Py_3_6)]
+        ffi::Py_FinalizeEx();
+        #[cfg(not(Py_3_6))]
+        ffi::Py_Finalize();
+
+        // Objects that were dropped during the finalization, e.g. by the deallocation of a
+        // `#[pyclass]`, belong to the finalized interpreter and can't be released anymore
+        discard_decrefs();
+        crate::type_object::reset_type_objects();
+        crate::derive_utils::reset_module_interpreters();
+        crate::freelist::reset_free_lists();
+        #[cfg(not(Py_LIMITED_API))]
+        crate::ffi::datetime::reset_datetime_api();
+    }
+    FINALIZED.store(true, Ordering::Release);
+    PREPARED.store(false, Ordering::Release);
+    FINALIZING.store(false, Ordering::SeqCst);
+
+    match live_handles() {
+        0 => Ok(()),
+        handles => Err(FinalizeError::LeakedHandles(handles)),
+    }
+}
+
+/// Discards the pending decrefs of a finalized interpreter, which can't be released anymore.
+/// Fails with the number of handles of the finalized interpreter that are still alive.
+fn release_finalized_handles() -> Result<(), usize> {
+    if !FINALIZED.load(Ordering::Acquire) {
+        return Ok(());
+    }
+    match live_handles() {
+        0 => {
+            unsafe { discard_decrefs() };
+            FINALIZED.store(false, Ordering::Release);
+            Ok(())
+        }
+        handles => Err(handles),
+    }
+}
+
+/// Configuration of the Python interpreter for applications that embed it.
+///
+/// The configuration has to be applied with [InterpreterConfig::initialize] before
+/// the interpreter is initialized, i.e. before the GIL is acquired the first time. It uses
+/// the `PyConfig` API of PEP 587 on Python 3.8 and later, and the legacy global configuration
+/// variables and setters otherwise. Like [prepare_freethreaded_python], it initializes the
+/// interpreter without signal handlers and releases the GIL afterwards.
+///
+/// # Example
+/// ```
+/// use pyo3::{InterpreterConfig, Python};
+///
+/// InterpreterConfig::new()
+///     .program_name("embedded")
+///     .argv(vec!["embedded", "--verbose"])
+///     .path("/opt/embedded/lib")
+///     .initialize()
+///     .unwrap();
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let sys = py.import("sys").unwrap();
+/// let argv: Vec<String> = sys.get("argv").unwrap().extract().unwrap();
+/// assert_eq!(argv, vec!["embedded", "--verbose"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct InterpreterConfig {
+    program_name: Option<OsString>,
+    argv: Vec<OsString>,
+    paths: Vec<OsString>,
+    home: Option<OsString>,
+    isolated: bool,
+    utf8_mode: Option<bool>,
+    site_import: bool,
+}
+
+impl Default for InterpreterConfig {
+    fn default() -> Self {
+        InterpreterConfig {
+            program_name: None,
+            argv: Vec::new(),
+            paths: Vec::new(),
+            home: None,
+            isolated: false,
+            utf8_mode: None,
+            site_import: true,
+        }
+    }
+}
+
+impl InterpreterConfig {
+    /// Creates a configuration with python's defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the program name, which is used to find the standard library relative to the
+    /// executable.
+    pub fn program_name(mut self, name: impl Into<OsString>) -> Self {
+        self.program_name = Some(name.into());
+        self
+    }
+
+    /// Sets `sys.argv`. The arguments are not parsed as options of the interpreter.
+    pub fn argv<I>(mut self, argv: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString>,
+    {
+        self.argv = argv.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Appends an entry to `sys.path`, after the paths that python computes itself.
+    pub fn path(mut self, path: impl Into<OsString>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Sets the location of the standard library, like the `PYTHONHOME` environment variable.
+    pub fn home(mut self, home: impl Into<OsString>) -> Self {
+        self.home = Some(home.into());
+        self
+    }
+
+    /// Enables the isolated mode, which ignores the environment variables of python and the
+    /// user's site-packages directory, like `python -I`.
+    pub fn isolated(mut self, isolated: bool) -> Self {
+        self.isolated = isolated;
+        self
+    }
+
+    /// Enables or disables the UTF-8 mode of PEP 540, like `python -X utf8`. By default, it
+    /// depends on the locale and the environment. It needs Python 3.7 or later.
+    pub fn utf8_mode(mut self, utf8_mode: bool) -> Self {
+        self.utf8_mode = Some(utf8_mode);
+        self
+    }
+
+    /// Enables or disables the import of the `site` module at startup, which is disabled
+    /// by `python -S`.
+    pub fn site_import(mut self, site_import: bool) -> Self {
+        self.site_import = site_import;
+        self
+    }
+
+    /// Initializes the interpreter with this configuration.
+    ///
+    /// Returns a `RuntimeError` if the interpreter is already initialized, including by
+    /// a previous call to [prepare_freethreaded_python] or `Python::acquire_gil`, or if
+    /// python fails to apply the configuration.
+    pub fn initialize(self) -> PyResult<()> {
+        #[cfg(PyPy)]
+        {
+            Err(RuntimeError::py_err(
+                "PyPy does not support the embedding API",
+            ))
+        }
+
+        #[cfg(not(PyPy))]
+        unsafe {
+            if ffi::Py_IsInitialized() != 0 {
+                return Err(RuntimeError::py_err(
+                    "The Python interpreter is already initialized",
+                ));
+            }
+            if let Err(handles) = release_finalized_handles() {
+                return Err(RuntimeError::py_err(format!(
+                    "{} PyObject or Py<T> handles of the finalized interpreter are still alive",
+                    handles
+                )));
+            }
+            self.apply()?;
+
+            self.append_paths();
+            #[cfg(not(Py_3_7))]
+            ffi::PyEval_InitThreads();
+            // Like in `prepare_freethreaded_python`, don't hold the GIL at this point
+            let _thread_state = ffi::PyEval_SaveThread();
+
+            // Python is initialized now, so this only initializes pyo3
+            prepare_freethreaded_python();
+            Ok(())
+        }
+    }
+
+    /// Initializes the interpreter with `Py_InitializeFromConfig`
+    #[cfg(all(Py_3_8, not(PyPy)))]
+    unsafe fn apply(&self) -> PyResult<()> {
+        let mut preconfig: ffi::PyPreConfig = mem::zeroed();
+        if self.isolated {
+            ffi::PyPreConfig_InitIsolatedConfig(&mut preconfig);
+        } else {
+            ffi::PyPreConfig_InitPythonConfig(&mut preconfig);
+        }
+        if let Some(utf8_mode) = self.utf8_mode {
+            preconfig.utf8_mode = utf8_mode as c_int;
+        }
+        check_status(ffi::Py_PreInitialize(&preconfig))?;
+
+        let mut config: ffi::PyConfig = mem::zeroed();
+        if self.isolated {
+            ffi::PyConfig_InitIsolatedConfig(&mut config);
+        } else {
+            ffi::PyConfig_InitPythonConfig(&mut config);
+        }
+        let result = self.fill_config(&mut config);
+        let result = result.and_then(|()| check_status(ffi::Py_InitializeFromConfig(&config)));
+        ffi::PyConfig_Clear(&mut config);
+        result
+    }
+
+    #[cfg(all(Py_3_8, not(PyPy)))]
+    unsafe fn fill_config(&self, config: *mut ffi::PyConfig) -> PyResult<()> {
+        // The equivalent of the `initsigs = 0` of `Py_InitializeEx`
+        (*config).install_signal_handlers = 0;
+        (*config).parse_argv = 0;
+        (*config).site_import = self.site_import as c_int;
+
+        if let Some(program_name) = &self.program_name {
+            let program_name = WideString::new(program_name)?;
+            let field = &mut (*config).program_name;
+            check_status(ffi::PyConfig_SetString(
+                config,
+                field,
+                program_name.as_ptr(),
+            ))?;
+        }
+        if let Some(home) = &self.home {
+            let home = WideString::new(home)?;
+            let field = &mut (*config).home;
+            check_status(ffi::PyConfig_SetString(config, field, home.as_ptr()))?;
+        }
+        if !self.argv.is_empty() {
+            let argv = self
+                .argv
+                .iter()
+                .map(|arg| WideString::new(arg))
+                .collect::<PyResult<Vec<_>>>()?;
+            let pointers: Vec<_> = argv.iter().map(WideString::as_ptr).collect();
+            let argc = pointers.len() as ffi::Py_ssize_t;
+            check_status(ffi::PyConfig_SetArgv(config, argc, pointers.as_ptr()))?;
+        }
+        Ok(())
+    }
+
+    /// Initializes the interpreter with the global configuration variables and `Py_InitializeEx`
+    #[cfg(all(not(Py_3_8), not(PyPy)))]
+    unsafe fn apply(&self) -> PyResult<()> {
+        // Convert everything first, so that nothing is changed if it fails
+        let program_name = self
+            .program_name
+            .as_ref()
+            .map(|s| WideString::new(s))
+            .transpose()?;
+        let home = self.home.as_ref().map(|s| WideString::new(s)).transpose()?;
+        let argv = self
+            .argv
+            .iter()
+            .map(|arg| WideString::new(arg))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        if let Some(utf8_mode) = self.utf8_mode {
+            #[cfg(Py_3_7)]
+            {
+                ffi::Py_UTF8Mode = utf8_mode as c_int;
+            }
+            #[cfg(not(Py_3_7))]
+            {
+                let _ = utf8_mode;
+                return Err(RuntimeError::py_err("The UTF-8 mode needs Python 3.7"));
+            }
+        }
+        // The program name and home have to stay valid while the interpreter runs
+        if let Some(program_name) = program_name {
+            ffi::Py_SetProgramName(program_name.leak());
+        }
+        if let Some(home) = home {
+            ffi::Py_SetPythonHome(home.leak());
+        }
+        if self.isolated {
+            ffi::Py_IsolatedFlag = 1;
+            ffi::Py_IgnoreEnvironmentFlag = 1;
+            ffi::Py_NoUserSiteDirectory = 1;
+        }
+        if !self.site_import {
+            ffi::Py_NoSiteFlag = 1;
+        }
+
+        ffi::Py_InitializeEx(0);
+
+        if !argv.is_empty() {
+            let mut pointers: Vec<_> = argv.iter().map(WideString::as_ptr).collect();
+            // Don't prepend the directory of the script to `sys.path`
+            ffi::PySys_SetArgvEx(pointers.len() as c_int, pointers.as_mut_ptr(), 0);
+        }
+        Ok(())
+    }
+
+    /// Appends the paths to `sys.path`. The GIL must be held.
+    #[cfg(not(PyPy))]
+    unsafe fn append_paths(&self) {
+        let sys_path = ffi::PySys_GetObject("path\0".as_ptr() as *const _);
+        for path in &self.paths {
+            let entry = match WideString::new(path) {
+                Ok(path) => ffi::PyUnicode_FromWideChar(path.as_ptr(), -1),
+                Err(_) => ptr::null_mut(),
+            };
+            if entry.is_null() || sys_path.is_null() || ffi::PyList_Append(sys_path, entry) != 0 {
+                ffi::PyErr_Clear();
+            }
+            ffi::Py_XDECREF(entry);
+        }
+    }
+}
+
+/// Converts a `PyStatus` that is an error or an exit into a `RuntimeError`
+#[cfg(all(Py_3_8, not(PyPy)))]
+unsafe fn check_status(status: ffi::PyStatus) -> PyResult<()> {
+    if ffi::PyStatus_Exception(status) == 0 {
+        return Ok(());
+    }
+    let message = if status.err_msg.is_null() {
+        format!("Python exited with code {}", status.exitcode)
+    } else {
+        CStr::from_ptr(status.err_msg)
+            .to_string_lossy()
+            .into_owned()
+    };
+    Err(RuntimeError::py_err(format!(
+        "Failed to initialize python: {}",
+        message
+    )))
+}
+
+/// A nul-terminated wide string allocated with `PyMem_RawMalloc`, as expected by the
+/// configuration API of python
+#[cfg(not(PyPy))]
+struct WideString(NonNull<wchar_t>);
+
+#[cfg(not(PyPy))]
+impl WideString {
+    #[cfg(unix)]
+    fn new(s: &OsStr) -> PyResult<WideString> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let bytes = CString::new(s.as_bytes())?;
+        let wide = unsafe { ffi::Py_DecodeLocale(bytes.as_ptr(), ptr::null_mut()) };
+        NonNull::new(wide)
+            .map(WideString)
+            .ok_or_else(|| RuntimeError::py_err(format!("Failed to decode {:?}", s)))
+    }
+
+    #[cfg(windows)]
+    fn new(s: &OsStr) -> PyResult<WideString> {
+        use std::os::windows::ffi::OsStrExt;
+
+        let wide: Vec<u16> = s.encode_wide().chain(Some(0)).collect();
+        if wide[..wide.len() - 1].contains(&0) {
+            return Err(crate::exceptions::ValueError::py_err(format!(
+                "{:?} contains a nul character",
+                s
+            )));
+        }
+        unsafe {
+            let ptr = ffi::PyMem_RawMalloc(wide.len() * mem::size_of::<u16>()) as *mut u16;
+            let ptr = NonNull::new(ptr).ok_or_else(|| {
+                crate::exceptions::MemoryError::py_err("Failed to allocate a string")
+            })?;
+            ptr::copy_nonoverlapping(wide.as_ptr(), ptr.as_ptr(), wide.len());
+            Ok(WideString(ptr.cast()))
+        }
+    }
+
+    fn as_ptr(&self) -> *mut wchar_t {
+        self.0.as_ptr()
+    }
+
+    /// Leaks the string, for the setters that expect it to be static
+    #[cfg(not(Py_3_8))]
+    fn leak(self) -> *mut wchar_t {
+        let ptr = self.as_ptr();
+        mem::forget(self);
+        ptr
+    }
+}
+
+#[cfg(not(PyPy))]
+impl Drop for WideString {
+    fn drop(&mut self) {
+        unsafe { ffi::PyMem_RawFree(self.0.as_ptr() as *mut _) }
+    }
+}
+
 /// RAII type that represents the Global Interpreter Lock acquisition.
 ///
+/// Objects owned by the guard are released when it is dropped, so guards of the same thread
+/// must be dropped in the reverse order of their acquisition. Dropping them in another order
+/// panics. [Python::with_gil] scopes the acquisition to a closure and can't be misused this way.
+///
 /// # Example
 /// ```
 /// use pyo3::Python;
@@ -100,20 +553,35 @@ pub fn init_once() {
 pub struct GILGuard {
     owned: usize,
     borrowed: usize,
-    gstate: ffi::PyGILState_STATE,
+    obj: usize,
+    /// The GIL count of the thread before the guard was acquired
+    depth: usize,
+    /// `None` if the thread already held the GIL
+    gstate: Option<ffi::PyGILState_STATE>,
     // hack to opt out of Send on stable rust, which doesn't
     // have negative impls
     no_send: marker::PhantomData<rc::Rc<()>>,
 }
 
+#[cfg(feature = "nightly")]
+impl !crate::Ungil for GILGuard {}
+
 /// The Drop implementation for `GILGuard` will release the GIL.
 impl Drop for GILGuard {
     fn drop(&mut self) {
+        let count = GIL_COUNT.with(Cell::get);
+        if count != self.depth + 1 && !std::thread::panicking() {
+            panic!("GILGuards must be dropped in the reverse order of their acquisition");
+        }
+        decrement_gil_count();
+
         unsafe {
             let pool: &'static mut ReleasePool = &mut *POOL;
-            pool.drain(self.python(), self.owned, self.borrowed, true);
+            pool.drain(self.python(), self.owned, self.borrowed, self.obj, true);
 
-            ffi::PyGILState_Release(self.gstate);
+            if let Some(gstate) = self.gstate {
+                ffi::PyGILState_Release(gstate);
+            }
         }
     }
 }
@@ -122,9 +590,7 @@ impl Drop for GILGuard {
 struct ReleasePool {
     owned: ArrayList<NonNull<ffi::PyObject>>,
     borrowed: ArrayList<NonNull<ffi::PyObject>>,
-    pointers: *mut Vec<NonNull<ffi::PyObject>>,
     obj: Vec<Box<dyn any::Any>>,
-    p: spin::Mutex<*mut Vec<NonNull<ffi::PyObject>>>,
 }
 
 impl ReleasePool {
@@ -132,31 +598,21 @@ impl ReleasePool {
         ReleasePool {
             owned: ArrayList::new(),
             borrowed: ArrayList::new(),
-            pointers: Box::into_raw(Box::new(Vec::with_capacity(256))),
             obj: Vec::with_capacity(8),
-            p: spin::Mutex::new(Box::into_raw(Box::new(Vec::with_capacity(256)))),
         }
     }
 
-    unsafe fn release_pointers(&mut self) {
-        let mut v = self.p.lock();
-        let vec = &mut **v;
-        if vec.is_empty() {
-            return;
-        }
-
-        // switch vectors
-        std::mem::swap(&mut self.pointers, &mut *v);
-        drop(v);
+    pub unsafe fn drain(
+        &mut self,
+        _py: Python,
+        owned: usize,
+        borrowed: usize,
+        obj: usize,
+        pointers: bool,
+    ) {
+        // The registered values may refer to the owned objects, so they are dropped first
+        self.obj.truncate(obj);
 
-        // release PyObjects
-        for ptr in vec.iter_mut() {
-            ffi::Py_DECREF(ptr.as_ptr());
-        }
-        vec.set_len(0);
-    }
-
-    pub unsafe fn drain(&mut self, _py: Python, owned: usize, borrowed: usize, pointers: bool) {
         // Release owned objects(call decref)
         while owned < self.owned.len() {
             let last = self.owned.pop_back().unwrap();
@@ -166,10 +622,8 @@ impl ReleasePool {
         self.borrowed.truncate(borrowed);
 
         if pointers {
-            self.release_pointers();
+            flush_decrefs();
         }
-
-        self.obj.clear();
     }
 }
 
@@ -180,6 +634,7 @@ pub struct GILPool<'p> {
     py: Python<'p>,
     owned: usize,
     borrowed: usize,
+    obj: usize,
     pointers: bool,
     no_send: marker::PhantomData<rc::Rc<()>>,
 }
@@ -187,22 +642,29 @@ pub struct GILPool<'p> {
 impl<'p> GILPool<'p> {
     #[inline]
     pub fn new(py: Python) -> GILPool {
+        increment_gil_count();
+        unsafe {
+            flush_decrefs();
+        }
         let p: &'static mut ReleasePool = unsafe { &mut *POOL };
         GILPool {
             py,
             owned: p.owned.len(),
             borrowed: p.borrowed.len(),
+            obj: p.obj.len(),
             pointers: true,
             no_send: marker::PhantomData,
         }
     }
     #[inline]
     pub fn new_no_pointers(py: Python) -> GILPool {
+        increment_gil_count();
         let p: &'static mut ReleasePool = unsafe { &mut *POOL };
         GILPool {
             py,
             owned: p.owned.len(),
             borrowed: p.borrowed.len(),
+            obj: p.obj.len(),
             pointers: false,
             no_send: marker::PhantomData,
         }
@@ -211,9 +673,10 @@ impl<'p> GILPool<'p> {
 
 impl<'p> Drop for GILPool<'p> {
     fn drop(&mut self) {
+        decrement_gil_count();
         unsafe {
             let pool: &'static mut ReleasePool = &mut *POOL;
-            pool.drain(self.py, self.owned, self.borrowed, self.pointers);
+            pool.drain(self.py, self.owned, self.borrowed, self.obj, self.pointers);
         }
     }
 }
@@ -230,9 +693,99 @@ pub unsafe fn register_any<'p, T: 'static>(obj: T) -> &'p T {
         .unwrap()
 }
 
+/// An object whose reference count has to be decremented the next time the GIL is acquired
+struct PendingDecref {
+    obj: NonNull<ffi::PyObject>,
+    next: *mut PendingDecref,
+}
+
+/// The objects that were dropped since the last flush, as a lock-free stack that is taken
+/// as a whole by [flush_decrefs]
+static PENDING_DECREFS: AtomicPtr<PendingDecref> = AtomicPtr::new(ptr::null_mut());
+static PENDING_DECREF_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Defers the decref of `obj` until the GIL is acquired the next time.
+///
+/// This is used by the `Drop` implementations of `PyObject` and `Py<T>`, which can run on any
+/// thread, with or without the GIL.
 pub unsafe fn register_pointer(obj: NonNull<ffi::PyObject>) {
-    let pool = &mut *POOL;
-    (**pool.p.lock()).push(obj);
+    let node = Box::into_raw(Box::new(PendingDecref {
+        obj,
+        next: ptr::null_mut(),
+    }));
+    PENDING_DECREF_COUNT.fetch_add(1, Ordering::Relaxed);
+
+    let mut head = PENDING_DECREFS.load(Ordering::Relaxed);
+    loop {
+        (*node).next = head;
+        match PENDING_DECREFS.compare_exchange_weak(
+            head,
+            node,
+            Ordering::Release,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => return,
+            Err(current) => head = current,
+        }
+    }
+}
+
+/// Decrements the reference counts of the objects that were dropped since the last flush and
+/// returns their number. The GIL must be held.
+pub(crate) unsafe fn flush_decrefs() -> usize {
+    // Decrefs can run arbitrary python code, which may drop other objects. Those are pushed to
+    // the new stack and released by the next flush.
+    let mut node = PENDING_DECREFS.swap(ptr::null_mut(), Ordering::Acquire);
+    let mut count = 0;
+    while !node.is_null() {
+        let pending = Box::from_raw(node);
+        node = pending.next;
+        ffi::Py_DECREF(pending.obj.as_ptr());
+        count += 1;
+    }
+    PENDING_DECREF_COUNT.fetch_sub(count, Ordering::Relaxed);
+    count
+}
+
+/// Frees the pending decrefs without releasing the objects
+unsafe fn discard_decrefs() {
+    let mut node = PENDING_DECREFS.swap(ptr::null_mut(), Ordering::Acquire);
+    let mut count = 0;
+    while !node.is_null() {
+        node = Box::from_raw(node).next;
+        count += 1;
+    }
+    PENDING_DECREF_COUNT.fetch_sub(count, Ordering::Relaxed);
+}
+
+/// The number of objects that were dropped without being released yet
+pub(crate) fn pending_decrefs() -> usize {
+    PENDING_DECREF_COUNT.load(Ordering::Relaxed)
+}
+
+/// The number of `PyObject`s and `Py<T>`s that are alive, which must be zero when the
+/// interpreter is finalized.
+///
+/// Handles are created and dropped all the time, so the counting only uses relaxed operations,
+/// which don't need any fences (see `benches/bench_object.rs`).
+/// The counter is only read by the thread that finalizes the interpreter while holding the GIL,
+/// so it is ordered with the changes of all other threads that held the GIL before.
+static LIVE_HANDLES: AtomicUsize = AtomicUsize::new(0);
+
+fn live_handles() -> usize {
+    LIVE_HANDLES.load(Ordering::Relaxed)
+}
+
+/// Called when a `PyObject` or `Py<T>` is created
+#[inline]
+pub(crate) fn handle_created() {
+    LIVE_HANDLES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Called when a `PyObject` or `Py<T>` is dropped or turned into a pointer
+#[inline]
+pub(crate) fn handle_released() {
+    LIVE_HANDLES.fetch_sub(1, Ordering::Relaxed);
 }
 
 pub unsafe fn register_owned(_py: Python, obj: NonNull<ffi::PyObject>) -> &PyAny {
@@ -250,18 +803,43 @@ impl GILGuard {
     ///
     /// If the Python runtime is not already initialized, this function will initialize it.
     /// See [prepare_freethreaded_python()](fn.prepare_freethreaded_python.html) for details.
+    ///
+    /// If the current thread already holds the GIL, e.g. inside of [SubInterpreter::run],
+    /// the guard only creates a new pool for owned objects.
     pub fn acquire() -> GILGuard {
+        let outermost = !gil_is_acquired();
+        // Counts the thread as active before it checks for a concurrent finalization, and
+        // undoes it if the initialization panics
+        let depth = increment_gil_count();
+        let count = GILCountGuard;
+        if outermost {
+            while FINALIZING.load(Ordering::SeqCst) {
+                std::thread::yield_now();
+            }
+        }
         prepare_freethreaded_python();
 
         unsafe {
-            let gstate = ffi::PyGILState_Ensure(); // acquire GIL
+            // `PyGILState_Ensure` only knows about the main interpreter, and would deadlock
+            // trying to switch back to it from a sub-interpreter
+            let gstate = if outermost {
+                Some(ffi::PyGILState_Ensure()) // acquire GIL
+            } else {
+                None
+            };
+            flush_decrefs();
             let pool: &'static mut ReleasePool = &mut *POOL;
-            GILGuard {
+            let guard = GILGuard {
                 owned: pool.owned.len(),
                 borrowed: pool.borrowed.len(),
+                obj: pool.obj.len(),
+                depth,
                 gstate,
                 no_send: marker::PhantomData,
-            }
+            };
+            // The count is decremented when the guard is dropped
+            mem::forget(count);
+            guard
         }
     }
 
@@ -272,6 +850,203 @@ impl GILGuard {
     }
 }
 
+thread_local! {
+    /// The number of `GILGuard`s and `GILPool`s of the current thread. The thread holds the GIL
+    /// if it isn't zero.
+    static GIL_COUNT: Cell<usize> = Cell::new(0);
+}
+
+/// The number of threads with a non-zero GIL count, including those that released the GIL
+/// temporarily with `allow_threads`. The interpreter can only be finalized if it is zero.
+static ACTIVE_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the previous count
+fn increment_gil_count() -> usize {
+    let previous = GIL_COUNT.with(|count| {
+        let previous = count.get();
+        count.set(previous + 1);
+        previous
+    });
+    if previous == 0 {
+        ACTIVE_THREADS.fetch_add(1, Ordering::SeqCst);
+    }
+    previous
+}
+
+/// Decrements the GIL count when it's dropped, unless it's forgotten
+struct GILCountGuard;
+
+impl Drop for GILCountGuard {
+    fn drop(&mut self) {
+        decrement_gil_count();
+    }
+}
+
+fn decrement_gil_count() {
+    let count = GIL_COUNT.with(|count| {
+        count.set(count.get() - 1);
+        count.get()
+    });
+    if count == 0 {
+        ACTIVE_THREADS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Whether the current thread holds the GIL through a `GILGuard` or a `GILPool`
+pub(crate) fn gil_is_acquired() -> bool {
+    GIL_COUNT.with(Cell::get) != 0
+}
+
+/// Releases the GIL and sets the GIL count of the current thread to zero, so that it can be
+/// acquired again by the thread. Both are restored when dropped.
+pub(crate) struct SuspendGIL {
+    count: usize,
+    tstate: *mut ffi::PyThreadState,
+}
+
+impl SuspendGIL {
+    pub(crate) unsafe fn new() -> SuspendGIL {
+        let count = GIL_COUNT.with(|count| count.replace(0));
+        let tstate = ffi::PyEval_SaveThread();
+        SuspendGIL { count, tstate }
+    }
+}
+
+impl Drop for SuspendGIL {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::PyEval_RestoreThread(self.tstate);
+        }
+        GIL_COUNT.with(|count| count.set(self.count));
+    }
+}
+
+/// The number of `SubInterpreter`s that are alive
+static SUB_INTERPRETERS: AtomicUsize = AtomicUsize::new(0);
+
+/// The interpreter that the current thread runs in
+#[cfg(all(Py_3_9, not(PyPy)))]
+pub(crate) unsafe fn current_interpreter() -> *mut ffi::PyInterpreterState {
+    ffi::PyInterpreterState_Get()
+}
+
+/// The interpreter that the current thread runs in
+#[cfg(not(any(Py_3_9, Py_LIMITED_API, PyPy)))]
+pub(crate) unsafe fn current_interpreter() -> *mut ffi::PyInterpreterState {
+    (*ffi::PyThreadState_Get()).interp
+}
+
+/// The interpreter that the current thread runs in.
+///
+/// The thread state is opaque here, so all interpreters are treated as the main one.
+#[cfg(any(PyPy, all(Py_LIMITED_API, not(Py_3_9))))]
+pub(crate) unsafe fn current_interpreter() -> *mut ffi::PyInterpreterState {
+    ptr::null_mut()
+}
+
+/// An isolated python sub-interpreter, created with `Py_NewInterpreter`.
+///
+/// A sub-interpreter has its own imported modules, `sys` and builtins. Code runs in it with
+/// [SubInterpreter::run], which switches the current thread into the sub-interpreter and back.
+/// Python objects belong to the interpreter they were created in and must not be moved to
+/// another one, which is why `run` is unsafe.
+///
+/// `#[pyclass]` type objects and modules with single-phase initialization are created once per
+/// process, so adding them to a module in a second interpreter raises an `ImportError`.
+/// Modules with `#[pymodule(multi_phase)]` are created separately in every interpreter.
+///
+/// The sub-interpreter is ended when it is dropped, which acquires the GIL.
+///
+/// # Example
+/// ```
+/// use pyo3::{Python, SubInterpreter};
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// py.run("import sys; sys.answer = 42", None, None).unwrap();
+///
+/// let sub = SubInterpreter::new(py).unwrap();
+/// unsafe {
+///     sub.run(py, |py| {
+///         py.run("import sys; assert not hasattr(sys, 'answer')", None, None)
+///     })
+/// }
+/// .unwrap();
+/// ```
+pub struct SubInterpreter {
+    tstate: NonNull<ffi::PyThreadState>,
+    no_send: marker::PhantomData<rc::Rc<()>>,
+}
+
+impl SubInterpreter {
+    /// Creates a new sub-interpreter. The current thread stays in its interpreter.
+    pub fn new(_py: Python) -> PyResult<SubInterpreter> {
+        unsafe {
+            let prev = ffi::PyThreadState_Get();
+            let tstate = ffi::Py_NewInterpreter();
+            // `Py_NewInterpreter` makes the new interpreter the current one
+            ffi::PyThreadState_Swap(prev);
+            match NonNull::new(tstate) {
+                Some(tstate) => {
+                    SUB_INTERPRETERS.fetch_add(1, Ordering::SeqCst);
+                    Ok(SubInterpreter {
+                        tstate,
+                        no_send: marker::PhantomData,
+                    })
+                }
+                None => Err(RuntimeError::py_err("Failed to create a sub-interpreter")),
+            }
+        }
+    }
+
+    /// Runs `f` in the sub-interpreter, and switches back to the current interpreter
+    /// afterwards, even if `f` panics.
+    ///
+    /// `f` gets its own `Python` token, so that references to the objects of the
+    /// sub-interpreter can't escape the closure, and it must be [Ungil](crate::Ungil),
+    /// so that it can't capture references to the objects of the current interpreter.
+    ///
+    /// # Safety
+    ///
+    /// Owned handles like `PyObject` and `Py<T>` are `Ungil`, so the type system can't keep them
+    /// in their interpreter. The caller must ensure that `f` neither uses a handle to an object
+    /// of another interpreter nor returns one, and that no handle to an object of the
+    /// sub-interpreter is kept after the sub-interpreter is dropped.
+    pub unsafe fn run<F, R>(&self, _py: Python, f: F) -> R
+    where
+        F: for<'s> FnOnce(Python<'s>) -> R + crate::Ungil,
+    {
+        /// Switches back to the previous thread state when dropped
+        struct Restore(*mut ffi::PyThreadState);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                unsafe {
+                    ffi::PyThreadState_Swap(self.0);
+                }
+            }
+        }
+
+        let _restore = Restore(ffi::PyThreadState_Swap(self.tstate.as_ptr()));
+        let py = Python::assume_gil_acquired();
+        let _pool = GILPool::new(py);
+        f(py)
+    }
+}
+
+impl Drop for SubInterpreter {
+    fn drop(&mut self) {
+        let _gil = Python::acquire_gil();
+        unsafe {
+            let prev = ffi::PyThreadState_Swap(self.tstate.as_ptr());
+            // `Py_EndInterpreter` leaves the thread without a current thread state
+            ffi::Py_EndInterpreter(self.tstate.as_ptr());
+            ffi::PyThreadState_Swap(prev);
+        }
+        SUB_INTERPRETERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 use self::array_list::ArrayList;
 
 mod array_list {
@@ -330,12 +1105,13 @@ mod array_list {
 
 #[cfg(test)]
 mod test {
-    use super::{GILPool, NonNull, ReleasePool, POOL};
+    use super::{gil_is_acquired, GILPool, NonNull, ReleasePool, GIL_COUNT, POOL};
     use crate::object::PyObject;
-    use crate::AsPyPointer;
+    use crate::ObjectProtocol;
     use crate::Python;
     use crate::ToPyObject;
     use crate::{ffi, gil};
+    use crate::{AsPyPointer, AsPyRef};
 
     fn get_object() -> PyObject {
         // Convenience function for getting a single unique object
@@ -502,4 +1278,111 @@ mod test {
             assert_eq!(ffi::Py_REFCNT(obj_ptr), 1);
         }
     }
+
+    #[test]
+    fn test_with_gil() {
+        gil::init_once();
+        let obj = get_object();
+        let obj_ptr = obj.as_ptr();
+
+        let count = Python::with_gil(|py| {
+            let _ref = obj.clone_ref(py);
+            assert!(gil_is_acquired());
+            let outer = GIL_COUNT.with(|c| c.get());
+
+            Python::with_gil(|py| {
+                assert_eq!(GIL_COUNT.with(|c| c.get()), outer + 1);
+                unsafe {
+                    let _ = gil::register_owned(py, obj.clone_ref(py).into_nonnull());
+                    assert_eq!(ffi::Py_REFCNT(obj.as_ptr()), 3);
+                }
+            });
+            // the nested call released its owned objects
+            unsafe {
+                assert_eq!(ffi::Py_REFCNT(obj.as_ptr()), 2);
+            }
+            outer
+        });
+        assert_eq!(GIL_COUNT.with(|c| c.get()), count - 1);
+
+        let gil = Python::acquire_gil();
+        unsafe {
+            assert_eq!(ffi::Py_REFCNT(obj_ptr), 1);
+        }
+        drop(gil);
+    }
+
+    #[test]
+    fn test_allow_threads() {
+        gil::init_once();
+        Python::with_gil(|py| {
+            let count = GIL_COUNT.with(|c| c.get());
+            let two = py.allow_threads(|| {
+                assert!(!gil_is_acquired());
+                // the GIL is really acquired again
+                Python::with_gil(|py| {
+                    let two = py.eval("1 + 1", None, None).unwrap();
+                    two.extract::<i32>().unwrap()
+                })
+            });
+            assert_eq!(two, 2);
+            assert_eq!(GIL_COUNT.with(|c| c.get()), count);
+        });
+    }
+
+    #[test]
+    fn test_with_pool() {
+        gil::init_once();
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let p: &'static mut ReleasePool = unsafe { &mut *POOL };
+
+        let list = py.eval("[1, 2, 3]", None, None).unwrap().to_object(py);
+        let owned = p.owned.len();
+        for _ in 0..1000 {
+            let len = py.with_pool(|py| {
+                let obj = py.eval("[4, 5]", None, None).unwrap();
+                assert_eq!(unsafe { (*POOL).owned.len() }, owned + 1);
+                obj.len().unwrap() + list.as_ref(py).len().unwrap()
+            });
+            assert_eq!(len, 5);
+            assert_eq!(p.owned.len(), owned);
+        }
+    }
+
+    #[test]
+    fn test_drop_without_gil() {
+        gil::init_once();
+        let obj = get_object();
+        let obj_ptr = obj.as_ptr();
+        let _ref = Python::with_gil(|py| obj.clone_ref(py));
+
+        std::thread::spawn(move || drop(obj)).join().unwrap();
+        // acquiring the GIL flushes the pending decrefs
+        let gil = Python::acquire_gil();
+        unsafe {
+            assert_eq!(ffi::Py_REFCNT(obj_ptr), 1);
+        }
+        drop(gil);
+    }
+
+    #[test]
+    fn test_flush_pending_decrefs() {
+        gil::init_once();
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = get_object();
+        let obj_ptr = obj.as_ptr();
+        let _ref = obj.clone_ref(py);
+
+        drop(obj);
+        assert!(super::pending_decrefs() >= 1);
+        unsafe {
+            assert_eq!(ffi::Py_REFCNT(obj_ptr), 2);
+        }
+        assert!(py.flush_pending_decrefs() >= 1);
+        unsafe {
+            assert_eq!(ffi::Py_REFCNT(obj_ptr), 1);
+        }
+    }
 }
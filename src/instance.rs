@@ -1,14 +1,16 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
-use crate::err::{PyErr, PyResult};
+use crate::err::{PyBorrowError, PyErr, PyResult};
 use crate::gil;
 use crate::instance;
 use crate::object::PyObject;
 use crate::objectprotocol::ObjectProtocol;
-use crate::type_object::PyTypeCreate;
+use crate::type_object::{BorrowGuard, PyTypeCreate};
 use crate::type_object::{PyTypeInfo, PyTypeObject};
 use crate::types::PyAny;
 use crate::{ffi, IntoPy};
-use crate::{AsPyPointer, FromPyObject, FromPyPointer, IntoPyPointer, Python, ToPyObject};
+use crate::{
+    AsPyPointer, FromPyObject, FromPyPointer, IntoPyPointer, PyTryFrom, Python, ToPyObject,
+};
 use std::marker::PhantomData;
 use std::mem;
 use std::ops::{Deref, DerefMut};
@@ -32,6 +34,11 @@ pub unsafe trait PyNativeType: Sized {
 /// objects until copied to the Python heap. So, instead of treating `&pyclass`es as Python objects,
 /// we need to use special reference types `PyRef` and `PyRefMut`.
 ///
+/// Like a `std::cell::Ref`, a `PyRef` of a `#[pyclass]` holds a shared borrow of the instance
+/// until it is dropped. Creating a `PyRef` while the instance is mutably borrowed, e.g. by a
+/// running `&mut self` method, fails with [`PyBorrowError`](struct.PyBorrowError.html) or, for
+/// the methods that can't return an error, panics.
+///
 /// # Example
 ///
 /// ```
@@ -55,7 +62,10 @@ pub unsafe trait PyNativeType: Sized {
 /// py.run("assert p.length() == 12", None, Some(d)).unwrap();
 /// ```
 #[derive(Debug)]
-pub struct PyRef<'a, T: PyTypeInfo>(&'a T, PhantomData<Rc<()>>);
+pub struct PyRef<'a, T: PyTypeInfo>(&'a T, BorrowGuard, PhantomData<Rc<()>>);
+
+#[cfg(feature = "nightly")]
+impl<'a, T: PyTypeInfo> !crate::Ungil for PyRef<'a, T> {}
 
 #[allow(clippy::cast_ptr_alignment)]
 fn ref_to_ptr<T>(t: &T) -> *mut ffi::PyObject
@@ -66,8 +76,46 @@ where
 }
 
 impl<'a, T: PyTypeInfo> PyRef<'a, T> {
+    /// Borrows `r`, which must be part of a python object
+    pub(crate) fn try_from_ref(r: &'a T) -> Result<Self, PyBorrowError> {
+        let guard = unsafe { BorrowGuard::try_borrow::<T>(ref_to_ptr(r))? };
+        Ok(PyRef(r, guard, PhantomData))
+    }
+
+    /// Borrows `r`, panicking if it is mutably borrowed
     pub(crate) fn from_ref(r: &'a T) -> Self {
-        PyRef(r, PhantomData)
+        Self::try_from_ref(r).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Borrows the instance of `T` behind `ptr`, or returns an error if it is mutably borrowed.
+    ///
+    /// Panics if `ptr` is null. Undefined behavior if `ptr` isn't an instance of `T`.
+    pub unsafe fn try_from_borrowed_ptr(
+        py: Python<'a>,
+        ptr: *mut ffi::PyObject,
+    ) -> Result<Self, PyBorrowError> {
+        Self::try_from_ref(FromPyPointer::from_borrowed_ptr(py, ptr))
+    }
+
+    /// Takes ownership of `ptr` and borrows the instance of `T` behind it.
+    ///
+    /// Returns the python error if `ptr` is null, and a `PyBorrowError` if the instance is
+    /// mutably borrowed. Undefined behavior if `ptr` isn't an instance of `T`.
+    pub unsafe fn from_owned_ptr_or_err(py: Python<'a>, ptr: *mut ffi::PyObject) -> PyResult<Self> {
+        let r = FromPyPointer::from_owned_ptr_or_err(py, ptr)?;
+        Ok(Self::try_from_ref(r)?)
+    }
+
+    /// Borrows the instance of `T` behind `ptr`.
+    ///
+    /// Returns the python error if `ptr` is null, and a `PyBorrowError` if the instance is
+    /// mutably borrowed. Undefined behavior if `ptr` isn't an instance of `T`.
+    pub unsafe fn from_borrowed_ptr_or_err(
+        py: Python<'a>,
+        ptr: *mut ffi::PyObject,
+    ) -> PyResult<Self> {
+        let r = FromPyPointer::from_borrowed_ptr_or_err(py, ptr)?;
+        Ok(Self::try_from_ref(r)?)
     }
 }
 
@@ -107,19 +155,20 @@ impl<'a, T: PyTypeInfo> Deref for PyRef<'a, T> {
     }
 }
 
-unsafe impl<'p, T> FromPyPointer<'p> for PyRef<'p, T>
+impl<'a, T> FromPyObject<'a> for PyRef<'a, T>
 where
     T: PyTypeInfo,
 {
-    unsafe fn from_owned_ptr_or_opt(py: Python<'p>, ptr: *mut ffi::PyObject) -> Option<Self> {
-        FromPyPointer::from_owned_ptr_or_opt(py, ptr).map(Self::from_ref)
-    }
-    unsafe fn from_borrowed_ptr_or_opt(py: Python<'p>, ptr: *mut ffi::PyObject) -> Option<Self> {
-        FromPyPointer::from_borrowed_ptr_or_opt(py, ptr).map(Self::from_ref)
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        Ok(Self::try_from_ref(<T as PyTryFrom>::try_from(ob)?)?)
     }
 }
 
 /// Mutable version of [`PyRef`](struct.PyRef.html).
+///
+/// A `PyRefMut` of a `#[pyclass]` holds the mutable borrow of the instance until it is dropped,
+/// so it can't be created while there is any other `PyRef` or `PyRefMut` of the instance.
+///
 /// # Example
 /// ```
 /// use pyo3::prelude::*;
@@ -140,14 +189,57 @@ where
 /// let mut obj = PyRefMut::new(gil.python(), Point { x: 3, y: 4 }).unwrap();
 /// let d = vec![("p", obj.to_object(py))].into_py_dict(py);
 /// obj.x = 5; obj.y = 20;
+/// // `length` can only borrow the instance once `obj` is gone
+/// drop(obj);
 /// py.run("assert p.length() == 100", None, Some(d)).unwrap();
 /// ```
 #[derive(Debug)]
-pub struct PyRefMut<'a, T: PyTypeInfo>(&'a mut T, PhantomData<Rc<()>>);
+pub struct PyRefMut<'a, T: PyTypeInfo>(&'a mut T, BorrowGuard, PhantomData<Rc<()>>);
+
+#[cfg(feature = "nightly")]
+impl<'a, T: PyTypeInfo> !crate::Ungil for PyRefMut<'a, T> {}
 
 impl<'a, T: PyTypeInfo> PyRefMut<'a, T> {
+    /// Mutably borrows `t`, which must be part of a python object
+    pub(crate) fn try_from_mut(t: &'a mut T) -> Result<Self, PyBorrowError> {
+        let guard = unsafe { BorrowGuard::try_borrow_mut::<T>(ref_to_ptr(t))? };
+        Ok(PyRefMut(t, guard, PhantomData))
+    }
+
+    /// Mutably borrows `t`, panicking if it is borrowed
     pub(crate) fn from_mut(t: &'a mut T) -> Self {
-        PyRefMut(t, PhantomData)
+        Self::try_from_mut(t).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Mutably borrows the instance of `T` behind `ptr`, or returns an error if it is borrowed.
+    ///
+    /// Panics if `ptr` is null. Undefined behavior if `ptr` isn't an instance of `T`.
+    pub unsafe fn try_from_borrowed_ptr(
+        py: Python<'a>,
+        ptr: *mut ffi::PyObject,
+    ) -> Result<Self, PyBorrowError> {
+        Self::try_from_mut(FromPyPointer::from_borrowed_ptr(py, ptr))
+    }
+
+    /// Takes ownership of `ptr` and mutably borrows the instance of `T` behind it.
+    ///
+    /// Returns the python error if `ptr` is null, and a `PyBorrowError` if the instance is
+    /// borrowed. Undefined behavior if `ptr` isn't an instance of `T`.
+    pub unsafe fn from_owned_ptr_or_err(py: Python<'a>, ptr: *mut ffi::PyObject) -> PyResult<Self> {
+        let r = FromPyPointer::from_owned_ptr_or_err(py, ptr)?;
+        Ok(Self::try_from_mut(r)?)
+    }
+
+    /// Mutably borrows the instance of `T` behind `ptr`.
+    ///
+    /// Returns the python error if `ptr` is null, and a `PyBorrowError` if the instance is
+    /// borrowed. Undefined behavior if `ptr` isn't an instance of `T`.
+    pub unsafe fn from_borrowed_ptr_or_err(
+        py: Python<'a>,
+        ptr: *mut ffi::PyObject,
+    ) -> PyResult<Self> {
+        let r = FromPyPointer::from_borrowed_ptr_or_err(py, ptr)?;
+        Ok(Self::try_from_mut(r)?)
     }
 }
 
@@ -193,24 +285,25 @@ impl<'a, T: PyTypeInfo> DerefMut for PyRefMut<'a, T> {
     }
 }
 
-unsafe impl<'p, T> FromPyPointer<'p> for PyRefMut<'p, T>
+impl<'a, T> FromPyObject<'a> for PyRefMut<'a, T>
 where
     T: PyTypeInfo,
 {
-    unsafe fn from_owned_ptr_or_opt(py: Python<'p>, ptr: *mut ffi::PyObject) -> Option<Self> {
-        FromPyPointer::from_owned_ptr_or_opt(py, ptr).map(Self::from_mut)
-    }
-    unsafe fn from_borrowed_ptr_or_opt(py: Python<'p>, ptr: *mut ffi::PyObject) -> Option<Self> {
-        FromPyPointer::from_borrowed_ptr_or_opt(py, ptr).map(Self::from_mut)
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        Ok(Self::try_from_mut(<T as PyTryFrom>::try_from_mut(ob)?)?)
     }
 }
 
 /// Trait implements object reference extraction from python managed pointer.
 pub trait AsPyRef<T: PyTypeInfo>: Sized {
     /// Return reference to object.
+    ///
+    /// Panics if the object is a `#[pyclass]` instance that is mutably borrowed.
     fn as_ref(&self, py: Python) -> PyRef<T>;
 
     /// Return mutable reference to object.
+    ///
+    /// Panics if the object is a `#[pyclass]` instance that is borrowed.
     fn as_mut(&mut self, py: Python) -> PyRefMut<T>;
 
     /// Acquire python gil and call closure with object reference.
@@ -265,6 +358,7 @@ pub trait AsPyRef<T: PyTypeInfo>: Sized {
 /// Safe wrapper around unsafe `*mut ffi::PyObject` pointer with specified type information.
 ///
 /// `Py<T>` is thread-safe, because any python related operations require a Python<'p> token.
+/// It can also be dropped without the GIL, see [Python::flush_pending_decrefs].
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct Py<T>(NonNull<ffi::PyObject>, std::marker::PhantomData<T>);
@@ -295,7 +389,7 @@ impl<T> Py<T> {
             !ptr.is_null() && ffi::Py_REFCNT(ptr) > 0,
             format!("REFCNT: {:?} - {:?}", ptr, ffi::Py_REFCNT(ptr))
         );
-        Py(NonNull::new_unchecked(ptr), std::marker::PhantomData)
+        Py::from_non_null(NonNull::new_unchecked(ptr))
     }
 
     /// Creates a `Py<T>` instance for the given FFI pointer.
@@ -304,7 +398,7 @@ impl<T> Py<T> {
     #[inline]
     pub unsafe fn from_owned_ptr_or_panic(ptr: *mut ffi::PyObject) -> Py<T> {
         match NonNull::new(ptr) {
-            Some(nonnull_ptr) => Py(nonnull_ptr, std::marker::PhantomData),
+            Some(nonnull_ptr) => Py::from_non_null(nonnull_ptr),
             None => {
                 crate::err::panic_after_error();
             }
@@ -317,7 +411,7 @@ impl<T> Py<T> {
     /// Unsafe because the pointer might be invalid.
     pub unsafe fn from_owned_ptr_or_err(py: Python, ptr: *mut ffi::PyObject) -> PyResult<Py<T>> {
         match NonNull::new(ptr) {
-            Some(nonnull_ptr) => Ok(Py(nonnull_ptr, std::marker::PhantomData)),
+            Some(nonnull_ptr) => Ok(Py::from_non_null(nonnull_ptr)),
             None => Err(PyErr::fetch(py)),
         }
     }
@@ -332,7 +426,7 @@ impl<T> Py<T> {
             format!("REFCNT: {:?} - {:?}", ptr, ffi::Py_REFCNT(ptr))
         );
         ffi::Py_INCREF(ptr);
-        Py(NonNull::new_unchecked(ptr), std::marker::PhantomData)
+        Py::from_non_null(NonNull::new_unchecked(ptr))
     }
 
     /// Gets the reference count of the ffi::PyObject pointer.
@@ -347,12 +441,19 @@ impl<T> Py<T> {
         unsafe { Py::from_borrowed_ptr(self.0.as_ptr()) }
     }
 
+    /// Takes ownership of the pointer
+    unsafe fn from_non_null(ptr: NonNull<ffi::PyObject>) -> Py<T> {
+        gil::handle_created();
+        Py(ptr, std::marker::PhantomData)
+    }
+
     /// Returns the inner pointer without decreasing the refcount
     ///
     /// This will eventually move into its own trait
     pub(crate) fn into_non_null(self) -> NonNull<ffi::PyObject> {
         let pointer = self.0;
         mem::forget(self);
+        gil::handle_released();
         pointer
     }
 }
@@ -398,6 +499,20 @@ where
     }
 }
 
+impl<T: PyTypeInfo> Py<T> {
+    /// Like [`AsPyRef::as_ref`](trait.AsPyRef.html#tymethod.as_ref), but returns an error
+    /// instead of panicking if the instance is mutably borrowed.
+    pub fn try_as_ref(&self, py: Python) -> Result<PyRef<T>, PyBorrowError> {
+        PyRef::try_from_ref(self.as_ref_dispatch(py))
+    }
+
+    /// Like [`AsPyRef::as_mut`](trait.AsPyRef.html#tymethod.as_mut), but returns an error
+    /// instead of panicking if the instance is borrowed.
+    pub fn try_as_mut(&mut self, py: Python) -> Result<PyRefMut<T>, PyBorrowError> {
+        PyRefMut::try_from_mut(self.as_mut_dispatch(py))
+    }
+}
+
 impl<T> ToPyObject for Py<T> {
     /// Converts `Py` instance -> PyObject.
     fn to_object(&self, py: Python) -> PyObject {
@@ -429,6 +544,7 @@ impl<T> IntoPyPointer for Py<T> {
     fn into_ptr(self) -> *mut ffi::PyObject {
         let ptr = self.0.as_ptr();
         std::mem::forget(self);
+        gil::handle_released();
         ptr
     }
 }
@@ -443,6 +559,7 @@ impl<T> PartialEq for Py<T> {
 /// Dropping a `Py` instance decrements the reference count on the object by 1.
 impl<T> Drop for Py<T> {
     fn drop(&mut self) {
+        gil::handle_released();
         unsafe {
             gil::register_pointer(self.0);
         }
@@ -498,7 +615,7 @@ where
     &'a T: 'a + FromPyObject<'a>,
 {
     /// Extracts `Self` from the source `PyObject`.
-    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+    default fn extract(ob: &'a PyAny) -> PyResult<Self> {
         unsafe {
             ob.extract::<&T>()
                 .map(|val| Py::from_borrowed_ptr(val.as_ptr()))
@@ -506,6 +623,21 @@ where
     }
 }
 
+/// Doesn't borrow the instance, unlike extracting `&T`
+impl<'a, T> FromPyObject<'a> for Py<T>
+where
+    T: PyTypeInfo + AsPyPointer,
+    &'a T: 'a + FromPyObject<'a>,
+{
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        unsafe {
+            <T as PyTryFrom>::try_from(ob)
+                .map(|val| Py::from_borrowed_ptr(val.as_ptr()))
+                .map_err(Into::into)
+        }
+    }
+}
+
 /// Reference to a converted [ToPyObject].
 ///
 /// Many methods want to take anything that can be converted into a python object. This type
@@ -1,4 +1,5 @@
 #![feature(specialization)]
+#![cfg_attr(feature = "nightly", feature(optin_builtin_traits))]
 
 //! Rust bindings to the Python interpreter.
 //!
@@ -122,12 +123,15 @@ pub use crate::conversion::{
     AsPyPointer, FromPy, FromPyObject, FromPyPointer, IntoPy, IntoPyPointer, PyTryFrom, PyTryInto,
     ToBorrowedObject, ToPyObject,
 };
-pub use crate::err::{PyDowncastError, PyErr, PyErrArguments, PyErrValue, PyResult};
-pub use crate::gil::{init_once, GILGuard, GILPool};
+pub use crate::err::{
+    FinalizeError, PyBorrowError, PyDowncastError, PyErr, PyErrArguments, PyErrValue, PyResult,
+};
+pub use crate::gil::{init_once, GILGuard, GILPool, InterpreterConfig, SubInterpreter};
 pub use crate::instance::{AsPyRef, ManagedPyRef, Py, PyNativeType, PyRef, PyRefMut};
 pub use crate::object::PyObject;
 pub use crate::objectprotocol::ObjectProtocol;
-pub use crate::python::{prepare_freethreaded_python, Python};
+pub use crate::pathlib::PathlibPath;
+pub use crate::python::{prepare_freethreaded_python, Python, Ungil};
 pub use crate::type_object::{PyObjectAlloc, PyRawObject, PyTypeInfo};
 
 // Re-exported for wrap_function
@@ -164,8 +168,10 @@ mod instance;
 pub mod marshal;
 mod object;
 mod objectprotocol;
+mod pathlib;
 pub mod prelude;
 mod python;
+pub mod stubs;
 pub mod type_object;
 pub mod types;
 
@@ -174,6 +180,8 @@ pub mod proc_macro {
     pub use pyo3cls::pymodule;
     /// The proc macro attributes
     pub use pyo3cls::{pyclass, pyfunction, pymethods, pyproto};
+    /// The derive macros
+    pub use pyo3cls::{FromPyObject, IntoPyObject};
 }
 
 /// Returns a function that takes a [Python] instance and returns a python function.
@@ -186,14 +194,15 @@ macro_rules! wrap_pyfunction {
     }};
 }
 
-/// Returns a function that takes a [Python] instance and returns a python module.
+/// Returns a function that takes a [Python] instance and returns a python module, or the error
+/// of its initializer.
 ///
 /// Use this together with `#[pymodule]` and [types::PyModule::add_wrapped].
 #[macro_export]
 macro_rules! wrap_pymodule {
     ($module_name:ident) => {{
         pyo3::paste::expr! {
-            &|py| unsafe { pyo3::PyObject::from_owned_ptr(py, [<PyInit_ $module_name>]()) }
+            &|py| unsafe { pyo3::derive_utils::module_from_init(py, [<PyInit_ $module_name>]()) }
         }
     }};
 }
@@ -17,6 +17,9 @@ use std::ptr::NonNull;
 /// collector.
 ///
 /// Technically, it is a safe wrapper around `NonNull<ffi::PyObject>`.
+///
+/// A `PyObject` can be dropped without the GIL. Its reference count is decremented the next
+/// time the GIL is acquired, see [Python::flush_pending_decrefs].
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct PyObject(NonNull<ffi::PyObject>);
@@ -28,12 +31,14 @@ unsafe impl Sync for PyObject {}
 impl PyObject {
     /// For internal conversions
     pub(crate) unsafe fn from_not_null(ptr: NonNull<ffi::PyObject>) -> PyObject {
+        gil::handle_created();
         PyObject(ptr)
     }
 
     pub(crate) unsafe fn into_nonnull(self) -> NonNull<ffi::PyObject> {
         let res = self.0;
         std::mem::forget(self); // Avoid Drop
+        gil::handle_released();
         res
     }
 
@@ -46,7 +51,7 @@ impl PyObject {
             !ptr.is_null() && ffi::Py_REFCNT(ptr) > 0,
             format!("REFCNT: {:?} - {:?}", ptr, ffi::Py_REFCNT(ptr))
         );
-        PyObject(NonNull::new_unchecked(ptr))
+        PyObject::from_not_null(NonNull::new_unchecked(ptr))
     }
 
     /// Creates a `PyObject` instance for the given FFI pointer.
@@ -55,7 +60,7 @@ impl PyObject {
     #[inline]
     pub unsafe fn from_owned_ptr_or_panic(_py: Python, ptr: *mut ffi::PyObject) -> PyObject {
         match NonNull::new(ptr) {
-            Some(nonnull_ptr) => PyObject(nonnull_ptr),
+            Some(nonnull_ptr) => PyObject::from_not_null(nonnull_ptr),
             None => {
                 crate::err::panic_after_error();
             }
@@ -67,7 +72,7 @@ impl PyObject {
     /// Returns `Err(PyErr)` if the pointer is `null`.
     pub unsafe fn from_owned_ptr_or_err(py: Python, ptr: *mut ffi::PyObject) -> PyResult<PyObject> {
         match NonNull::new(ptr) {
-            Some(nonnull_ptr) => Ok(PyObject(nonnull_ptr)),
+            Some(nonnull_ptr) => Ok(PyObject::from_not_null(nonnull_ptr)),
             None => Err(PyErr::fetch(py)),
         }
     }
@@ -77,7 +82,7 @@ impl PyObject {
     /// Returns `None` if the pointer is `null`.
     pub unsafe fn from_owned_ptr_or_opt(_py: Python, ptr: *mut ffi::PyObject) -> Option<PyObject> {
         match NonNull::new(ptr) {
-            Some(nonnull_ptr) => Some(PyObject(nonnull_ptr)),
+            Some(nonnull_ptr) => Some(PyObject::from_not_null(nonnull_ptr)),
             None => None,
         }
     }
@@ -92,7 +97,7 @@ impl PyObject {
             format!("REFCNT: {:?} - {:?}", ptr, ffi::Py_REFCNT(ptr))
         );
         ffi::Py_INCREF(ptr);
-        PyObject(NonNull::new_unchecked(ptr))
+        PyObject::from_not_null(NonNull::new_unchecked(ptr))
     }
 
     /// Creates a `PyObject` instance for the given Python FFI pointer.
@@ -285,6 +290,7 @@ impl IntoPyPointer for PyObject {
     fn into_ptr(self) -> *mut ffi::PyObject {
         let ptr = self.0.as_ptr();
         std::mem::forget(self); // Avoid Drop
+        gil::handle_released();
         ptr
     }
 }
@@ -308,6 +314,7 @@ impl<'a> FromPyObject<'a> for PyObject {
 /// Dropping a `PyObject` instance decrements the reference count on the object by 1.
 impl Drop for PyObject {
     fn drop(&mut self) {
+        gil::handle_released();
         unsafe {
             gil::register_pointer(self.0);
         }
@@ -0,0 +1,125 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! Conversion of paths to `pathlib.Path`
+
+use crate::err::PyResult;
+use crate::instance::PyNativeType;
+use crate::object::PyObject;
+use crate::types::PyAny;
+use crate::{FromPyObject, IntoPy, Python, ToPyObject};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A `PathBuf` that is converted to a `pathlib.Path` instead of a `str`
+///
+/// The `pathlib.Path` is created together with the `PathlibPath`, so that a failure to import
+/// `pathlib` is returned from [PathlibPath::new] instead of panicking in the conversion.
+/// It's extracted from the same objects as `PathBuf`.
+///
+/// # Example
+/// ```
+/// use pyo3::prelude::*;
+/// use pyo3::PathlibPath;
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let path = PathlibPath::new(py, "dir/file.txt").unwrap().to_object(py);
+/// let name: String = path.getattr(py, "name").unwrap().extract(py).unwrap();
+/// assert_eq!(name, "file.txt");
+/// ```
+#[derive(Debug)]
+pub struct PathlibPath {
+    path: PathBuf,
+    object: PyObject,
+}
+
+impl PathlibPath {
+    /// Calls `pathlib.Path` with the `str` of the path
+    pub fn new(py: Python, path: impl Into<PathBuf>) -> PyResult<PathlibPath> {
+        let path = path.into();
+        let object = py
+            .import("pathlib")?
+            .call1("Path", (path.as_path(),))?
+            .to_object(py);
+        Ok(PathlibPath { path, object })
+    }
+
+    /// Returns the path
+    pub fn as_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path, dropping the `pathlib.Path`
+    pub fn into_path_buf(self) -> PathBuf {
+        self.path
+    }
+}
+
+impl Deref for PathlibPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for PathlibPath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl ToPyObject for PathlibPath {
+    #[inline]
+    fn to_object(&self, py: Python) -> PyObject {
+        self.object.clone_ref(py)
+    }
+}
+
+impl IntoPy<PyObject> for PathlibPath {
+    #[inline]
+    fn into_py(self, _py: Python) -> PyObject {
+        self.object
+    }
+}
+
+impl<'source> FromPyObject<'source> for PathlibPath {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        PathlibPath::new(ob.py(), PathBuf::extract(ob)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PathlibPath;
+    use crate::objectprotocol::ObjectProtocol;
+    use crate::types::PyType;
+    use crate::{IntoPy, PyObject, Python};
+    use std::path::Path;
+
+    #[test]
+    fn test_pathlib_path() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ob: PyObject = PathlibPath::new(py, "dir/file.txt").unwrap().into_py(py);
+        let path_type: &PyType = py
+            .import("pathlib")
+            .unwrap()
+            .get("Path")
+            .unwrap()
+            .downcast_ref()
+            .unwrap();
+        assert!(path_type.is_instance(&ob).unwrap());
+        assert_eq!(
+            ob.getattr(py, "suffix")
+                .unwrap()
+                .extract::<String>(py)
+                .unwrap(),
+            ".txt"
+        );
+        assert_eq!(
+            ob.extract::<PathlibPath>(py).unwrap().as_path(),
+            Path::new("dir/file.txt")
+        );
+    }
+}
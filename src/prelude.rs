@@ -23,3 +23,4 @@ pub use crate::types::PyModule;
 pub use crate::PyRawObject;
 pub use pyo3cls::pymodule;
 pub use pyo3cls::{pyclass, pyfunction, pymethods, pyproto};
+pub use pyo3cls::{FromPyObject, IntoPyObject};
@@ -2,9 +2,9 @@
 //
 // based on Daniel Grunwald's https://github.com/dgrunwald/rust-cpython
 
-use crate::err::{PyDowncastError, PyErr, PyResult};
+use crate::err::{FinalizeError, PyDowncastError, PyErr, PyResult};
 use crate::ffi;
-use crate::gil::{self, GILGuard};
+use crate::gil::{self, GILGuard, GILPool};
 use crate::instance::AsPyRef;
 use crate::object::PyObject;
 use crate::type_object::{PyTypeInfo, PyTypeObject};
@@ -50,6 +50,32 @@ pub use gil::prepare_freethreaded_python;
 #[derive(Copy, Clone)]
 pub struct Python<'p>(PhantomData<&'p GILGuard>);
 
+/// Types that may be used while the GIL is released, i.e. in and from the closure of
+/// [Python::allow_threads], and in the closures of [Python::with_gil] and [Python::with_pool].
+///
+/// `Python` tokens, references to Python objects such as `&PyAny`, `PyRef` and `GILGuard` are
+/// bound to the GIL and don't implement it, while `PyObject` and `Py<T>` do.
+///
+/// Without the `nightly` feature, it's implemented for all `Send` types, which is why the
+/// native types like `PyAny` are `!Sync`. With the feature, it's an auto trait, so that
+/// types like `Rc` that aren't `Send` but unrelated to the GIL can be used as well.
+#[cfg(not(feature = "nightly"))]
+pub unsafe trait Ungil {}
+
+#[cfg(not(feature = "nightly"))]
+unsafe impl<T: Send> Ungil for T {}
+
+/// Types that may be used while the GIL is released, i.e. in and from the closure of
+/// [Python::allow_threads], and in the closures of [Python::with_gil] and [Python::with_pool].
+///
+/// `Python` tokens, references to Python objects such as `&PyAny`, `PyRef` and `GILGuard` are
+/// bound to the GIL and don't implement it, while `PyObject` and `Py<T>` do.
+#[cfg(feature = "nightly")]
+pub unsafe auto trait Ungil {}
+
+#[cfg(feature = "nightly")]
+impl<'p> !Ungil for Python<'p> {}
+
 impl<'p> Python<'p> {
     /// Retrieve Python instance under the assumption that the GIL is already acquired at this point,
     /// and stays acquired for the lifetime `'p`.
@@ -66,24 +92,175 @@ impl<'p> Python<'p> {
     ///
     /// If the Python runtime is not already initialized, this function will initialize it.
     /// See [prepare_freethreaded_python()](fn.prepare_freethreaded_python.html) for details.
+    ///
+    /// The returned guards must be dropped in the reverse order of their acquisition;
+    /// prefer [Python::with_gil], which takes care of this.
     #[inline]
     pub fn acquire_gil() -> GILGuard {
         GILGuard::acquire()
     }
 
+    /// Acquires the global interpreter lock and runs `f` with it.
+    ///
+    /// The objects owned by `f` are released when it returns, and the GIL is released
+    /// unless the current thread already held it. Calls can be nested, in which case the GIL
+    /// isn't acquired again. The `Python` token is only valid for the closure, and like with
+    /// [Python::with_pool], the [Ungil] bound rejects closures that capture a token or
+    /// references of an outer scope, which could create objects with a longer lifetime in the
+    /// new pool. So references to the released objects can't escape.
+    ///
+    /// If the Python runtime is not already initialized, this function will initialize it.
+    /// See [prepare_freethreaded_python()](fn.prepare_freethreaded_python.html) for details.
+    ///
+    /// # Example
+    /// ```
+    /// use pyo3::prelude::*;
+    ///
+    /// let sum = Python::with_gil(|py| -> PyResult<i32> {
+    ///     let a: i32 = py.eval("6 * 7", None, None)?.extract()?;
+    ///     // the nested call doesn't acquire the GIL again
+    ///     let b: i32 = Python::with_gil(|py| py.eval("1", None, None)?.extract())?;
+    ///     Ok(a + b)
+    /// })
+    /// .unwrap();
+    /// assert_eq!(sum, 43);
+    /// ```
+    #[inline]
+    pub fn with_gil<F, R>(f: F) -> R
+    where
+        F: for<'py> FnOnce(Python<'py>) -> R + Ungil,
+    {
+        let guard = GILGuard::acquire();
+        f(guard.python())
+    }
+
+    /// Runs `f` with a new pool for owned objects, which releases the objects created in `f`
+    /// when it returns.
+    ///
+    /// Otherwise the references returned e.g. by [Python::eval] live until the outermost
+    /// `GILGuard` or pool is dropped, so that a long loop would keep all of its temporary
+    /// objects alive. The `Python` token passed to `f` is only valid for the closure.
+    ///
+    /// References to the released objects could still escape through the outer token or
+    /// through references like `&PyAny`, which create objects with the outer lifetime, e.g.
+    /// with `list.py().eval(...)`. The [Ungil] bound rejects closures that capture them: the
+    /// `Python` token isn't `Ungil`, and neither are references to the native types, which
+    /// contain an [Unsendable](crate::types::Unsendable) marker. Objects that are used in `f`
+    /// have to be passed as `PyObject` or `Py<T>`.
+    ///
+    /// # Example
+    /// ```
+    /// use pyo3::prelude::*;
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let mut sum = 0;
+    /// for i in 0..1000 {
+    ///     sum += py.with_pool(|py| -> PyResult<i64> {
+    ///         let square = py.eval(&format!("{} ** 2", i), None, None)?;
+    ///         square.extract()
+    ///     })?;
+    /// }
+    /// assert_eq!(sum, 332_833_500);
+    /// # Ok::<(), PyErr>(())
+    /// ```
+    #[inline]
+    pub fn with_pool<F, R>(self, f: F) -> R
+    where
+        F: for<'py> FnOnce(Python<'py>) -> R + Ungil,
+    {
+        let _pool = GILPool::new(self);
+        f(unsafe { Python::assume_gil_acquired() })
+    }
+
+    /// Releases the objects that were dropped since the GIL was last acquired, and returns
+    /// their number.
+    ///
+    /// Dropping a `PyObject` or `Py<T>` doesn't need the GIL, so the decrement of its reference
+    /// count is deferred. The pending decrefs are flushed whenever the GIL is acquired with a
+    /// `GILGuard`, [Python::with_gil] or [Python::with_pool], when a function or method of
+    /// pyo3 is called from Python, and when a `GILGuard` is dropped. This method flushes them
+    /// explicitly, e.g. in a long running function that drops objects from other threads.
+    ///
+    /// # Example
+    /// ```
+    /// use pyo3::prelude::*;
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let obj = py.eval("object()", None, None).unwrap().to_object(py);
+    /// std::thread::spawn(move || drop(obj)).join().unwrap();
+    /// assert!(Python::pending_decrefs() >= 1);
+    /// assert!(py.flush_pending_decrefs() >= 1);
+    /// ```
+    pub fn flush_pending_decrefs(self) -> usize {
+        unsafe { gil::flush_decrefs() }
+    }
+
+    /// The number of objects that were dropped, but not yet released because the GIL wasn't
+    /// acquired since.
+    ///
+    /// This doesn't need the GIL, so that it can be used to monitor a growing backlog,
+    /// e.g. of objects that are dropped by threads that never acquire the GIL.
+    pub fn pending_decrefs() -> usize {
+        gil::pending_decrefs()
+    }
+
+    /// Finalizes the interpreter with `Py_FinalizeEx`, which runs the `atexit` handlers and
+    /// releases the modules and objects of the interpreter.
+    ///
+    /// The type objects of `#[pyclass]`es and exceptions, and the other objects that pyo3 caches,
+    /// are reset, so that the interpreter can be initialized again, e.g. by acquiring the GIL or
+    /// with [InterpreterConfig](crate::InterpreterConfig).
+    ///
+    /// The interpreter isn't finalized if the current thread holds the GIL, if another thread
+    /// holds the GIL or released it temporarily with [Python::allow_threads], or if a
+    /// [SubInterpreter](crate::SubInterpreter) is alive. Threads that acquire the GIL while the
+    /// interpreter is finalized wait and then initialize a new interpreter.
+    ///
+    /// `PyObject` and `Py<T>` handles that are still alive after the finalization, e.g. in a
+    /// static, are reported with [FinalizeError::LeakedHandles]. Their objects are gone, and
+    /// initializing the interpreter again panics until they are dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use pyo3::prelude::*;
+    ///
+    /// Python::with_gil(|py| py.run("import sys; sys.marker = 1", None, None)).unwrap();
+    /// Python::finalize().unwrap();
+    ///
+    /// let has_marker: bool = Python::with_gil(|py| {
+    ///     py.eval("hasattr(__import__('sys'), 'marker')", None, None)?.extract()
+    /// })
+    /// .unwrap();
+    /// assert!(!has_marker);
+    /// ```
+    pub fn finalize() -> Result<(), FinalizeError> {
+        gil::finalize()
+    }
+
     /// Temporarily releases the `GIL`, thus allowing other Python threads to run.
+    ///
+    /// The closure and its result must implement [Ungil], so that they can't contain
+    /// references to Python objects:
+    ///
+    /// ```compile_fail
+    /// use pyo3::prelude::*;
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let string = py.eval("'text'", None, None).unwrap();
+    /// py.allow_threads(|| println!("{:?}", string));
+    /// ```
     pub fn allow_threads<T, F>(self, f: F) -> T
     where
-        F: Send + FnOnce() -> T,
+        F: Ungil + FnOnce() -> T,
+        T: Ungil,
     {
-        // The `Send` bound on the closure prevents the user from
+        // The `Ungil` bound on the closure prevents the user from
         // transferring the `Python` token into the closure.
-        unsafe {
-            let save = ffi::PyEval_SaveThread();
-            let result = f();
-            ffi::PyEval_RestoreThread(save);
-            result
-        }
+        let _suspend = unsafe { gil::SuspendGIL::new() };
+        f()
     }
 
     /// Evaluates a Python expression in the given context and returns the result.
@@ -0,0 +1,570 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! Generation of `.pyi` type stubs for extension modules
+//!
+//! `#[pyfunction]`, `#[pyclass]`, `#[pymethods]` and `#[pyproto]` record the python signatures
+//! of everything they define. [module_stub] walks the attributes of a module and renders the
+//! ones it knows about as a type stub, so that IDEs and type checkers such as mypy can see
+//! the types of a module written in Rust.
+//!
+//! # Example
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//! use pyo3::stubs::module_stub;
+//! use pyo3::wrap_pyfunction;
+//!
+//! #[pyfunction]
+//! fn double(x: i64) -> i64 {
+//!     x * 2
+//! }
+//!
+//! #[pymodule]
+//! fn maths(_py: Python, m: &PyModule) -> PyResult<()> {
+//!     m.add_wrapped(wrap_pyfunction!(double))
+//! }
+//!
+//! let gil = Python::acquire_gil();
+//! let py = gil.python();
+//! let module = PyModule::new(py, "maths").unwrap();
+//! maths(py, module).unwrap();
+//! let stub = module_stub(module).unwrap();
+//! assert!(stub.contains("def double(x: int) -> int: ..."));
+//! ```
+
+use crate::instance::PyNativeType;
+use crate::type_object::{PyTypeInfo, PyTypeObject};
+use crate::types::{PyAny, PyModule, PyType};
+use crate::{ffi, AsPyPointer, ObjectProtocol, PathlibPath, Py, PyRef, PyRefMut, PyResult, Python};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// The python annotation of a rust type
+///
+/// This is implemented for the types pyo3 converts from and to python objects. Classes
+/// and native python types use the name of their type object, while any other type is
+/// annotated as `Any`.
+pub trait TypeHint {
+    fn type_hint(py: Python) -> String;
+}
+
+impl<T: ?Sized> TypeHint for T {
+    default fn type_hint(_py: Python) -> String {
+        "Any".to_string()
+    }
+}
+
+impl<T: PyTypeObject> TypeHint for T {
+    default fn type_hint(py: Python) -> String {
+        use crate::AsPyRef;
+        let type_object = T::type_object();
+        let type_object = type_object.as_ref(py);
+        let name = type_object.name();
+        // Drop the module of e.g. `datetime.date`
+        match name.rfind('.') {
+            Some(index) => name[index + 1..].to_string(),
+            None => name.into_owned(),
+        }
+    }
+}
+
+macro_rules! type_hint_impl {
+    ($hint: expr, $($t: ty),*) => {
+        $(
+            impl TypeHint for $t {
+                fn type_hint(_py: Python) -> String {
+                    $hint.to_string()
+                }
+            }
+        )*
+    };
+}
+
+type_hint_impl!("int", i8, u8, i16, u16, i32, u32, i64, u64, i128, u128, isize, usize);
+type_hint_impl!("float", f32, f64);
+type_hint_impl!("bool", bool);
+type_hint_impl!("str", str, String, char, OsStr, OsString, Path, PathBuf);
+type_hint_impl!("pathlib.Path", PathlibPath);
+type_hint_impl!("bytes", [u8]);
+type_hint_impl!("None", ());
+type_hint_impl!("Any", PyAny);
+
+#[doc(hidden)] // Only to be used through the proc macros
+/// Stands in for references in the annotated types, since `&T` can't implement [TypeHint]
+/// next to the implementation for python types
+pub struct Ref<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized + TypeHint> TypeHint for Ref<T> {
+    fn type_hint(py: Python) -> String {
+        T::type_hint(py)
+    }
+}
+
+impl<'a> TypeHint for Cow<'a, str> {
+    fn type_hint(_py: Python) -> String {
+        "str".to_string()
+    }
+}
+
+impl<T: TypeHint, E> TypeHint for Result<T, E> {
+    fn type_hint(py: Python) -> String {
+        T::type_hint(py)
+    }
+}
+
+impl<T: TypeHint> TypeHint for Option<T> {
+    fn type_hint(py: Python) -> String {
+        format!("Optional[{}]", T::type_hint(py))
+    }
+}
+
+impl<T: TypeHint> TypeHint for Py<T> {
+    fn type_hint(py: Python) -> String {
+        T::type_hint(py)
+    }
+}
+
+impl<'a, T: TypeHint + PyTypeInfo> TypeHint for PyRef<'a, T> {
+    fn type_hint(py: Python) -> String {
+        T::type_hint(py)
+    }
+}
+
+impl<'a, T: TypeHint + PyTypeInfo> TypeHint for PyRefMut<'a, T> {
+    fn type_hint(py: Python) -> String {
+        T::type_hint(py)
+    }
+}
+
+macro_rules! sequence_type_hint_impl {
+    ($hint: expr, $t: ident $(, $bound: ident)*) => {
+        impl<T: TypeHint $(+ $bound)*> TypeHint for $t<T> {
+            fn type_hint(py: Python) -> String {
+                format!("{}[{}]", $hint, T::type_hint(py))
+            }
+        }
+    };
+}
+
+sequence_type_hint_impl!("List", Vec);
+sequence_type_hint_impl!("List", VecDeque);
+sequence_type_hint_impl!("Set", BTreeSet);
+
+impl<T: TypeHint> TypeHint for [T] {
+    default fn type_hint(py: Python) -> String {
+        format!("List[{}]", T::type_hint(py))
+    }
+}
+
+macro_rules! array_type_hint_impl {
+    ($($N: expr),+) => {
+        $(
+            impl<T: TypeHint> TypeHint for [T; $N] {
+                fn type_hint(py: Python) -> String {
+                    format!("List[{}]", T::type_hint(py))
+                }
+            }
+        )+
+    };
+}
+
+array_type_hint_impl!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32
+);
+
+impl<T: TypeHint, S> TypeHint for HashSet<T, S> {
+    fn type_hint(py: Python) -> String {
+        format!("Set[{}]", T::type_hint(py))
+    }
+}
+
+impl<K: TypeHint, V: TypeHint, S> TypeHint for HashMap<K, V, S> {
+    fn type_hint(py: Python) -> String {
+        format!("Dict[{}, {}]", K::type_hint(py), V::type_hint(py))
+    }
+}
+
+impl<K: TypeHint, V: TypeHint> TypeHint for BTreeMap<K, V> {
+    fn type_hint(py: Python) -> String {
+        format!("Dict[{}, {}]", K::type_hint(py), V::type_hint(py))
+    }
+}
+
+macro_rules! tuple_type_hint_impl {
+    ($($T: ident),+) => {
+        impl<$($T: TypeHint),+> TypeHint for ($($T,)+) {
+            fn type_hint(py: Python) -> String {
+                let items: Vec<String> = vec![$($T::type_hint(py)),+];
+                format!("Tuple[{}]", items.join(", "))
+            }
+        }
+    };
+}
+
+tuple_type_hint_impl!(A);
+tuple_type_hint_impl!(A, B);
+tuple_type_hint_impl!(A, B, C);
+tuple_type_hint_impl!(A, B, C, D);
+tuple_type_hint_impl!(A, B, C, D, E);
+tuple_type_hint_impl!(A, B, C, D, E, F);
+tuple_type_hint_impl!(A, B, C, D, E, F, G);
+tuple_type_hint_impl!(A, B, C, D, E, F, G, H);
+tuple_type_hint_impl!(A, B, C, D, E, F, G, H, I);
+
+/// What kind of callable a [FunctionStub] describes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FunctionKind {
+    /// A `#[pyfunction]`
+    Function,
+    /// An instance method
+    Method,
+    /// A `#[classmethod]`
+    ClassMethod,
+    /// A `#[staticmethod]`
+    StaticMethod,
+    /// A `#[new]` constructor
+    New,
+    /// A `#[call]` method
+    Call,
+    /// A `#[getter]`
+    Getter,
+    /// A `#[setter]`
+    Setter,
+    /// A `#[classattr]`, which is shown as a class variable of its return type
+    ClassAttribute,
+}
+
+/// How an argument is passed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ArgumentKind {
+    Positional,
+    /// `*args`
+    VarArgs,
+    KeywordOnly,
+    /// `**kwargs`
+    KeywordArgs,
+}
+
+/// An argument of a [FunctionStub]
+pub struct ArgumentStub {
+    pub name: &'static str,
+    pub kind: ArgumentKind,
+    pub annotation: fn(Python) -> String,
+    pub has_default: bool,
+}
+
+/// The signature of a function or method
+pub struct FunctionStub {
+    pub name: &'static str,
+    pub kind: FunctionKind,
+    pub arguments: Vec<ArgumentStub>,
+    pub output: fn(Python) -> String,
+}
+
+/// How an attribute of a class can be accessed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AttributeKind {
+    /// `#[pyo3(get)]`
+    ReadOnly,
+    /// `#[pyo3(get, set)]` or `#[pyo3(set)]`
+    ReadWrite,
+    /// An attribute of the class itself
+    Class,
+}
+
+/// An attribute of a [ClassStub]
+pub struct AttributeStub {
+    pub name: &'static str,
+    pub kind: AttributeKind,
+    pub annotation: fn(Python) -> String,
+}
+
+/// A `#[pyclass]` with the attributes defined on the struct
+pub struct ClassStub {
+    pub name: &'static str,
+    pub type_object: fn() -> Py<PyType>,
+    pub attributes: Vec<AttributeStub>,
+}
+
+/// The methods of a `#[pymethods]` or `#[pyproto]` block
+pub struct MethodsStub {
+    pub type_object: fn() -> Py<PyType>,
+    pub methods: Vec<FunctionStub>,
+}
+
+#[doc(hidden)] // Only to be used through the proc macros
+/// The type information the proc macros submit through inventory
+pub enum TypeStub {
+    /// A `#[pyfunction]`, which is identified by the function that python calls, because two
+    /// rust modules may define functions with the same name
+    Function {
+        wrapper: ffi::PyCFunctionWithKeywords,
+        function: FunctionStub,
+    },
+    Class(ClassStub),
+    Methods(MethodsStub),
+}
+
+inventory::collect!(TypeStub);
+
+const HEADER: &str = "# This file was generated by pyo3, do not edit it\n";
+
+const TYPING_IMPORT: &str = "from typing import Any, ClassVar, Dict, List, Optional, Set, Tuple\n";
+
+/// Renders the type stub of a module
+///
+/// Classes and functions defined with pyo3 are rendered with their signatures, other values
+/// are annotated with their type if it's a builtin type and with `Any` otherwise.
+/// Submodules are skipped, their stubs can be generated separately.
+pub fn module_stub(module: &PyModule) -> PyResult<String> {
+    let py = module.py();
+    let mut stub = String::new();
+
+    for (name, value) in module.dict().iter() {
+        let name: &str = name.extract()?;
+        if name.starts_with("__") {
+            continue;
+        }
+        if let Ok(class) = value.downcast_ref::<PyType>() {
+            if let Some(class_stub) = find_class(class) {
+                stub.push('\n');
+                write_class(py, &mut stub, name, class, class_stub);
+                continue;
+            }
+        }
+        let function = if unsafe { ffi::PyCFunction_Check(value.as_ptr()) } != 0 {
+            find_function(value)
+        } else {
+            None
+        };
+        match function {
+            Some(function) => {
+                stub.push('\n');
+                write_function(py, &mut stub, "", None, function);
+            }
+            None => {
+                if value.downcast_ref::<PyModule>().is_err() {
+                    writeln!(stub, "\n{}: {}", name, value_type_hint(value)?).unwrap();
+                }
+            }
+        }
+    }
+
+    let mut header = String::from(HEADER);
+    // `pathlib` is only imported if a path is converted to a `pathlib.Path`
+    if stub.contains("pathlib.") {
+        header.push_str("import pathlib\n");
+    }
+    header.push_str(TYPING_IMPORT);
+    Ok(header + &stub)
+}
+
+/// Writes the type stub of a module to a `.pyi` file
+pub fn write_module_stub(module: &PyModule, path: impl AsRef<Path>) -> PyResult<()> {
+    std::fs::write(path, module_stub(module)?)?;
+    Ok(())
+}
+
+fn find_class(class: &PyType) -> Option<&'static ClassStub> {
+    inventory::iter::<TypeStub>
+        .into_iter()
+        .filter_map(|stub| match stub {
+            TypeStub::Class(class_stub) => Some(class_stub),
+            _ => None,
+        })
+        .find(|class_stub| (class_stub.type_object)().as_ptr() == class.as_ptr())
+}
+
+/// Finds the stub of a builtin function object by the C function it calls
+fn find_function(function: &PyAny) -> Option<&'static FunctionStub> {
+    let meth = unsafe { ffi::PyCFunction_GetFunction(function.as_ptr()) }? as usize;
+    inventory::iter::<TypeStub>
+        .into_iter()
+        .find_map(|stub| match stub {
+            TypeStub::Function { wrapper, function } if *wrapper as usize == meth => Some(function),
+            _ => None,
+        })
+}
+
+fn find_methods(class: &PyType) -> impl Iterator<Item = &'static FunctionStub> + '_ {
+    inventory::iter::<TypeStub>
+        .into_iter()
+        .filter_map(move |stub| match stub {
+            TypeStub::Methods(methods) if (methods.type_object)().as_ptr() == class.as_ptr() => {
+                Some(methods.methods.iter())
+            }
+            _ => None,
+        })
+        .flatten()
+}
+
+fn value_type_hint(value: &PyAny) -> PyResult<String> {
+    let value_type = value.get_type();
+    let module: String = value_type.getattr("__module__")?.extract()?;
+    if module == "builtins" {
+        Ok(value_type.name().into_owned())
+    } else {
+        Ok("Any".to_string())
+    }
+}
+
+fn write_class(py: Python, stub: &mut String, name: &str, class: &PyType, class_stub: &ClassStub) {
+    writeln!(stub, "class {}:", name).unwrap();
+    let methods: Vec<&FunctionStub> = find_methods(class).collect();
+    let mut empty = true;
+
+    for attribute in class_stub.attributes.iter() {
+        let annotation = (attribute.annotation)(py);
+        match attribute.kind {
+            AttributeKind::ReadOnly => write_property(stub, attribute.name, &annotation),
+            AttributeKind::ReadWrite => {
+                writeln!(stub, "    {}: {}", attribute.name, annotation).unwrap()
+            }
+            AttributeKind::Class => {
+                writeln!(stub, "    {}: ClassVar[{}]", attribute.name, annotation).unwrap()
+            }
+        }
+        empty = false;
+    }
+
+    for attribute in methods
+        .iter()
+        .filter(|m| m.kind == FunctionKind::ClassAttribute)
+    {
+        write_function(py, stub, "    ", Some(name), attribute);
+        empty = false;
+    }
+
+    // Getters and setters are shown as attributes, just like the fields of the class
+    for getter in methods.iter().filter(|m| m.kind == FunctionKind::Getter) {
+        let annotation = (getter.output)(py);
+        let has_setter = methods
+            .iter()
+            .any(|m| m.kind == FunctionKind::Setter && m.name == getter.name);
+        if has_setter {
+            writeln!(stub, "    {}: {}", getter.name, annotation).unwrap();
+        } else {
+            write_property(stub, getter.name, &annotation);
+        }
+        empty = false;
+    }
+    for setter in methods.iter().filter(|m| m.kind == FunctionKind::Setter) {
+        let has_getter = methods
+            .iter()
+            .any(|m| m.kind == FunctionKind::Getter && m.name == setter.name);
+        if !has_getter {
+            let annotation = match setter.arguments.first() {
+                Some(argument) => (argument.annotation)(py),
+                None => "Any".to_string(),
+            };
+            writeln!(stub, "    {}: {}", setter.name, annotation).unwrap();
+            empty = false;
+        }
+    }
+
+    for method in methods.iter() {
+        match method.kind {
+            FunctionKind::Getter | FunctionKind::Setter | FunctionKind::ClassAttribute => (),
+            _ => {
+                write_function(py, stub, "    ", Some(name), method);
+                empty = false;
+            }
+        }
+    }
+
+    if empty {
+        stub.push_str("    ...\n");
+    }
+}
+
+fn write_property(stub: &mut String, name: &str, annotation: &str) {
+    writeln!(stub, "    @property").unwrap();
+    writeln!(stub, "    def {}(self) -> {}: ...", name, annotation).unwrap();
+}
+
+fn write_function(
+    py: Python,
+    stub: &mut String,
+    indent: &str,
+    class: Option<&str>,
+    function: &FunctionStub,
+) {
+    let mut params = Vec::new();
+    let name = match function.kind {
+        FunctionKind::Function => function.name,
+        FunctionKind::Method | FunctionKind::Getter | FunctionKind::Setter => {
+            params.push("self".to_string());
+            function.name
+        }
+        FunctionKind::ClassMethod => {
+            writeln!(stub, "{}@classmethod", indent).unwrap();
+            params.push("cls".to_string());
+            function.name
+        }
+        FunctionKind::StaticMethod => {
+            writeln!(stub, "{}@staticmethod", indent).unwrap();
+            function.name
+        }
+        FunctionKind::New => {
+            params.push("cls".to_string());
+            "__new__"
+        }
+        FunctionKind::Call => {
+            params.push("self".to_string());
+            "__call__"
+        }
+        FunctionKind::ClassAttribute => {
+            let annotation = (function.output)(py);
+            writeln!(
+                stub,
+                "{}{}: ClassVar[{}]",
+                indent, function.name, annotation
+            )
+            .unwrap();
+            return;
+        }
+    };
+
+    let mut keyword_only = false;
+    for argument in function.arguments.iter() {
+        let annotation = (argument.annotation)(py);
+        let param = match argument.kind {
+            ArgumentKind::VarArgs => {
+                keyword_only = true;
+                format!("*{}", argument.name)
+            }
+            ArgumentKind::KeywordArgs => format!("**{}", argument.name),
+            ArgumentKind::Positional | ArgumentKind::KeywordOnly => {
+                if argument.kind == ArgumentKind::KeywordOnly && !keyword_only {
+                    keyword_only = true;
+                    params.push("*".to_string());
+                }
+                if argument.has_default {
+                    format!("{}: {} = ...", argument.name, annotation)
+                } else {
+                    format!("{}: {}", argument.name, annotation)
+                }
+            }
+        };
+        params.push(param);
+    }
+
+    let output = match (function.kind, class) {
+        (FunctionKind::New, Some(class)) => class.to_string(),
+        _ => (function.output)(py),
+    };
+    writeln!(
+        stub,
+        "{}def {}({}) -> {}: ...",
+        indent,
+        name,
+        params.join(", "),
+        output
+    )
+    .unwrap();
+}
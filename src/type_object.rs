@@ -2,11 +2,11 @@
 
 //! Python type object information
 
-use crate::class::methods::PyMethodDefType;
-use crate::err::{PyErr, PyResult};
+use crate::class::methods::{PyMethodDefType, PySlotDef};
+use crate::err::{PyBorrowError, PyErr, PyResult};
+use crate::exceptions::{ImportError, PanicException};
 use crate::instance::{Py, PyNativeType};
-use crate::types::PyAny;
-use crate::types::PyType;
+use crate::types::{PyAny, PyTuple, PyType};
 use crate::AsPyPointer;
 use crate::IntoPyPointer;
 use crate::Python;
@@ -15,7 +15,9 @@ use class::methods::PyMethodsProtocol;
 use std::collections::HashMap;
 use std::ffi::CString;
 use std::os::raw::c_void;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicPtr, Ordering};
 
 /// Python type information.
 pub trait PyTypeInfo {
@@ -148,6 +150,9 @@ impl PyRawObject {
             // The `as *mut u8` part is required because the offset is in bytes
             let ptr = (self.ptr as *mut u8).offset(T::OFFSET) as *mut T;
             std::ptr::write(ptr, value);
+            if let Some(flag) = borrow_flag::<T>(self.ptr) {
+                *flag = UNUSED;
+            }
         }
     }
 
@@ -177,6 +182,91 @@ impl IntoPyPointer for PyRawObject {
 
 unsafe impl PyNativeType for PyRawObject {}
 
+/// The borrow state of a `#[pyclass]` instance, which is stored right after the rust value.
+///
+/// It counts the live `PyRef`s of the instance, or is `-1` while there is a `PyRefMut`.
+#[doc(hidden)]
+pub type BorrowFlag = isize;
+
+const UNUSED: BorrowFlag = 0;
+const BORROWED_MUT: BorrowFlag = -1;
+
+/// Offset of the borrow flag of a `#[pyclass]` value of `size` bytes at `offset`
+#[doc(hidden)]
+pub const fn borrow_flag_offset(offset: usize, size: usize) -> usize {
+    let align = std::mem::align_of::<BorrowFlag>();
+    (offset + size + align - 1) / align * align
+}
+
+/// The borrow flag of the `T` in `obj`, or `None` if `T` is a native type, which isn't
+/// borrow checked
+unsafe fn borrow_flag<T: PyTypeInfo>(obj: *mut ffi::PyObject) -> Option<*mut BorrowFlag> {
+    if T::OFFSET == 0 {
+        None
+    } else {
+        let offset = borrow_flag_offset(T::OFFSET as usize, std::mem::size_of::<T>());
+        Some((obj as *mut u8).add(offset) as *mut BorrowFlag)
+    }
+}
+
+/// A borrow of a `#[pyclass]` instance, which is released when the guard is dropped.
+///
+/// The guard of a native type doesn't track anything, as only `#[pyclass]` instances are
+/// borrow checked.
+#[derive(Debug)]
+pub(crate) struct BorrowGuard {
+    flag: Option<*mut BorrowFlag>,
+}
+
+impl BorrowGuard {
+    /// A guard that doesn't hold any borrow
+    pub(crate) fn unused() -> Self {
+        BorrowGuard { flag: None }
+    }
+
+    /// Acquires a shared borrow of the `T` in `obj`
+    pub(crate) unsafe fn try_borrow<T: PyTypeInfo>(
+        obj: *mut ffi::PyObject,
+    ) -> Result<Self, PyBorrowError> {
+        let flag = borrow_flag::<T>(obj);
+        if let Some(flag) = flag {
+            if *flag == BORROWED_MUT {
+                return Err(PyBorrowError::already_mutably_borrowed());
+            }
+            *flag += 1;
+        }
+        Ok(BorrowGuard { flag })
+    }
+
+    /// Acquires the mutable borrow of the `T` in `obj`
+    pub(crate) unsafe fn try_borrow_mut<T: PyTypeInfo>(
+        obj: *mut ffi::PyObject,
+    ) -> Result<Self, PyBorrowError> {
+        let flag = borrow_flag::<T>(obj);
+        if let Some(flag) = flag {
+            if *flag != UNUSED {
+                return Err(PyBorrowError::already_borrowed());
+            }
+            *flag = BORROWED_MUT;
+        }
+        Ok(BorrowGuard { flag })
+    }
+}
+
+impl Drop for BorrowGuard {
+    fn drop(&mut self) {
+        if let Some(flag) = self.flag {
+            unsafe {
+                if *flag == BORROWED_MUT {
+                    *flag = UNUSED;
+                } else {
+                    *flag -= 1;
+                }
+            }
+        }
+    }
+}
+
 pub(crate) unsafe fn pytype_drop<T: PyTypeInfo>(py: Python, obj: *mut ffi::PyObject) {
     if T::OFFSET != 0 {
         let ptr = (obj as *mut u8).offset(T::OFFSET) as *mut T;
@@ -185,44 +275,110 @@ pub(crate) unsafe fn pytype_drop<T: PyTypeInfo>(py: Python, obj: *mut ffi::PyObj
     }
 }
 
+/// Returns the type object of the first native (i.e. not `#[pyclass]`) type in the
+/// inheritance chain of `T`, which is `object` unless `T` extends a builtin type.
+pub(crate) unsafe fn native_base_type<T: PyTypeInfo>() -> *mut ffi::PyTypeObject {
+    if T::OFFSET == 0 {
+        T::type_object()
+    } else {
+        native_base_type::<T::BaseType>()
+    }
+}
+
+/// Allocates an instance of `subtype`, which must be `T` or a subclass of `T`.
+///
+/// If `T` extends a builtin type other than `object`, the instance is created by the `tp_new` of
+/// that builtin type with `args` and `kwargs`, so that its part of the object is properly
+/// initialized, e.g. an exception keeps the arguments for its `args` and `str()`. `args` may be
+/// null for no arguments. Only the rust part of the object is left for `PyRawObject::init`.
+pub unsafe fn alloc_object<T: PyTypeInfo>(
+    py: Python,
+    subtype: *mut ffi::PyTypeObject,
+    args: *mut ffi::PyObject,
+    kwargs: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    let base = native_base_type::<T>();
+    if base != &mut ffi::PyBaseObject_Type as *mut _ {
+        if let Some(new) = (*base).tp_new {
+            let args = if args.is_null() {
+                PyTuple::empty(py).as_ptr()
+            } else {
+                args
+            };
+            return new(subtype, args, kwargs);
+        }
+    }
+    let alloc = (*subtype).tp_alloc.unwrap_or(ffi::PyType_GenericAlloc);
+    alloc(subtype, 0)
+}
+
+/// Frees the memory of `obj` with the `tp_free` of its type.
+///
+/// The type is a heap type if `obj` is an instance of a Python subclass. Its reference to the
+/// type object is released by `subtype_dealloc`, so it must not be released here.
+pub(crate) unsafe fn free_object(obj: *mut ffi::PyObject) {
+    let ty = ffi::Py_TYPE(obj);
+    match (*ty).tp_free {
+        Some(free) => free(obj as *mut c_void),
+        None if ffi::PyType_IS_GC(ty) != 0 => ffi::PyObject_GC_Del(obj as *mut c_void),
+        None => ffi::PyObject_Free(obj as *mut c_void),
+    }
+}
+
 /// A Python object allocator that is usable as a base type for `#[pyclass]`
 ///
 /// All native types and all `#[pyclass]` types use the default functions, while
 /// [PyObjectWithFreeList](crate::freelist::PyObjectWithFreeList) gets a special version.
 pub trait PyObjectAlloc: PyTypeInfo + Sized {
-    unsafe fn alloc(_py: Python) -> *mut ffi::PyObject {
-        let tp_ptr = Self::type_object();
-        let alloc = (*tp_ptr).tp_alloc.unwrap_or(ffi::PyType_GenericAlloc);
-        alloc(tp_ptr, 0)
+    unsafe fn alloc(py: Python) -> *mut ffi::PyObject {
+        alloc_object::<Self>(py, Self::type_object(), ptr::null_mut(), ptr::null_mut())
     }
 
     /// Calls the rust destructor for the object and frees the memory
     /// (usually by calling ptr->ob_type->tp_free).
     /// This function is used as tp_dealloc implementation.
+    ///
+    /// `obj` might be an instance of a Python subclass, in which case this is called by
+    /// `subtype_dealloc` after it has cleaned up the slots added by the subclass.
     unsafe fn dealloc(py: Python, obj: *mut ffi::PyObject) {
-        Self::drop(py, obj);
-
         if ffi::PyObject_CallFinalizerFromDealloc(obj) < 0 {
             return;
         }
 
-        match Self::type_object().tp_free {
-            Some(free) => free(obj as *mut c_void),
-            None => {
-                let ty = ffi::Py_TYPE(obj);
-                if ffi::PyType_IS_GC(ty) != 0 {
-                    ffi::PyObject_GC_Del(obj as *mut c_void);
-                } else {
-                    ffi::PyObject_Free(obj as *mut c_void);
-                }
+        // The garbage collector must not traverse the object while it is torn down
+        if ffi::PyType_IS_GC(ffi::Py_TYPE(obj)) != 0 {
+            ffi::PyObject_GC_UnTrack(obj as *mut c_void);
+        }
+
+        let tp_ptr = Self::type_object() as *mut ffi::PyTypeObject;
+        let base = native_base_type::<Self>();
+
+        // `#[pyclass(weakref)]` and `#[pyclass(dict)]` slots, unless the base type owns them
+        if (*tp_ptr).tp_weaklistoffset != 0
+            && (*tp_ptr).tp_weaklistoffset != (*base).tp_weaklistoffset
+        {
+            ffi::PyObject_ClearWeakRefs(obj);
+        }
+        if (*tp_ptr).tp_dictoffset != 0 && (*tp_ptr).tp_dictoffset != (*base).tp_dictoffset {
+            let dict_ptr =
+                (obj as *mut u8).offset((*tp_ptr).tp_dictoffset) as *mut *mut ffi::PyObject;
+            ffi::Py_CLEAR(&mut *dict_ptr);
+        }
+
+        Self::drop(py, obj);
 
-                // For heap types, PyType_GenericAlloc calls INCREF on the type objects,
-                // so we need to call DECREF here:
-                if ffi::PyType_HasFeature(ty, ffi::Py_TPFLAGS_HEAPTYPE) != 0 {
-                    ffi::Py_DECREF(ty as *mut ffi::PyObject);
+        // A builtin base type cleans up its part of the object and frees the memory itself
+        if base != tp_ptr && base != &mut ffi::PyBaseObject_Type as *mut _ {
+            if let Some(base_dealloc) = (*base).tp_dealloc {
+                // The deallocator of a gc type expects the object to be tracked
+                if ffi::PyType_IS_GC(base) != 0 {
+                    ffi::PyObject_GC_Track(obj as *mut c_void);
                 }
+                return base_dealloc(obj);
             }
         }
+
+        free_object(obj);
     }
 
     #[allow(unconditional_recursion)]
@@ -271,8 +427,8 @@ where
 
 /// Python object types that can be instanciated with [Self::create()]
 ///
-/// We can't just make this a part of [PyTypeObject] because exceptions have
-/// no PyTypeInfo
+/// We can't just make this a part of [PyTypeObject] because exceptions defined
+/// with `create_exception!` or `import_exception!` have no PyTypeInfo
 pub trait PyTypeCreate: PyObjectAlloc + PyTypeObject + Sized {
     /// Create PyRawObject which can be initialized with rust value
     #[must_use]
@@ -371,6 +527,9 @@ where
     // buffer protocol
     type_object.tp_as_buffer = to_ptr(<T as class::buffer::PyBufferProtocolImpl>::tp_as_buffer());
 
+    // dunder methods of #[pymethods], which take precedence over the protocol traits
+    py_class_slots::<T>(type_object);
+
     // normal methods
     let (new, init, call, mut methods) = py_class_method_defs::<T>();
     if !methods.is_empty() {
@@ -408,11 +567,111 @@ where
 
     // register type object
     unsafe {
-        if ffi::PyType_Ready(type_object) == 0 {
-            Ok(type_object as *mut ffi::PyTypeObject)
-        } else {
-            PyErr::fetch(py).into()
+        if ffi::PyType_Ready(type_object) != 0 {
+            return PyErr::fetch(py).into();
+        }
+    }
+    register_type_interpreter(type_object);
+
+    // class attributes may be instances of the class itself, so they can only be created
+    // once the type object is ready
+    py_class_attributes::<T>(py, type_object)?;
+
+    Ok(type_object as *mut ffi::PyTypeObject)
+}
+
+/// The interpreters that the type objects of `#[pyclass]`es were initialized in, as addresses
+static TYPE_INTERPRETERS: spin::Mutex<Vec<(usize, usize)>> = spin::Mutex::new(Vec::new());
+
+fn register_type_interpreter(type_object: *mut ffi::PyTypeObject) {
+    let interpreter = unsafe { gil::current_interpreter() } as usize;
+    let mut interpreters = TYPE_INTERPRETERS.lock();
+    if !interpreters
+        .iter()
+        .any(|&(ty, _)| ty == type_object as usize)
+    {
+        interpreters.push((type_object as usize, interpreter));
+    }
+}
+
+/// Fails with an `ImportError` if the type object of `T` was initialized in another interpreter
+/// than the current one. Type objects are static, so they can't be used in more than one
+/// interpreter.
+pub(crate) fn check_type_interpreter<T: PyTypeInfo>() -> PyResult<()> {
+    let type_object = unsafe { T::type_object() } as *mut ffi::PyTypeObject as usize;
+    let interpreter = unsafe { gil::current_interpreter() } as usize;
+    let interpreters = TYPE_INTERPRETERS.lock();
+    match interpreters.iter().find(|&&(ty, _)| ty == type_object) {
+        Some(&(_, owner)) if owner != interpreter => Err(ImportError::py_err(format!(
+            "Class {} was created in another interpreter and can't be used in this one",
+            T::NAME
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// A type object that is created at runtime on its first use, like the exceptions of
+/// `create_exception!` and `import_exception!`.
+///
+/// It is reset when the interpreter is finalized, so that it is created again in the next
+/// interpreter.
+#[doc(hidden)]
+pub struct LazyTypeObject(AtomicPtr<ffi::PyTypeObject>);
+
+/// The `LazyTypeObject`s that were created
+static LAZY_TYPE_OBJECTS: spin::Mutex<Vec<&'static LazyTypeObject>> = spin::Mutex::new(Vec::new());
+
+impl LazyTypeObject {
+    pub const fn new() -> Self {
+        LazyTypeObject(AtomicPtr::new(ptr::null_mut()))
+    }
+
+    /// Returns the type object, which is created with `create` if it doesn't exist yet.
+    /// `create` has to return an owned reference.
+    pub fn get_or_init<F>(&'static self, create: F) -> NonNull<ffi::PyTypeObject>
+    where
+        F: FnOnce(Python) -> *mut ffi::PyTypeObject,
+    {
+        if let Some(type_object) = NonNull::new(self.0.load(Ordering::Acquire)) {
+            return type_object;
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // `create` may release the GIL, e.g. to import a module, so that another thread can
+        // create the type object in the meantime. The first one wins.
+        let type_object = create(py);
+        match self.0.compare_exchange(
+            ptr::null_mut(),
+            type_object,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                LAZY_TYPE_OBJECTS.lock().push(self);
+                unsafe { NonNull::new_unchecked(type_object) }
+            }
+            Err(existing) => unsafe {
+                ffi::Py_DECREF(type_object as *mut ffi::PyObject);
+                NonNull::new_unchecked(existing)
+            },
+        }
+    }
+}
+
+/// Resets the type objects of the `#[pyclass]`es and the lazily created type objects after the
+/// interpreter was finalized, so that they are created again when they are used the next time.
+pub(crate) unsafe fn reset_type_objects() {
+    for (type_object, _) in TYPE_INTERPRETERS.lock().drain(..) {
+        #[cfg(not(Py_LIMITED_API))]
+        {
+            *(type_object as *mut ffi::PyTypeObject) = ffi::PyTypeObject_INIT;
         }
+        #[cfg(Py_LIMITED_API)]
+        let _ = type_object;
+    }
+    for lazy in LAZY_TYPE_OBJECTS.lock().drain(..) {
+        lazy.0.store(ptr::null_mut(), Ordering::Release);
     }
 }
 
@@ -422,7 +681,12 @@ where
 {
     let py = Python::assume_gil_acquired();
     let _pool = gil::GILPool::new_no_pointers(py);
-    <T as PyObjectAlloc>::dealloc(py, obj)
+    // A deallocator can't raise an exception, so a panic in `drop` is reported as unraisable
+    let result = panic::catch_unwind(AssertUnwindSafe(|| <T as PyObjectAlloc>::dealloc(py, obj)));
+    if let Err(payload) = result {
+        PanicException::from_panic_payload(payload).restore(py);
+        ffi::PyErr_WriteUnraisable(ptr::null_mut());
+    }
 }
 fn py_class_flags<T: PyTypeInfo>(type_object: &mut ffi::PyTypeObject) {
     if type_object.tp_traverse != None
@@ -506,6 +770,133 @@ fn py_class_async_methods<T>(defs: &mut Vec<ffi::PyMethodDef>) {
     }
 }
 
+#[cfg(not(Py_LIMITED_API))]
+fn py_class_slots<T: PyMethodsProtocol>(type_object: &mut ffi::PyTypeObject) {
+    fn or_init<T>(slots: &mut *mut T, init: T) -> &mut T {
+        if slots.is_null() {
+            *slots = Box::into_raw(Box::new(init));
+        }
+        unsafe { &mut **slots }
+    }
+    fn nb(type_object: &mut ffi::PyTypeObject) -> &mut ffi::PyNumberMethods {
+        or_init(&mut type_object.tp_as_number, ffi::PyNumberMethods_INIT)
+    }
+
+    for def in T::py_methods() {
+        let slot = match *def {
+            PyMethodDefType::Slot(slot) => slot,
+            _ => continue,
+        };
+        match slot {
+            PySlotDef::Repr(f) => type_object.tp_repr = Some(f),
+            PySlotDef::Str(f) => type_object.tp_str = Some(f),
+            PySlotDef::Hash(f) => type_object.tp_hash = Some(f),
+            PySlotDef::RichCompare(f) => type_object.tp_richcompare = Some(f),
+            PySlotDef::GetAttr(f) => type_object.tp_getattro = Some(f),
+            PySlotDef::SetAttr(f) => type_object.tp_setattro = Some(f),
+            PySlotDef::Iter(f) => type_object.tp_iter = Some(f),
+            PySlotDef::IterNext(f) => type_object.tp_iternext = Some(f),
+            PySlotDef::Len(f) => {
+                or_init(&mut type_object.tp_as_mapping, ffi::PyMappingMethods_INIT).mp_length =
+                    Some(f);
+                or_init(&mut type_object.tp_as_sequence, ffi::PySequenceMethods_INIT)
+                    .sq_length = Some(f);
+            }
+            PySlotDef::GetItem(f) => {
+                or_init(&mut type_object.tp_as_mapping, ffi::PyMappingMethods_INIT)
+                    .mp_subscript = Some(f);
+                // Makes the class a sequence for `PySequence_Check`, `iter()` and `in`
+                or_init(&mut type_object.tp_as_sequence, ffi::PySequenceMethods_INIT)
+                    .sq_item = Some(sq_item_from_subscript);
+            }
+            PySlotDef::SetItem(f) => {
+                or_init(&mut type_object.tp_as_mapping, ffi::PyMappingMethods_INIT)
+                    .mp_ass_subscript = Some(f)
+            }
+            PySlotDef::Contains(f) => {
+                or_init(&mut type_object.tp_as_sequence, ffi::PySequenceMethods_INIT)
+                    .sq_contains = Some(f)
+            }
+            PySlotDef::Bool(f) => nb(type_object).nb_bool = Some(f),
+            PySlotDef::Neg(f) => nb(type_object).nb_negative = Some(f),
+            PySlotDef::Pos(f) => nb(type_object).nb_positive = Some(f),
+            PySlotDef::Abs(f) => nb(type_object).nb_absolute = Some(f),
+            PySlotDef::Invert(f) => nb(type_object).nb_invert = Some(f),
+            PySlotDef::Int(f) => nb(type_object).nb_int = Some(f),
+            PySlotDef::Float(f) => nb(type_object).nb_float = Some(f),
+            PySlotDef::Index(f) => nb(type_object).nb_index = Some(f),
+            PySlotDef::Add(f) => nb(type_object).nb_add = Some(f),
+            PySlotDef::Sub(f) => nb(type_object).nb_subtract = Some(f),
+            PySlotDef::Mul(f) => nb(type_object).nb_multiply = Some(f),
+            PySlotDef::MatMul(f) => nb(type_object).nb_matrix_multiply = Some(f),
+            PySlotDef::TrueDiv(f) => nb(type_object).nb_true_divide = Some(f),
+            PySlotDef::FloorDiv(f) => nb(type_object).nb_floor_divide = Some(f),
+            PySlotDef::Mod(f) => nb(type_object).nb_remainder = Some(f),
+            PySlotDef::DivMod(f) => nb(type_object).nb_divmod = Some(f),
+            PySlotDef::Pow(f) => nb(type_object).nb_power = Some(f),
+            PySlotDef::LShift(f) => nb(type_object).nb_lshift = Some(f),
+            PySlotDef::RShift(f) => nb(type_object).nb_rshift = Some(f),
+            PySlotDef::And(f) => nb(type_object).nb_and = Some(f),
+            PySlotDef::Xor(f) => nb(type_object).nb_xor = Some(f),
+            PySlotDef::Or(f) => nb(type_object).nb_or = Some(f),
+            PySlotDef::IAdd(f) => nb(type_object).nb_inplace_add = Some(f),
+            PySlotDef::ISub(f) => nb(type_object).nb_inplace_subtract = Some(f),
+            PySlotDef::IMul(f) => nb(type_object).nb_inplace_multiply = Some(f),
+            PySlotDef::IMatMul(f) => nb(type_object).nb_inplace_matrix_multiply = Some(f),
+            PySlotDef::ITrueDiv(f) => nb(type_object).nb_inplace_true_divide = Some(f),
+            PySlotDef::IFloorDiv(f) => nb(type_object).nb_inplace_floor_divide = Some(f),
+            PySlotDef::IMod(f) => nb(type_object).nb_inplace_remainder = Some(f),
+            PySlotDef::IPow(f) => nb(type_object).nb_inplace_power = Some(f),
+            PySlotDef::ILShift(f) => nb(type_object).nb_inplace_lshift = Some(f),
+            PySlotDef::IRShift(f) => nb(type_object).nb_inplace_rshift = Some(f),
+            PySlotDef::IAnd(f) => nb(type_object).nb_inplace_and = Some(f),
+            PySlotDef::IXor(f) => nb(type_object).nb_inplace_xor = Some(f),
+            PySlotDef::IOr(f) => nb(type_object).nb_inplace_or = Some(f),
+        }
+    }
+}
+
+/// `sq_item` of a class with `__getitem__`, which calls `mp_subscript` with the index
+unsafe extern "C" fn sq_item_from_subscript(
+    obj: *mut ffi::PyObject,
+    index: ffi::Py_ssize_t,
+) -> *mut ffi::PyObject {
+    let index = ffi::PyLong_FromSsize_t(index);
+    if index.is_null() {
+        return std::ptr::null_mut();
+    }
+    let result = ffi::PyObject_GetItem(obj, index);
+    ffi::Py_DECREF(index);
+    result
+}
+
+fn py_class_attributes<T: PyMethodsProtocol>(
+    py: Python,
+    type_object: &mut ffi::PyTypeObject,
+) -> PyResult<()> {
+    let mut modified = false;
+
+    for def in T::py_methods() {
+        if let PyMethodDefType::ClassAttribute(ref attr) = *def {
+            let name =
+                CString::new(attr.name).expect("Class attribute name must not contain NUL byte");
+            let value = (attr.meth)(py)?;
+            let result = unsafe {
+                ffi::PyDict_SetItemString(type_object.tp_dict, name.as_ptr(), value.as_ptr())
+            };
+            if result != 0 {
+                return Err(PyErr::fetch(py));
+            }
+            modified = true;
+        }
+    }
+
+    if modified {
+        unsafe { ffi::PyType_Modified(type_object) };
+    }
+    Ok(())
+}
+
 fn py_class_properties<T: PyMethodsProtocol>() -> Vec<ffi::PyGetSetDef> {
     let mut defs = HashMap::new();
 
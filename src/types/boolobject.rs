@@ -2,6 +2,7 @@
 use crate::ffi;
 use crate::object::PyObject;
 use crate::types::PyAny;
+use crate::types::Unsendable;
 use crate::FromPyObject;
 use crate::PyResult;
 use crate::Python;
@@ -10,7 +11,7 @@ use crate::{PyTryFrom, ToPyObject};
 
 /// Represents a Python `bool`.
 #[repr(transparent)]
-pub struct PyBool(PyObject);
+pub struct PyBool(PyObject, Unsendable);
 
 pyobject_native_type!(PyBool, ffi::PyBool_Type, ffi::PyBool_Check);
 
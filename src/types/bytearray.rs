@@ -4,6 +4,7 @@ use crate::err::{PyErr, PyResult};
 use crate::ffi;
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
+use crate::types::Unsendable;
 use crate::AsPyPointer;
 use crate::Python;
 use std::os::raw::c_char;
@@ -11,7 +12,7 @@ use std::slice;
 
 /// Represents a Python `bytearray`.
 #[repr(transparent)]
-pub struct PyByteArray(PyObject);
+pub struct PyByteArray(PyObject, Unsendable);
 
 pyobject_native_type!(PyByteArray, ffi::PyByteArray_Type, ffi::PyByteArray_Check);
 
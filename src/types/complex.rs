@@ -1,6 +1,7 @@
 use crate::ffi;
 #[cfg(not(PyPy))]
 use crate::instance::PyNativeType;
+use crate::types::Unsendable;
 use crate::AsPyPointer;
 use crate::PyObject;
 use crate::Python;
@@ -10,9 +11,14 @@ use std::os::raw::c_double;
 
 /// Represents a Python `complex`.
 #[repr(transparent)]
-pub struct PyComplex(PyObject);
+pub struct PyComplex(PyObject, Unsendable);
 
-pyobject_native_type!(PyComplex, ffi::PyComplex_Type, ffi::PyComplex_Check);
+pyobject_native_type!(
+    PyComplex,
+    layout = ffi::PyComplexObject,
+    ffi::PyComplex_Type,
+    ffi::PyComplex_Check
+);
 
 impl PyComplex {
     /// Creates a new Python `PyComplex` object, from its real and imaginary values.
@@ -6,6 +6,7 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::err::PyResult;
+use crate::exceptions::{OverflowError, ValueError};
 use crate::ffi;
 #[cfg(PyPy)]
 use crate::ffi::datetime::{PyDateTime_FromTimestamp, PyDate_FromTimestamp};
@@ -25,14 +26,20 @@ use crate::ffi::{
     PyDateTime_TIME_GET_HOUR, PyDateTime_TIME_GET_MICROSECOND, PyDateTime_TIME_GET_MINUTE,
     PyDateTime_TIME_GET_SECOND,
 };
+use crate::instance::PyNativeType;
 use crate::object::PyObject;
+use crate::objectprotocol::ObjectProtocol;
+use crate::types::PyAny;
 use crate::types::PyTuple;
+use crate::types::Unsendable;
 use crate::AsPyPointer;
 use crate::Python;
-use crate::ToPyObject;
+use crate::{FromPyObject, IntoPy, PyTryFrom, ToPyObject};
+use std::cmp;
 use std::os::raw::c_int;
 #[cfg(not(PyPy))]
 use std::ptr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Access traits
 
@@ -65,9 +72,10 @@ pub trait PyTimeAccess {
 }
 
 /// Bindings around `datetime.date`
-pub struct PyDate(PyObject);
+pub struct PyDate(PyObject, Unsendable);
 pyobject_native_type!(
     PyDate,
+    layout = ffi::PyDateTime_Date,
     *PyDateTimeAPI.DateType,
     Some("datetime"),
     PyDate_Check
@@ -120,7 +128,7 @@ impl PyDateAccess for PyDate {
 }
 
 /// Bindings for `datetime.datetime`
-pub struct PyDateTime(PyObject);
+pub struct PyDateTime(PyObject, Unsendable);
 pyobject_native_type!(
     PyDateTime,
     *PyDateTimeAPI.DateTimeType,
@@ -189,6 +197,29 @@ impl PyDateTime {
             py.from_owned_ptr_or_err(ptr)
         }
     }
+
+    /// Construct a `datetime` in UTC from a `SystemTime`, rounded down to microseconds
+    ///
+    /// Raises an `OverflowError` if the time is outside of the range of `datetime`.
+    pub fn from_system_time(py: Python, time: SystemTime) -> PyResult<&PyDateTime> {
+        let epoch = unix_epoch(py)?;
+        let ptr = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => {
+                let delta = PyDelta::from_duration(py, after)?;
+                unsafe { ffi::PyNumber_Add(epoch.as_ptr(), delta.as_ptr()) }
+            }
+            Err(before) => {
+                let mut before = before.duration();
+                let nanos = before.subsec_nanos() % 1000;
+                if nanos != 0 {
+                    before += Duration::from_nanos(u64::from(1000 - nanos));
+                }
+                let delta = PyDelta::from_duration(py, before)?;
+                unsafe { ffi::PyNumber_Subtract(epoch.as_ptr(), delta.as_ptr()) }
+            }
+        };
+        unsafe { py.from_owned_ptr_or_err(ptr) }
+    }
 }
 
 impl PyDateAccess for PyDateTime {
@@ -229,7 +260,7 @@ impl PyTimeAccess for PyDateTime {
 }
 
 /// Bindings for `datetime.time`
-pub struct PyTime(PyObject);
+pub struct PyTime(PyObject, Unsendable);
 pyobject_native_type!(
     PyTime,
     *PyDateTimeAPI.TimeType,
@@ -313,7 +344,7 @@ impl PyTimeAccess for PyTime {
 /// Bindings for `datetime.tzinfo`
 ///
 /// This is an abstract base class and should not be constructed directly.
-pub struct PyTzInfo(PyObject);
+pub struct PyTzInfo(PyObject, Unsendable);
 pyobject_native_type!(
     PyTzInfo,
     *PyDateTimeAPI.TZInfoType,
@@ -322,9 +353,10 @@ pyobject_native_type!(
 );
 
 /// Bindings for `datetime.timedelta`
-pub struct PyDelta(PyObject);
+pub struct PyDelta(PyObject, Unsendable);
 pyobject_native_type!(
     PyDelta,
+    layout = ffi::PyDateTime_Delta,
     *PyDateTimeAPI.DeltaType,
     Some("datetime"),
     PyDelta_Check
@@ -349,6 +381,26 @@ impl PyDelta {
             py.from_owned_ptr_or_err(ptr)
         }
     }
+
+    /// Construct a `timedelta` from a `Duration`, truncated to microseconds
+    ///
+    /// Raises an `OverflowError` if the duration is longer than `timedelta.max`.
+    pub fn from_duration(py: Python, duration: Duration) -> PyResult<&PyDelta> {
+        let days = duration.as_secs() / SECONDS_PER_DAY;
+        if days > i32::max_value() as u64 {
+            return Err(OverflowError::py_err(format!(
+                "a duration of {} days is too long for a timedelta",
+                days
+            )));
+        }
+        PyDelta::new(
+            py,
+            days as i32,
+            (duration.as_secs() % SECONDS_PER_DAY) as i32,
+            duration.subsec_micros() as i32,
+            false,
+        )
+    }
 }
 
 impl PyDeltaAccess for PyDelta {
@@ -365,6 +417,135 @@ impl PyDeltaAccess for PyDelta {
     }
 }
 
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Returns `timedelta.max`
+fn timedelta_max() -> Duration {
+    Duration::new(1_000_000_000 * SECONDS_PER_DAY - 1, 999_999_000)
+}
+
+/// The distance of `datetime.min` in UTC from the unix epoch
+const DATETIME_MIN_BEFORE_EPOCH: Duration = Duration::from_secs(719_162 * SECONDS_PER_DAY);
+
+/// The distance of `datetime.max` in UTC from the unix epoch
+const DATETIME_MAX_AFTER_EPOCH: Duration =
+    Duration::from_micros((2_932_897 * SECONDS_PER_DAY - 1) * 1_000_000 + 999_999);
+
+/// Converts a `Duration` to a `timedelta`.
+///
+/// Durations longer than `timedelta.max` are clamped to `timedelta.max`, use
+/// [PyDelta::from_duration] to get an `OverflowError` instead.
+///
+/// May panic when running out of memory.
+impl ToPyObject for Duration {
+    fn to_object(&self, py: Python) -> PyObject {
+        PyDelta::from_duration(py, cmp::min(*self, timedelta_max()))
+            .expect("Failed to create a timedelta")
+            .to_object(py)
+    }
+}
+
+impl IntoPy<PyObject> for Duration {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+}
+
+/// Extracts a `Duration` from a `timedelta`, raising a `ValueError` if it is negative.
+impl<'source> FromPyObject<'source> for Duration {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let delta = <PyDelta as PyTryFrom>::try_from(ob)?;
+        let days = delta.get_days();
+        if days < 0 {
+            return Err(ValueError::py_err(
+                "a negative timedelta can't be converted to a Duration",
+            ));
+        }
+        Ok(Duration::new(
+            days as u64 * SECONDS_PER_DAY + delta.get_seconds() as u64,
+            delta.get_microseconds() as u32 * 1000,
+        ))
+    }
+}
+
+/// Converts a `SystemTime` to a `datetime` in UTC.
+///
+/// Times outside of the range of `datetime` are clamped to `datetime.min` or `datetime.max`,
+/// use [PyDateTime::from_system_time] to get an `OverflowError` instead.
+///
+/// May panic when running out of memory.
+impl ToPyObject for SystemTime {
+    fn to_object(&self, py: Python) -> PyObject {
+        let time = match self.duration_since(UNIX_EPOCH) {
+            Ok(after) if after > DATETIME_MAX_AFTER_EPOCH => UNIX_EPOCH + DATETIME_MAX_AFTER_EPOCH,
+            Err(before) if before.duration() > DATETIME_MIN_BEFORE_EPOCH => {
+                UNIX_EPOCH - DATETIME_MIN_BEFORE_EPOCH
+            }
+            _ => *self,
+        };
+        PyDateTime::from_system_time(py, time)
+            .expect("Failed to create a datetime")
+            .to_object(py)
+    }
+}
+
+impl IntoPy<PyObject> for SystemTime {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+}
+
+/// Extracts a `SystemTime` from a `datetime` with a time zone.
+///
+/// Naive datetimes raise a `ValueError`, because it isn't clear which time zone they are in.
+/// Like in python, a datetime whose `tzinfo` returns no utc offset counts as naive.
+impl<'source> FromPyObject<'source> for SystemTime {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let py = ob.py();
+        let datetime = <PyDateTime as PyTryFrom>::try_from(ob)?;
+        if datetime.call_method0("utcoffset")?.is_none() {
+            return Err(ValueError::py_err(
+                "a naive datetime can't be converted to a SystemTime",
+            ));
+        }
+        let delta: &PyDelta = unsafe {
+            let epoch = unix_epoch(py)?;
+            py.from_owned_ptr_or_err(ffi::PyNumber_Subtract(datetime.as_ptr(), epoch.as_ptr()))?
+        };
+        let seconds =
+            i64::from(delta.get_days()) * SECONDS_PER_DAY as i64 + i64::from(delta.get_seconds());
+        let microseconds = Duration::from_micros(delta.get_microseconds() as u64);
+        let time = if seconds >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64) + microseconds)
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(-seconds as u64))
+                .and_then(|time| time.checked_add(microseconds))
+        };
+        time.ok_or_else(|| OverflowError::py_err("datetime is out of the range of SystemTime"))
+    }
+}
+
+/// Returns `datetime.timezone.utc`
+fn utc(py: Python) -> PyResult<&PyTzInfo> {
+    #[cfg(Py_3_7)]
+    unsafe {
+        Ok(py.from_borrowed_ptr(PyDateTimeAPI.TimeZone_UTC))
+    }
+
+    #[cfg(not(Py_3_7))]
+    {
+        let utc = py.import("datetime")?.get("timezone")?.getattr("utc")?;
+        Ok(<PyTzInfo as PyTryFrom>::try_from(utc)?)
+    }
+}
+
+/// Returns `1970-01-01T00:00:00+00:00`
+fn unix_epoch(py: Python) -> PyResult<&PyDateTime> {
+    let utc = utc(py)?.to_object(py);
+    PyDateTime::new(py, 1970, 1, 1, 0, 0, 0, 0, Some(&utc))
+}
+
 // Utility function
 unsafe fn opt_to_pyobj(py: Python, opt: Option<&PyObject>) -> *mut ffi::PyObject {
     // Convenience function for unpacking Options to either an Object or None
@@ -373,3 +554,421 @@ unsafe fn opt_to_pyobj(py: Python, opt: Option<&PyObject>) -> *mut ffi::PyObject
         None => py.None().as_ptr(),
     }
 }
+
+#[cfg(feature = "chrono")]
+mod chrono_conversion {
+    //! Conversions of the date and time types of `chrono`
+    //!
+    //! Date times with a time zone are converted to a `datetime` with a fixed
+    //! `datetime.timezone`, which makes them unambiguous. When a `datetime` is extracted, the
+    //! offset is the result of its `utcoffset()`, which takes `fold` into account for the
+    //! ambiguous local times of other `tzinfo` implementations. Python can't represent leap
+    //! seconds, so they are truncated to the end of the previous second.
+    //!
+    //! The years of `chrono` reach far beyond the range of `datetime`, so dates, date times and
+    //! offsets are converted with fallible constructors like `PyDate::from_naive_date`, which
+    //! raise an `OverflowError` for a year that is out of range, instead of `ToPyObject`.
+
+    use super::*;
+    use crate::exceptions::TypeError;
+    use chrono::{
+        DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+        Timelike, Utc,
+    };
+
+    fn microsecond(nanosecond: u32) -> u32 {
+        // A leap second has a nanosecond value of at least one second
+        nanosecond.min(999_999_999) / 1000
+    }
+
+    fn naive_date(date: &impl PyDateAccess) -> PyResult<NaiveDate> {
+        NaiveDate::from_ymd_opt(
+            date.get_year(),
+            date.get_month().into(),
+            date.get_day().into(),
+        )
+        .ok_or_else(|| ValueError::py_err("date is out of the range of NaiveDate"))
+    }
+
+    fn naive_time(time: &impl PyTimeAccess) -> PyResult<NaiveTime> {
+        NaiveTime::from_hms_micro_opt(
+            time.get_hour().into(),
+            time.get_minute().into(),
+            time.get_second().into(),
+            time.get_microsecond(),
+        )
+        .ok_or_else(|| ValueError::py_err("invalid time"))
+    }
+
+    fn check_naive(ob: &PyAny) -> PyResult<()> {
+        if ob.getattr("tzinfo")?.is_none() {
+            Ok(())
+        } else {
+            Err(TypeError::py_err(format!(
+                "expected a naive {} without tzinfo",
+                ob.get_type().name()
+            )))
+        }
+    }
+
+    /// Raises an `OverflowError` if `year` is outside of the range of `date`
+    fn check_year(year: i32) -> PyResult<()> {
+        // `datetime.MINYEAR` and `datetime.MAXYEAR`
+        if year >= 1 && year <= 9999 {
+            Ok(())
+        } else {
+            Err(OverflowError::py_err(format!(
+                "year {} is out of the range of datetime.date",
+                year
+            )))
+        }
+    }
+
+    /// Returns a `datetime.timezone` with the offset
+    fn timezone(py: Python, offset: FixedOffset) -> PyResult<&PyTzInfo> {
+        let delta = PyDelta::new(py, 0, offset.local_minus_utc(), 0, true)?;
+
+        #[cfg(Py_3_7)]
+        unsafe {
+            py.from_owned_ptr_or_err((PyDateTimeAPI.TimeZone_FromTimeZone)(
+                delta.as_ptr(),
+                std::ptr::null_mut(),
+            ))
+        }
+
+        #[cfg(not(Py_3_7))]
+        {
+            let timezone = py.import("datetime")?.get("timezone")?.call1((delta,))?;
+            Ok(<PyTzInfo as PyTryFrom>::try_from(timezone)?)
+        }
+    }
+
+    /// Converts the result of `utcoffset()`
+    fn fixed_offset(offset: &PyAny) -> PyResult<FixedOffset> {
+        if offset.is_none() {
+            return Err(ValueError::py_err("the tzinfo has no utc offset"));
+        }
+        let delta = <PyDelta as PyTryFrom>::try_from(offset)?;
+        if delta.get_microseconds() != 0 {
+            return Err(ValueError::py_err(
+                "a utc offset with microseconds can't be converted to a FixedOffset",
+            ));
+        }
+        FixedOffset::east_opt(delta.get_days() * SECONDS_PER_DAY as i32 + delta.get_seconds())
+            .ok_or_else(|| ValueError::py_err("the utc offset is out of range"))
+    }
+
+    impl PyDate {
+        /// Construct a `date` from a `NaiveDate`
+        ///
+        /// Raises an `OverflowError` if the year is outside of the range of `date`.
+        /// Only available with the `chrono` feature.
+        pub fn from_naive_date<'p>(py: Python<'p>, date: &NaiveDate) -> PyResult<&'p PyDate> {
+            check_year(date.year())?;
+            PyDate::new(py, date.year(), date.month() as u8, date.day() as u8)
+        }
+    }
+
+    /// Extracts a `NaiveDate` from a `date`, which must not be a `datetime`.
+    impl<'source> FromPyObject<'source> for NaiveDate {
+        fn extract(ob: &'source PyAny) -> PyResult<Self> {
+            let date = <PyDate as PyTryFrom>::try_from(ob)?;
+            if unsafe { PyDateTime_Check(ob.as_ptr()) } != 0 {
+                return Err(TypeError::py_err(
+                    "expected a date, a datetime can't be converted to a NaiveDate",
+                ));
+            }
+            naive_date(date)
+        }
+    }
+
+    /// Converts a `NaiveTime` to a `time` without tzinfo.
+    ///
+    /// May panic when running out of memory.
+    impl ToPyObject for NaiveTime {
+        fn to_object(&self, py: Python) -> PyObject {
+            PyTime::new(
+                py,
+                self.hour() as u8,
+                self.minute() as u8,
+                self.second() as u8,
+                microsecond(self.nanosecond()),
+                None,
+            )
+            .expect("Failed to create a time")
+            .to_object(py)
+        }
+    }
+
+    impl IntoPy<PyObject> for NaiveTime {
+        fn into_py(self, py: Python) -> PyObject {
+            self.to_object(py)
+        }
+    }
+
+    /// Extracts a `NaiveTime` from a `time` without tzinfo.
+    impl<'source> FromPyObject<'source> for NaiveTime {
+        fn extract(ob: &'source PyAny) -> PyResult<Self> {
+            let time = <PyTime as PyTryFrom>::try_from(ob)?;
+            check_naive(ob)?;
+            naive_time(time)
+        }
+    }
+
+    /// Converts the local date and time to a `datetime`
+    fn new_datetime<'p>(
+        py: Python<'p>,
+        local: &NaiveDateTime,
+        tzinfo: Option<&PyObject>,
+    ) -> PyResult<&'p PyDateTime> {
+        check_year(local.year())?;
+        PyDateTime::new(
+            py,
+            local.year(),
+            local.month() as u8,
+            local.day() as u8,
+            local.hour() as u8,
+            local.minute() as u8,
+            local.second() as u8,
+            microsecond(local.nanosecond()),
+            tzinfo,
+        )
+    }
+
+    impl PyDateTime {
+        /// Construct a `datetime` without tzinfo from a `NaiveDateTime`
+        ///
+        /// Raises an `OverflowError` if the year is outside of the range of `datetime`.
+        /// Only available with the `chrono` feature.
+        pub fn from_naive_datetime<'p>(
+            py: Python<'p>,
+            datetime: &NaiveDateTime,
+        ) -> PyResult<&'p PyDateTime> {
+            new_datetime(py, datetime, None)
+        }
+
+        /// Construct a `datetime` from a `DateTime`, with a `datetime.timezone` of its current
+        /// offset
+        ///
+        /// Raises an `OverflowError` if the local year is outside of the range of `datetime`.
+        /// Only available with the `chrono` feature.
+        pub fn from_datetime<'p, Tz: TimeZone>(
+            py: Python<'p>,
+            datetime: &DateTime<Tz>,
+        ) -> PyResult<&'p PyDateTime> {
+            let tzinfo = PyTzInfo::from_fixed_offset(py, datetime.offset().fix())?.to_object(py);
+            new_datetime(py, &datetime.naive_local(), Some(&tzinfo))
+        }
+    }
+
+    /// Extracts a `NaiveDateTime` from a `datetime` without tzinfo.
+    impl<'source> FromPyObject<'source> for NaiveDateTime {
+        fn extract(ob: &'source PyAny) -> PyResult<Self> {
+            let datetime = <PyDateTime as PyTryFrom>::try_from(ob)?;
+            check_naive(ob)?;
+            Ok(naive_date(datetime)?.and_time(naive_time(datetime)?))
+        }
+    }
+
+    impl PyTzInfo {
+        /// Construct a `datetime.timezone` from a `FixedOffset`
+        ///
+        /// Before python 3.7, this raises a `ValueError` if the offset isn't a whole number of
+        /// minutes. Only available with the `chrono` feature.
+        pub fn from_fixed_offset(py: Python, offset: FixedOffset) -> PyResult<&PyTzInfo> {
+            timezone(py, offset)
+        }
+    }
+
+    /// Extracts a `FixedOffset` from a `tzinfo` whose `utcoffset(None)` is not `None`.
+    impl<'source> FromPyObject<'source> for FixedOffset {
+        fn extract(ob: &'source PyAny) -> PyResult<Self> {
+            let tzinfo = <PyTzInfo as PyTryFrom>::try_from(ob)?;
+            fixed_offset(tzinfo.call_method1("utcoffset", (ob.py().None(),))?)
+        }
+    }
+
+    /// Converts `Utc` to `datetime.timezone.utc`.
+    ///
+    /// Before python 3.7, this imports `datetime.timezone`, and panics if that fails.
+    impl ToPyObject for Utc {
+        fn to_object(&self, py: Python) -> PyObject {
+            utc(py)
+                .expect("Failed to get datetime.timezone.utc")
+                .to_object(py)
+        }
+    }
+
+    impl IntoPy<PyObject> for Utc {
+        fn into_py(self, py: Python) -> PyObject {
+            self.to_object(py)
+        }
+    }
+
+    /// Extracts `Utc` from a `tzinfo` with a utc offset of zero.
+    impl<'source> FromPyObject<'source> for Utc {
+        fn extract(ob: &'source PyAny) -> PyResult<Self> {
+            if FixedOffset::extract(ob)?.local_minus_utc() == 0 {
+                Ok(Utc)
+            } else {
+                Err(ValueError::py_err(
+                    "expected a tzinfo with a utc offset of zero",
+                ))
+            }
+        }
+    }
+
+    /// Extracts a `DateTime` from a `datetime` with tzinfo, using the offset of `utcoffset()`.
+    impl<'source> FromPyObject<'source> for DateTime<FixedOffset> {
+        fn extract(ob: &'source PyAny) -> PyResult<Self> {
+            let datetime = <PyDateTime as PyTryFrom>::try_from(ob)?;
+            if datetime.getattr("tzinfo")?.is_none() {
+                return Err(TypeError::py_err("expected a datetime with tzinfo"));
+            }
+            // Unlike `tzinfo.utcoffset(None)`, this respects `fold`
+            let offset = fixed_offset(datetime.call_method0("utcoffset")?)?;
+            let local = naive_date(datetime)?.and_time(naive_time(datetime)?);
+            Ok(offset.from_utc_datetime(&(local - offset)))
+        }
+    }
+
+    /// Extracts a `DateTime` in UTC from a `datetime` with tzinfo.
+    impl<'source> FromPyObject<'source> for DateTime<Utc> {
+        fn extract(ob: &'source PyAny) -> PyResult<Self> {
+            DateTime::<FixedOffset>::extract(ob).map(|datetime| datetime.with_timezone(&Utc))
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::instance::AsPyRef;
+
+        #[test]
+        fn naive_round_trip() {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let date = NaiveDate::from_ymd_opt(2019, 10, 27).unwrap();
+            let time = NaiveTime::from_hms_micro_opt(2, 30, 15, 123_456).unwrap();
+            let datetime = date.and_time(time);
+
+            let ob = PyDateTime::from_naive_datetime(py, &datetime).unwrap();
+            assert_eq!(ob.extract::<NaiveDateTime>().unwrap(), datetime);
+            assert!(ob
+                .extract::<NaiveDate>()
+                .unwrap_err()
+                .is_instance::<TypeError>(py));
+            let ob = PyDate::from_naive_date(py, &date).unwrap();
+            assert_eq!(ob.extract::<NaiveDate>().unwrap(), date);
+            assert_eq!(time.to_object(py).extract::<NaiveTime>(py).unwrap(), time);
+        }
+
+        #[test]
+        fn year_out_of_range() {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let date = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+            let err = PyDate::from_naive_date(py, &date).unwrap_err();
+            assert!(err.is_instance::<OverflowError>(py));
+            let datetime = NaiveDate::from_ymd_opt(10_000, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap();
+            let err = PyDateTime::from_naive_datetime(py, &datetime).unwrap_err();
+            assert!(err.is_instance::<OverflowError>(py));
+            let err = PyDateTime::from_datetime(py, &Utc.from_utc_datetime(&datetime)).unwrap_err();
+            assert!(err.is_instance::<OverflowError>(py));
+        }
+
+        #[test]
+        fn leap_second_is_truncated() {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let leap_second = NaiveTime::from_hms_nano_opt(23, 59, 59, 1_500_000_000).unwrap();
+            let time: NaiveTime = leap_second.to_object(py).extract(py).unwrap();
+            assert_eq!(
+                time,
+                NaiveTime::from_hms_micro_opt(23, 59, 59, 999_999).unwrap()
+            );
+        }
+
+        #[test]
+        fn datetime_with_offset() {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let offset = FixedOffset::west_opt(5 * 3600).unwrap();
+            let local = NaiveDate::from_ymd_opt(2019, 10, 27)
+                .unwrap()
+                .and_hms_opt(2, 30, 0)
+                .unwrap();
+            let datetime = offset.from_local_datetime(&local).unwrap();
+
+            let ob = PyDateTime::from_datetime(py, &datetime)
+                .unwrap()
+                .to_object(py);
+            let utcoffset = ob.call_method0(py, "utcoffset").unwrap();
+            let utcoffset = <PyDelta as PyTryFrom>::try_from(utcoffset.as_ref(py)).unwrap();
+            assert_eq!(
+                (utcoffset.get_days(), utcoffset.get_seconds()),
+                (-1, 19 * 3600)
+            );
+
+            assert_eq!(ob.extract::<DateTime<FixedOffset>>(py).unwrap(), datetime);
+            let in_utc = ob.extract::<DateTime<Utc>>(py).unwrap();
+            assert_eq!(
+                in_utc,
+                Utc.from_utc_datetime(&(local + chrono::Duration::seconds(5 * 3600)))
+            );
+            assert_eq!(
+                in_utc.offset().to_object(py).extract::<Utc>(py).unwrap(),
+                Utc
+            );
+            assert!(ob
+                .extract::<NaiveDateTime>(py)
+                .unwrap_err()
+                .is_instance::<TypeError>(py));
+
+            let naive = PyDateTime::from_naive_datetime(py, &datetime.naive_local()).unwrap();
+            let err = naive.extract::<DateTime<FixedOffset>>().unwrap_err();
+            assert!(err.is_instance::<TypeError>(py));
+        }
+
+        #[test]
+        #[cfg(Py_3_6)]
+        fn fold_selects_the_offset() {
+            use crate::types::IntoPyDict;
+
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let locals = [("datetime", py.import("datetime").unwrap())].into_py_dict(py);
+            // The clocks go back from 03:00 +02:00 to 02:00 +01:00, so 02:30 happens twice
+            py.run(
+                r#"
+class Zone(datetime.tzinfo):
+    def utcoffset(self, dt):
+        hours = 1 if dt.fold or dt.hour >= 3 else 2
+        return datetime.timedelta(hours=hours)
+
+    def dst(self, dt):
+        return None
+
+first = datetime.datetime(2019, 10, 27, 2, 30, tzinfo=Zone())
+second = first.replace(fold=1)
+"#,
+                None,
+                Some(locals),
+            )
+            .unwrap();
+
+            let first: DateTime<FixedOffset> = locals.get_item("first").unwrap().extract().unwrap();
+            let second: DateTime<FixedOffset> =
+                locals.get_item("second").unwrap().extract().unwrap();
+            assert_eq!(first.offset().local_minus_utc(), 2 * 3600);
+            assert_eq!(second.offset().local_minus_utc(), 3600);
+            assert_eq!((second - first).num_seconds(), 3600);
+
+            let zone = locals.get_item("Zone").unwrap().call0().unwrap();
+            assert!(zone.extract::<FixedOffset>().is_err());
+        }
+    }
+}
@@ -1,22 +1,31 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
+use crate::derive_utils::failed_to_extract_item;
 use crate::err::{self, PyErr, PyResult};
+use crate::exceptions::TypeError;
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
+use crate::objectprotocol::ObjectProtocol;
+use crate::types::Unsendable;
 use crate::types::{PyAny, PyList};
 use crate::AsPyPointer;
 #[cfg(not(PyPy))]
 use crate::IntoPyPointer;
 use crate::Python;
 use crate::{ffi, IntoPy};
-use crate::{ToBorrowedObject, ToPyObject};
+use crate::{FromPyObject, PyTryFrom, ToBorrowedObject, ToPyObject};
 use std::{cmp, collections, hash};
 
 /// Represents a Python `dict`.
 #[repr(transparent)]
-pub struct PyDict(PyObject);
+pub struct PyDict(PyObject, Unsendable);
 
-pyobject_native_type!(PyDict, ffi::PyDict_Type, ffi::PyDict_Check);
+pyobject_native_type!(
+    PyDict,
+    layout = ffi::PyDictObject,
+    ffi::PyDict_Type,
+    ffi::PyDict_Check
+);
 
 impl PyDict {
     /// Creates a new empty dictionary.
@@ -241,6 +250,70 @@ where
     }
 }
 
+impl<'source, K, V, S> FromPyObject<'source> for collections::HashMap<K, V, S>
+where
+    K: FromPyObject<'source> + cmp::Eq + hash::Hash,
+    V: FromPyObject<'source>,
+    S: hash::BuildHasher + Default,
+{
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        extract_mapping(ob)
+    }
+}
+
+impl<'source, K, V> FromPyObject<'source> for collections::BTreeMap<K, V>
+where
+    K: FromPyObject<'source> + cmp::Ord,
+    V: FromPyObject<'source>,
+{
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        extract_mapping(ob)
+    }
+}
+
+/// Extracts the items of a `dict`, or of any other mapping with an `items()` method
+fn extract_mapping<'source, K, V, M>(ob: &'source PyAny) -> PyResult<M>
+where
+    K: FromPyObject<'source>,
+    V: FromPyObject<'source>,
+    M: Default + Extend<(K, V)>,
+{
+    let mut map = M::default();
+    let mut insert = |key: &'source PyAny, value: &'source PyAny| -> PyResult<()> {
+        let k = key
+            .extract()
+            .map_err(|err| failed_to_extract_item(key, err, "dict key"))?;
+        let v = value
+            .extract()
+            .map_err(|err| failed_to_extract_item(key, err, "dict value for key"))?;
+        map.extend(Some((k, v)));
+        Ok(())
+    };
+
+    // Subclasses of `dict` may override `items()`
+    if let Ok(dict) = <PyDict as PyTryFrom>::try_from_exact(ob) {
+        for (key, value) in dict {
+            insert(key, value)?;
+        }
+        return Ok(map);
+    }
+
+    let items = match ob.getattr("items") {
+        Ok(items) => items.call0()?,
+        Err(_) => {
+            return Err(TypeError::py_err(format!(
+                "'{}' object is not a mapping",
+                ob.get_type().name()
+            )))
+        }
+    };
+    for item in items.iter()? {
+        let (key, value): (&PyAny, &PyAny) = item?.extract()?;
+        insert(key, value)?;
+    }
+    Ok(map)
+}
+
 /// Conversion trait that allows a sequence of tuples to be converted into `PyDict`
 /// Primary use case for this trait is `call` and `call_method` methods as keywords argument.
 pub trait IntoPyDict {
@@ -674,4 +747,86 @@ mod test {
         assert_eq!(py_map.len(), 3);
         assert_eq!(py_map.get_item("b").unwrap().extract::<i32>().unwrap(), 2);
     }
+
+    fn error_message(py: Python, err: crate::PyErr) -> String {
+        let instance = err.to_object(py);
+        let instance = instance.as_ref(py);
+        instance.str().unwrap().to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_extract_hashmap() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let dict = [("a", 1), ("b", 2)].into_py_dict(py);
+        let map: HashMap<String, i64> = dict.extract().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 2);
+
+        // a custom hasher
+        type Hasher = std::hash::BuildHasherDefault<std::collections::hash_map::DefaultHasher>;
+        let map: HashMap<String, i64, Hasher> = dict.extract().unwrap();
+        assert_eq!(map["b"], 2);
+    }
+
+    #[test]
+    fn test_extract_btreemap() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let dict = py.eval("{3: 'c', 1: 'a', 2: 'b'}", None, None).unwrap();
+        let map: BTreeMap<i32, String> = dict.extract().unwrap();
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(map[&2], "b");
+    }
+
+    #[test]
+    fn test_extract_mapping() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let proxy = py
+            .eval("__import__('types').MappingProxyType({'a': 1})", None, None)
+            .unwrap();
+        let map: HashMap<String, i32> = proxy.extract().unwrap();
+        assert_eq!(map["a"], 1);
+
+        let list = py.eval("[1, 2]", None, None).unwrap();
+        let err = list.extract::<BTreeMap<i32, i32>>().unwrap_err();
+        assert_eq!(error_message(py, err), "'list' object is not a mapping");
+    }
+
+    #[test]
+    fn test_extract_map_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let dict = py.eval("{'a': 1, 'b': 'x'}", None, None).unwrap();
+        let err = dict.extract::<HashMap<String, i32>>().unwrap_err();
+        let message = error_message(py, err);
+        assert!(message.starts_with("failed to extract dict value for key 'b' (TypeError"));
+
+        let dict = py.eval("{1: 1}", None, None).unwrap();
+        let err = dict.extract::<BTreeMap<String, i32>>().unwrap_err();
+        let message = error_message(py, err);
+        assert!(message.starts_with("failed to extract dict key 1 (TypeError"));
+    }
+
+    #[test]
+    fn test_extract_map_error_cause() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let dict = py.eval("{'a': 1 << 70}", None, None).unwrap();
+        let err = dict.extract::<HashMap<String, i32>>().unwrap_err();
+        assert!(err.is_instance::<crate::exceptions::TypeError>(py));
+        let instance = err.to_object(py);
+        let cause = instance.getattr(py, "__cause__").unwrap();
+        assert!(crate::PyErr::from_instance(&cause.as_ref(py))
+            .is_instance::<crate::exceptions::OverflowError>(py));
+        let message = error_message(py, err);
+        assert!(message.starts_with("failed to extract dict value for key 'a' (OverflowError"));
+    }
 }
@@ -8,6 +8,7 @@ use crate::instance::PyNativeType;
 use crate::object::PyObject;
 use crate::objectprotocol::ObjectProtocol;
 use crate::types::PyAny;
+use crate::types::Unsendable;
 use crate::FromPyObject;
 use crate::PyResult;
 use crate::Python;
@@ -22,9 +23,14 @@ use std::os::raw::c_double;
 /// and [extract](struct.PyObject.html#method.extract)
 /// with `f32`/`f64`.
 #[repr(transparent)]
-pub struct PyFloat(PyObject);
-
-pyobject_native_type!(PyFloat, ffi::PyFloat_Type, ffi::PyFloat_Check);
+pub struct PyFloat(PyObject, Unsendable);
+
+pyobject_native_type!(
+    PyFloat,
+    layout = ffi::PyFloatObject,
+    ffi::PyFloat_Type,
+    ffi::PyFloat_Check
+);
 
 impl PyFloat {
     /// Creates a new Python `float` object.
@@ -7,6 +7,7 @@ use crate::ffi::{self, Py_ssize_t};
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
 use crate::types::PyAny;
+use crate::types::Unsendable;
 use crate::IntoPyPointer;
 use crate::Python;
 use crate::{AsPyPointer, IntoPy};
@@ -14,9 +15,14 @@ use crate::{ToBorrowedObject, ToPyObject};
 
 /// Represents a Python `list`.
 #[repr(transparent)]
-pub struct PyList(PyObject);
+pub struct PyList(PyObject, Unsendable);
 
-pyobject_native_type!(PyList, ffi::PyList_Type, ffi::PyList_Check);
+pyobject_native_type!(
+    PyList,
+    layout = ffi::PyListObject,
+    ffi::PyList_Type,
+    ffi::PyList_Check
+);
 
 impl PyList {
     /// Construct a new list with the given elements.
@@ -202,13 +208,43 @@ where
     }
 }
 
+macro_rules! array_impls {
+    ($($N:expr),+) => {
+        $(
+            impl<T> ToPyObject for [T; $N]
+            where
+                T: ToPyObject,
+            {
+                fn to_object(&self, py: Python<'_>) -> PyObject {
+                    self[..].to_object(py)
+                }
+            }
+
+            impl<T> IntoPy<PyObject> for [T; $N]
+            where
+                T: IntoPy<PyObject>,
+            {
+                fn into_py(self, py: Python) -> PyObject {
+                    let elements: Box<[T]> = Box::new(self);
+                    elements.into_vec().into_py(py)
+                }
+            }
+        )+
+    };
+}
+
+array_impls!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32
+);
+
 #[cfg(test)]
 mod test {
     use crate::instance::AsPyRef;
     use crate::objectprotocol::ObjectProtocol;
     use crate::types::PyList;
     use crate::Python;
-    use crate::{PyTryFrom, ToPyObject};
+    use crate::{IntoPy, PyObject, PyTryFrom, ToPyObject};
 
     #[test]
     fn test_new() {
@@ -422,4 +458,24 @@ mod test {
         assert_eq!(3, list.get_item(2).extract::<i32>().unwrap());
         assert_eq!(2, list.get_item(3).extract::<i32>().unwrap());
     }
+
+    #[test]
+    fn test_array_to_list() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ob = [1.0f32, 0.5, 0.25].to_object(py);
+        let list = <PyList as PyTryFrom>::try_from(ob.as_ref(py)).unwrap();
+        assert_eq!(3, list.len());
+        assert_eq!(0.5, list.get_item(1).extract::<f32>().unwrap());
+
+        let matrix: PyObject = [[1, 0], [0, 1]].into_py(py);
+        let rows = <PyList as PyTryFrom>::try_from(matrix.as_ref(py)).unwrap();
+        assert_eq!(vec![0, 1], rows.get_item(1).extract::<Vec<i32>>().unwrap());
+
+        let empty: [String; 0] = [];
+        let ob = empty.to_object(py);
+        assert!(<PyList as PyTryFrom>::try_from(ob.as_ref(py))
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -24,6 +24,25 @@ pub use self::string::{PyBytes, PyString, PyString as PyUnicode};
 pub use self::tuple::PyTuple;
 pub use self::typeobject::PyType;
 
+/// Marker field of the native types, which makes them `!Send`, `!Sync` and `!Ungil`, so that
+/// references to them can't be used while the GIL is released.
+///
+/// Native types defined outside of pyo3 with
+/// [pyobject_native_type](crate::pyobject_native_type) need to contain it as well, otherwise
+/// their references can be passed to [Python::allow_threads](crate::Python::allow_threads):
+///
+/// ```rust,ignore
+/// pub struct PyMyType(PyObject, pyo3::types::Unsendable);
+/// pyo3::pyobject_native_type!(PyMyType, ffi::PyMyType_Type, Some("mymodule"), ffi::PyMyType_Check);
+/// ```
+///
+/// Native types are never constructed, they are only cast from pointers to Python objects, so
+/// `Unsendable` has no constructor.
+pub struct Unsendable(std::marker::PhantomData<std::rc::Rc<()>>);
+
+#[cfg(feature = "nightly")]
+impl !crate::Ungil for Unsendable {}
+
 /// Implements a typesafe conversions throught [FromPyObject], given a typecheck function as second
 /// parameter
 #[macro_export]
@@ -77,11 +96,18 @@ macro_rules! pyobject_native_type_named (
     };
 );
 
+/// Implements the python object traits for a native type.
+///
+/// With `layout = ...`, the instances are stored as the given struct, which allows using the type
+/// as the base of a `#[pyclass(extends = ...)]`. Otherwise the layout is unknown or variable sized
+/// and the type can't be extended.
+///
+/// The type must be a tuple struct of a `PyObject` and an [Unsendable](crate::types::Unsendable).
 #[macro_export]
 macro_rules! pyobject_native_type (
-    ($name: ty, $typeobject: expr, $module: expr, $checkfunction: path $(,$type_param: ident)*) => {
+    ($name: ty, layout = $layout: path, $typeobject: expr, $module: expr, $checkfunction: path $(,$type_param: ident)*) => {
         pyobject_native_type_named!($name $(,$type_param)*);
-        pyobject_native_type_convert!($name, $typeobject, $module, $checkfunction $(,$type_param)*);
+        pyobject_native_type_convert!($name, layout = $layout, $typeobject, $module, $checkfunction $(,$type_param)*);
 
         impl<'a, $($type_param,)*> ::std::convert::From<&'a $name> for &'a $crate::types::PyAny {
             fn from(ob: &'a $name) -> Self {
@@ -89,6 +115,12 @@ macro_rules! pyobject_native_type (
             }
         }
     };
+    ($name: ty, layout = $layout: path, $typeobject: expr, $checkfunction: path $(,$type_param: ident)*) => {
+        pyobject_native_type!{$name, layout = $layout, $typeobject, Some("builtins"), $checkfunction $(,$type_param)*}
+    };
+    ($name: ty, $typeobject: expr, $module: expr, $checkfunction: path $(,$type_param: ident)*) => {
+        pyobject_native_type!{$name, layout = $crate::ffi::PyObject, $typeobject, $module, $checkfunction $(,$type_param)*}
+    };
     ($name: ty, $typeobject: expr, $checkfunction: path $(,$type_param: ident)*) => {
         pyobject_native_type!{$name, $typeobject, Some("builtins"), $checkfunction $(,$type_param)*}
     };
@@ -96,14 +128,14 @@ macro_rules! pyobject_native_type (
 
 #[macro_export]
 macro_rules! pyobject_native_type_convert(
-    ($name: ty, $typeobject: expr, $module: expr, $checkfunction: path $(,$type_param: ident)*) => {
+    ($name: ty, layout = $layout: path, $typeobject: expr, $module: expr, $checkfunction: path $(,$type_param: ident)*) => {
         impl<$($type_param,)*> $crate::type_object::PyTypeInfo for $name {
             type Type = ();
             type BaseType = $crate::types::PyAny;
 
             const NAME: &'static str = stringify!($name);
             const MODULE: Option<&'static str> = $module;
-            const SIZE: usize = ::std::mem::size_of::<$crate::ffi::PyObject>();
+            const SIZE: usize = ::std::mem::size_of::<$layout>();
             const OFFSET: isize = 0;
 
             #[inline]
@@ -158,6 +190,12 @@ macro_rules! pyobject_native_type_convert(
             }
         }
     };
+    ($name: ty, layout = $layout: path, $typeobject: expr, $checkfunction: path $(,$type_param: ident)*) => {
+        pyobject_native_type_convert!{$name, layout = $layout, $typeobject, Some("builtins"), $checkfunction $(,$type_param)*}
+    };
+    ($name: ty, $typeobject: expr, $module: expr, $checkfunction: path $(,$type_param: ident)*) => {
+        pyobject_native_type_convert!{$name, layout = $crate::ffi::PyObject, $typeobject, $module, $checkfunction $(,$type_param)*}
+    };
     ($name: ty, $typeobject: expr, $checkfunction: path $(,$type_param: ident)*) => {
         pyobject_native_type_convert!{$name, $typeobject, Some("builtins"), $checkfunction $(,$type_param)*}
     };
@@ -174,6 +212,7 @@ mod iterator;
 mod list;
 mod module;
 mod num;
+mod path;
 mod sequence;
 mod set;
 mod slice;
@@ -2,28 +2,31 @@
 //
 // based on Daniel Grunwald's https://github.com/dgrunwald/rust-cpython
 
+use crate::derive_utils::{free_module_state, IntoPyResult, ModuleState};
 use crate::err::{PyErr, PyResult};
 use crate::exceptions;
 use crate::ffi;
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
 use crate::objectprotocol::ObjectProtocol;
-use crate::type_object::PyTypeCreate;
 use crate::type_object::PyTypeObject;
+use crate::type_object::{check_type_interpreter, PyTypeCreate};
 use crate::types::PyTuple;
+use crate::types::Unsendable;
 use crate::types::{PyAny, PyDict, PyList};
 use crate::AsPyPointer;
 use crate::IntoPy;
 use crate::Py;
 use crate::Python;
 use crate::ToPyObject;
+use std::any::Any;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::str;
 
 /// Represents a Python `module` object.
 #[repr(transparent)]
-pub struct PyModule(PyObject);
+pub struct PyModule(PyObject, Unsendable);
 
 pyobject_native_type!(PyModule, ffi::PyModule_Type, ffi::PyModule_Check);
 
@@ -152,6 +155,61 @@ impl PyModule {
         self.getattr(name)
     }
 
+    /// The slot of the per-module state, if the module was created by `#[pymodule(multi_phase)]`
+    fn state_slot(&self) -> Option<*mut ModuleState> {
+        unsafe {
+            let def = ffi::PyModule_GetDef(self.as_ptr());
+            if def.is_null() || (*def).m_free != Some(free_module_state) {
+                return None;
+            }
+            let state = ffi::PyModule_GetState(self.as_ptr()) as *mut ModuleState;
+            if state.is_null() {
+                None
+            } else {
+                Some(state)
+            }
+        }
+    }
+
+    /// Sets the per-module state of a module created by `#[pymodule(multi_phase)]`, usually in
+    /// its initializer.
+    ///
+    /// The state lives as long as the module and can only be set once. Every instance of
+    /// the module, e.g. in every sub-interpreter, has its own state.
+    ///
+    /// The garbage collector can't see the python objects in the state, so a reference cycle
+    /// through the state, e.g. a state holding a function of the module, is never collected.
+    pub fn set_state<T: 'static>(&self, state: T) -> PyResult<()> {
+        let slot = self.state_slot().ok_or_else(|| {
+            exceptions::RuntimeError::py_err(
+                "Only modules with multi-phase initialization have a per-module state",
+            )
+        })?;
+        unsafe {
+            if !(*slot).is_null() {
+                return Err(exceptions::RuntimeError::py_err(
+                    "The module state is already set",
+                ));
+            }
+            let state: Box<dyn Any> = Box::new(state);
+            *slot = Box::into_raw(Box::new(state));
+        }
+        Ok(())
+    }
+
+    /// Returns the per-module state set with [set_state](PyModule::set_state), or `None` if
+    /// the module has no state of type `T`.
+    pub fn state<T: 'static>(&self) -> Option<&T> {
+        let slot = self.state_slot()?;
+        unsafe {
+            if (*slot).is_null() {
+                None
+            } else {
+                (**slot).downcast_ref()
+            }
+        }
+    }
+
     /// Adds a member to the module.
     ///
     /// This is a convenience function which can be used from the module's initialization function.
@@ -170,11 +228,15 @@ impl PyModule {
     /// This is a convenience function that initializes the `class`,
     /// sets `new_type.__module__` to this module's name,
     /// and adds the type to this module.
+    ///
+    /// Fails with an `ImportError` if the class was already initialized in another interpreter.
     pub fn add_class<T>(&self) -> PyResult<()>
     where
         T: PyTypeCreate,
     {
-        self.add(T::NAME, <T as PyTypeObject>::type_object())
+        let type_object = <T as PyTypeObject>::type_object();
+        check_type_interpreter::<T>()?;
+        self.add(T::NAME, type_object)
     }
 
     /// Adds a function or a (sub)module to a module, using the functions __name__ as name.
@@ -192,8 +254,11 @@ impl PyModule {
     /// ```rust,ignore
     /// m.add("also_double", wrap_pyfunction!(double)(py));
     /// ```
-    pub fn add_wrapped(&self, wrapper: &impl Fn(Python) -> PyObject) -> PyResult<()> {
-        let function = wrapper(self.py());
+    pub fn add_wrapped<R>(&self, wrapper: &impl Fn(Python) -> R) -> PyResult<()>
+    where
+        R: IntoPyResult<PyObject>,
+    {
+        let function = wrapper(self.py()).into_py_result()?;
         let name = function
             .getattr(self.py(), "__name__")
             .expect("A function or module must have a __name__");
@@ -8,6 +8,7 @@ use crate::ffi;
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
 use crate::types::PyAny;
+use crate::types::Unsendable;
 use crate::AsPyPointer;
 use crate::IntoPy;
 use crate::Python;
@@ -122,7 +123,7 @@ pub(super) const IS_LITTLE_ENDIAN: c_int = 0;
 /// and [extract](struct.PyObject.html#method.extract)
 /// with the primitive Rust integer types.
 #[repr(transparent)]
-pub struct PyLong(PyObject);
+pub struct PyLong(PyObject, Unsendable);
 
 pyobject_native_type!(
     PyLong,
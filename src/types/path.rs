@@ -0,0 +1,261 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! Conversions of file system paths and OS strings
+//!
+//! `OsStr` and `Path` are converted to `str` with the file system encoding and the
+//! `surrogateescape` error handler, so that names which are not valid unicode survive a round
+//! trip. A `str` is accepted everywhere python expects a path. Use
+//! [PathlibPath](crate::PathlibPath) to convert a path to a `pathlib.Path` instead.
+//!
+//! The extraction accepts everything `os.fspath()` does: `str`, `bytes` and objects that
+//! implement `__fspath__`, like `pathlib.Path`.
+
+use crate::err::PyResult;
+use crate::ffi::{self, Py_ssize_t};
+use crate::instance::PyNativeType;
+use crate::object::PyObject;
+use crate::types::PyAny;
+use crate::{AsPyPointer, FromPyObject, IntoPy, Python, ToPyObject};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Converts an `OsStr` to a python `str`, like `os.fsdecode()`.
+impl ToPyObject for OsStr {
+    fn to_object(&self, py: Python) -> PyObject {
+        // Most strings are valid unicode, which doesn't need the file system encoding
+        if let Some(s) = self.to_str() {
+            return s.to_object(py);
+        }
+
+        #[cfg(unix)]
+        unsafe {
+            use std::os::unix::ffi::OsStrExt;
+            let bytes = self.as_bytes();
+            PyObject::from_owned_ptr_or_panic(
+                py,
+                ffi::PyUnicode_DecodeFSDefaultAndSize(
+                    bytes.as_ptr() as *const _,
+                    bytes.len() as Py_ssize_t,
+                ),
+            )
+        }
+
+        #[cfg(windows)]
+        unsafe {
+            use std::os::windows::ffi::OsStrExt;
+            let wide: Vec<u16> = self.encode_wide().collect();
+            PyObject::from_owned_ptr_or_panic(
+                py,
+                ffi::PyUnicode_FromWideChar(wide.as_ptr(), wide.len() as Py_ssize_t),
+            )
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        self.to_string_lossy().to_object(py)
+    }
+}
+
+impl<'a> IntoPy<PyObject> for &'a OsStr {
+    #[inline]
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+}
+
+impl ToPyObject for OsString {
+    #[inline]
+    fn to_object(&self, py: Python) -> PyObject {
+        self.as_os_str().to_object(py)
+    }
+}
+
+impl IntoPy<PyObject> for OsString {
+    #[inline]
+    fn into_py(self, py: Python) -> PyObject {
+        self.as_os_str().to_object(py)
+    }
+}
+
+impl ToPyObject for Path {
+    #[inline]
+    fn to_object(&self, py: Python) -> PyObject {
+        self.as_os_str().to_object(py)
+    }
+}
+
+impl<'a> IntoPy<PyObject> for &'a Path {
+    #[inline]
+    fn into_py(self, py: Python) -> PyObject {
+        self.as_os_str().to_object(py)
+    }
+}
+
+impl ToPyObject for PathBuf {
+    #[inline]
+    fn to_object(&self, py: Python) -> PyObject {
+        self.as_os_str().to_object(py)
+    }
+}
+
+impl IntoPy<PyObject> for PathBuf {
+    #[inline]
+    fn into_py(self, py: Python) -> PyObject {
+        self.as_os_str().to_object(py)
+    }
+}
+
+/// Returns the `str` or `bytes` representation of a path, like `os.fspath()`
+#[cfg(Py_3_6)]
+fn fspath(ob: &PyAny) -> PyResult<&PyAny> {
+    unsafe { ob.py().from_owned_ptr_or_err(ffi::PyOS_FSPath(ob.as_ptr())) }
+}
+
+/// Returns the `str` or `bytes` representation of a path. Python 3.5 has no `__fspath__`, so
+/// only these two types are accepted.
+#[cfg(not(Py_3_6))]
+fn fspath(ob: &PyAny) -> PyResult<&PyAny> {
+    use crate::exceptions::TypeError;
+    use crate::ObjectProtocol;
+
+    unsafe {
+        if ffi::PyUnicode_Check(ob.as_ptr()) != 0 || ffi::PyBytes_Check(ob.as_ptr()) != 0 {
+            Ok(ob)
+        } else {
+            Err(TypeError::py_err(format!(
+                "expected str or bytes object, not {}",
+                ob.get_type().name()
+            )))
+        }
+    }
+}
+
+/// Extracts an `OsString` from `str`, `bytes` or a path-like object, like `os.fsencode()`.
+impl<'source> FromPyObject<'source> for OsString {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let py = ob.py();
+        let path = fspath(ob)?;
+
+        #[cfg(unix)]
+        unsafe {
+            use crate::types::PyBytes;
+            use std::os::unix::ffi::OsStrExt;
+
+            let bytes: &PyBytes = if ffi::PyUnicode_Check(path.as_ptr()) != 0 {
+                py.from_owned_ptr_or_err(ffi::PyUnicode_EncodeFSDefault(path.as_ptr()))?
+            } else {
+                path.downcast_ref()?
+            };
+            Ok(OsStr::from_bytes(bytes.as_bytes()).to_os_string())
+        }
+
+        #[cfg(windows)]
+        unsafe {
+            use std::os::windows::ffi::OsStringExt;
+
+            let string: &PyAny = if ffi::PyUnicode_Check(path.as_ptr()) != 0 {
+                path
+            } else {
+                py.from_owned_ptr_or_err(ffi::PyUnicode_DecodeFSDefaultAndSize(
+                    ffi::PyBytes_AsString(path.as_ptr()),
+                    ffi::PyBytes_Size(path.as_ptr()),
+                ))?
+            };
+            let mut size: Py_ssize_t = 0;
+            let wide = ffi::PyUnicode_AsWideCharString(string.as_ptr(), &mut size);
+            if wide.is_null() {
+                return Err(crate::PyErr::fetch(py));
+            }
+            let os_string = OsString::from_wide(std::slice::from_raw_parts(wide, size as usize));
+            ffi::PyMem_Free(wide as *mut _);
+            Ok(os_string)
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = py;
+            path.extract::<String>().map(OsString::from)
+        }
+    }
+}
+
+/// Extracts a `PathBuf` from `str`, `bytes` or a path-like object, like `os.fsencode()`.
+impl<'source> FromPyObject<'source> for PathBuf {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        OsString::extract(ob).map(PathBuf::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exceptions::TypeError;
+    use crate::instance::AsPyRef;
+    use crate::objectprotocol::ObjectProtocol;
+    use crate::types::PyString;
+    use crate::{IntoPy, PyObject, PyTryFrom, Python, ToPyObject};
+    use std::ffi::{OsStr, OsString};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_path_to_str() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ob = Path::new("dir/file.txt").to_object(py);
+        let s = <PyString as PyTryFrom>::try_from(ob.as_ref(py)).unwrap();
+        assert_eq!(s.to_string().unwrap(), "dir/file.txt");
+
+        let ob: PyObject = OsString::from("name").into_py(py);
+        assert_eq!(ob.extract::<String>(py).unwrap(), "name");
+    }
+
+    #[test]
+    fn test_extract_path() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for code in &["'dir/file.txt'", "b'dir/file.txt'"] {
+            let ob = py.eval(code, None, None).unwrap();
+            assert_eq!(
+                ob.extract::<PathBuf>().unwrap(),
+                PathBuf::from("dir/file.txt")
+            );
+        }
+        let ob = py.eval("42", None, None).unwrap();
+        assert!(ob
+            .extract::<OsString>()
+            .unwrap_err()
+            .is_instance::<TypeError>(py));
+    }
+
+    #[test]
+    #[cfg(Py_3_6)]
+    fn test_extract_path_like() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ob = py
+            .eval(
+                "__import__('pathlib').PurePosixPath('dir', 'file.txt')",
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            ob.extract::<PathBuf>().unwrap(),
+            PathBuf::from("dir/file.txt")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_unicode_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let name = OsStr::from_bytes(b"file\xff.txt");
+        let ob = name.to_object(py);
+        assert!(<PyString as PyTryFrom>::try_from(ob.as_ref(py)).is_ok());
+        assert_eq!(ob.extract::<OsString>(py).unwrap(), name);
+
+        let ob = py.eval("b'file\\xff.txt'", None, None).unwrap();
+        assert_eq!(ob.extract::<OsString>().unwrap(), name);
+    }
+}
@@ -2,17 +2,19 @@
 
 use crate::buffer;
 use crate::err::{self, PyDowncastError, PyErr, PyResult};
+use crate::exceptions;
 use crate::ffi::{self, Py_ssize_t};
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
 use crate::objectprotocol::ObjectProtocol;
+use crate::types::Unsendable;
 use crate::types::{PyAny, PyList, PyTuple};
 use crate::AsPyPointer;
 use crate::{FromPyObject, PyTryFrom, ToBorrowedObject};
 
 /// Represents a reference to a python object supporting the sequence protocol.
 #[repr(transparent)]
-pub struct PySequence(PyObject);
+pub struct PySequence(PyObject, Unsendable);
 pyobject_native_type_named!(PySequence);
 
 impl PySequence {
@@ -255,20 +257,28 @@ where
 {
     fn extract(obj: &'source PyAny) -> PyResult<Self> {
         // first try buffer protocol
-        if let Ok(buf) = buffer::PyBuffer::get(obj.py(), obj) {
-            if buf.dimensions() == 1 {
-                if let Ok(v) = buf.to_vec::<T>(obj.py()) {
-                    buf.release(obj.py());
-                    return Ok(v);
-                }
-            }
-            buf.release(obj.py());
+        if let Some(v) = extract_buffer(obj) {
+            return Ok(v);
         }
         // fall back to sequence protocol
         extract_sequence(obj)
     }
 }
 
+fn extract_buffer<T>(obj: &PyAny) -> Option<Vec<T>>
+where
+    T: buffer::Element + Copy,
+{
+    let buf = buffer::PyBuffer::get(obj.py(), obj).ok()?;
+    let v = if buf.dimensions() == 1 {
+        buf.to_vec::<T>(obj.py()).ok()
+    } else {
+        None
+    };
+    buf.release(obj.py());
+    v
+}
+
 fn extract_sequence<'s, T>(obj: &'s PyAny) -> PyResult<Vec<T>>
 where
     T: FromPyObject<'s>,
@@ -281,6 +291,70 @@ where
     Ok(v)
 }
 
+/// Extracts a sequence for an array, checking the length before extracting the elements
+fn extract_sequence_of_length<'s, T>(obj: &'s PyAny, length: usize) -> PyResult<Box<[T]>>
+where
+    T: FromPyObject<'s>,
+{
+    let seq = <PySequence as PyTryFrom>::try_from(obj)?;
+    check_length(seq.len()? as usize, length)?;
+    into_boxed_slice_of_length(extract_sequence(obj)?, length)
+}
+
+fn into_boxed_slice_of_length<T>(v: Vec<T>, length: usize) -> PyResult<Box<[T]>> {
+    // The length can change while a sequence is iterated
+    check_length(v.len(), length)?;
+    Ok(v.into_boxed_slice())
+}
+
+fn check_length(actual: usize, expected: usize) -> PyResult<()> {
+    if actual == expected {
+        Ok(())
+    } else {
+        let msg = format!(
+            "Expected a sequence of length {}, but got a sequence of length {}.",
+            expected, actual
+        );
+        Err(exceptions::ValueError::py_err(msg))
+    }
+}
+
+macro_rules! array_impls {
+    ($($N:expr),+) => {
+        $(
+            impl<'a, T> FromPyObject<'a> for [T; $N]
+            where
+                T: FromPyObject<'a>,
+            {
+                default fn extract(obj: &'a PyAny) -> PyResult<Self> {
+                    let elements: Box<[T]> = extract_sequence_of_length(obj, $N)?;
+                    // Safe because the length of the slice is checked
+                    Ok(*unsafe { Box::from_raw(Box::into_raw(elements) as *mut [T; $N]) })
+                }
+            }
+
+            impl<'source, T> FromPyObject<'source> for [T; $N]
+            where
+                for<'a> T: FromPyObject<'a> + buffer::Element + Copy,
+            {
+                fn extract(obj: &'source PyAny) -> PyResult<Self> {
+                    let elements: Box<[T]> = match extract_buffer(obj) {
+                        Some(v) => into_boxed_slice_of_length(v, $N)?,
+                        None => extract_sequence_of_length(obj, $N)?,
+                    };
+                    // Safe because the length of the slice is checked
+                    Ok(*unsafe { Box::from_raw(Box::into_raw(elements) as *mut [T; $N]) })
+                }
+            }
+        )+
+    };
+}
+
+array_impls!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32
+);
+
 impl<'v> PyTryFrom<'v> for PySequence {
     fn try_from<V: Into<&'v PyAny>>(value: V) -> Result<&'v PySequence, PyDowncastError> {
         let value = value.into();
@@ -329,6 +403,7 @@ impl<'v> PyTryFrom<'v> for PySequence {
 
 #[cfg(test)]
 mod test {
+    use crate::exceptions;
     use crate::instance::AsPyRef;
     use crate::object::PyObject;
     use crate::objectprotocol::ObjectProtocol;
@@ -651,6 +726,75 @@ mod test {
         assert!(v == b"abc");
     }
 
+    #[test]
+    fn test_extract_sequence_to_array() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v: [i32; 3] = py.eval("(1, 2, 3)", None, None).unwrap().extract().unwrap();
+        assert_eq!(v, [1, 2, 3]);
+        let v: [String; 2] = py
+            .eval("['a', 'b']", None, None)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(v, ["a".to_string(), "b".to_string()]);
+        let v: [[f64; 2]; 2] = py
+            .eval("[[1.0, 0.0], (0.0, 1.0)]", None, None)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(v, [[1.0, 0.0], [0.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_extract_buffer_to_array() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v: [u8; 3] = py
+            .eval("bytearray(b'abc')", None, None)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(&v, b"abc");
+        let v: [f32; 4] = py
+            .eval("__import__('array').array('f', [1, 2, 3, 4])", None, None)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(v, [1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_extract_array_wrong_length() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = py
+            .eval("[1, 2]", None, None)
+            .unwrap()
+            .extract::<[i32; 3]>()
+            .unwrap_err();
+        assert!(err.is_instance::<exceptions::ValueError>(py));
+        let err = err.to_object(py);
+        let err = err.as_ref(py);
+        assert_eq!(
+            err.str().unwrap().to_string_lossy(),
+            "Expected a sequence of length 3, but got a sequence of length 2."
+        );
+
+        let err = py
+            .eval("bytearray(b'abcd')", None, None)
+            .unwrap()
+            .extract::<[u8; 3]>()
+            .unwrap_err();
+        assert!(err.is_instance::<exceptions::ValueError>(py));
+        let err = py
+            .eval("None", None, None)
+            .unwrap()
+            .extract::<[u8; 3]>()
+            .unwrap_err();
+        assert!(err.is_instance::<exceptions::TypeError>(py));
+    }
+
     #[test]
     fn test_seq_try_from_unchecked() {
         let gil = Python::acquire_gil();
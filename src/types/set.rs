@@ -1,23 +1,26 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 //
 
+use crate::derive_utils::failed_to_extract_item;
 use crate::err::{self, PyErr, PyResult};
 use crate::ffi;
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
+use crate::objectprotocol::ObjectProtocol;
+use crate::types::{PyAny, Unsendable};
 use crate::AsPyPointer;
 use crate::Python;
-use crate::{ToBorrowedObject, ToPyObject};
+use crate::{FromPyObject, ToBorrowedObject, ToPyObject};
 use std::ptr;
-use std::{collections, hash};
+use std::{cmp, collections, hash};
 
 /// Represents a Python `set`
 #[repr(transparent)]
-pub struct PySet(PyObject);
+pub struct PySet(PyObject, Unsendable);
 
 /// Represents a  Python `frozenset`
 #[repr(transparent)]
-pub struct PyFrozenSet(PyObject);
+pub struct PyFrozenSet(PyObject, Unsendable);
 
 pyobject_native_type!(PySet, ffi::PySet_Type, Some("builtins"), ffi::PySet_Check);
 pyobject_native_type!(PyFrozenSet, ffi::PyFrozenSet_Type, ffi::PyFrozenSet_Check);
@@ -125,6 +128,42 @@ where
     }
 }
 
+impl<'source, K, S> FromPyObject<'source> for collections::HashSet<K, S>
+where
+    K: FromPyObject<'source> + cmp::Eq + hash::Hash,
+    S: hash::BuildHasher + Default,
+{
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        extract_set(ob)
+    }
+}
+
+impl<'source, K> FromPyObject<'source> for collections::BTreeSet<K>
+where
+    K: FromPyObject<'source> + cmp::Ord,
+{
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        extract_set(ob)
+    }
+}
+
+/// Extracts the elements of a `set`, a `frozenset` or any other iterable
+fn extract_set<'source, K, C>(ob: &'source PyAny) -> PyResult<C>
+where
+    K: FromPyObject<'source>,
+    C: Default + Extend<K>,
+{
+    let mut set = C::default();
+    for element in ob.iter()? {
+        let element = element?;
+        let k = element
+            .extract()
+            .map_err(|err| failed_to_extract_item(element, err, "set element"))?;
+        set.extend(Some(k));
+    }
+    Ok(set)
+}
+
 impl PyFrozenSet {
     /// Creates a new frozenset.
     ///
@@ -174,7 +213,7 @@ mod test {
     use crate::objectprotocol::ObjectProtocol;
     use crate::Python;
     use crate::{PyTryFrom, ToPyObject};
-    use std::collections::HashSet;
+    use std::collections::{BTreeSet, HashSet};
 
     #[test]
     fn test_set_new() {
@@ -309,4 +348,39 @@ mod test {
             assert_eq!(1i32, el.unwrap().extract::<i32>().unwrap());
         }
     }
+
+    #[test]
+    fn test_extract_hashset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let set = PySet::new(py, &[1, 2, 3]).unwrap();
+        let hash_set: HashSet<i32> = set.extract().unwrap();
+        assert_eq!(hash_set, [1, 2, 3].iter().copied().collect());
+
+        let frozenset = PyFrozenSet::new(py, &["a"]).unwrap();
+        let hash_set: HashSet<String> = frozenset.extract().unwrap();
+        assert!(hash_set.contains("a"));
+    }
+
+    #[test]
+    fn test_extract_btreeset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        // any iterable
+        let list = py.eval("[3, 1, 2, 1]", None, None).unwrap();
+        let set: BTreeSet<u8> = list.extract().unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        let err = py
+            .eval("{1, 'a'}", None, None)
+            .unwrap()
+            .extract::<BTreeSet<u8>>()
+            .unwrap_err();
+        let instance = err.to_object(py);
+        let instance = instance.as_ref(py);
+        let message = instance.str().unwrap().to_string_lossy();
+        assert!(message.starts_with("failed to extract set element 'a' (TypeError"));
+    }
 }
@@ -6,6 +6,7 @@ use crate::err::{PyErr, PyResult};
 use crate::instance::PyNativeType;
 use crate::object::PyObject;
 use crate::types::PyAny;
+use crate::types::Unsendable;
 use crate::AsPyPointer;
 use crate::Python;
 use crate::{exceptions, IntoPy};
@@ -20,7 +21,7 @@ use std::str;
 ///
 /// This type is immutable
 #[repr(transparent)]
-pub struct PyString(PyObject);
+pub struct PyString(PyObject, Unsendable);
 
 pyobject_native_type!(PyString, ffi::PyUnicode_Type, ffi::PyUnicode_Check);
 
@@ -28,7 +29,7 @@ pyobject_native_type!(PyString, ffi::PyUnicode_Type, ffi::PyUnicode_Check);
 ///
 /// This type is immutable
 #[repr(transparent)]
-pub struct PyBytes(PyObject);
+pub struct PyBytes(PyObject, Unsendable);
 
 pyobject_native_type!(
     PyBytes,
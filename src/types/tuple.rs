@@ -7,6 +7,7 @@ use crate::ffi::{self, Py_ssize_t};
 use crate::instance::{AsPyRef, Py, PyNativeType};
 use crate::object::PyObject;
 use crate::types::PyAny;
+use crate::types::Unsendable;
 use crate::AsPyPointer;
 use crate::IntoPyPointer;
 use crate::Python;
@@ -15,7 +16,7 @@ use std::slice;
 
 /// Represents a Python `tuple` object.
 #[repr(transparent)]
-pub struct PyTuple(PyObject);
+pub struct PyTuple(PyObject, Unsendable);
 
 pyobject_native_type!(PyTuple, ffi::PyTuple_Type, ffi::PyTuple_Check);
 
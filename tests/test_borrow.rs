@@ -0,0 +1,247 @@
+use pyo3::exceptions::RuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyAny};
+use pyo3::{py_run, wrap_pyfunction, AsPyPointer, PyBorrowError};
+
+mod common;
+
+#[pyclass]
+#[derive(Debug)]
+struct Counter {
+    count: u32,
+}
+
+#[pymethods]
+impl Counter {
+    fn increment(&mut self) {
+        self.count += 1;
+    }
+
+    fn get(&self) -> u32 {
+        self.count
+    }
+
+    fn call(&self, py: Python, callback: PyObject) -> PyResult<PyObject> {
+        callback.call0(py)
+    }
+
+    fn call_mut(&mut self, py: Python, callback: PyObject) -> PyResult<PyObject> {
+        self.count += 1;
+        callback.call0(py)
+    }
+
+    #[getter]
+    fn value(&self) -> u32 {
+        self.count
+    }
+
+    #[setter]
+    fn set_value(&mut self, value: u32) {
+        self.count = value;
+    }
+}
+
+#[test]
+fn shared_borrows_can_nest() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(py, Counter { count: 3 }).unwrap();
+    py_assert!(py, c, "c.call(c.get) == 3");
+    py_assert!(py, c, "c.call(lambda: c.value) == 3");
+    py_assert!(py, c, "c.call(lambda: c.call(c.get)) == 3");
+}
+
+#[test]
+fn mutable_borrow_during_shared_borrow() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(py, Counter { count: 0 }).unwrap();
+    py_expect_exception!(py, c, "c.call(c.increment)", RuntimeError);
+    py_expect_exception!(
+        py,
+        c,
+        "c.call(lambda: setattr(c, 'value', 5))",
+        RuntimeError
+    );
+    py_assert!(py, c, "c.get() == 0");
+}
+
+#[test]
+fn shared_borrow_during_mutable_borrow() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(py, Counter { count: 0 }).unwrap();
+    py_expect_exception!(py, c, "c.call_mut(c.get)", RuntimeError);
+    py_expect_exception!(py, c, "c.call_mut(lambda: c.value)", RuntimeError);
+    py_expect_exception!(py, c, "c.call_mut(c.increment)", RuntimeError);
+    // the borrows are released when the methods fail
+    py_run!(py, c, "c.increment(); assert c.value == 4");
+}
+
+#[test]
+fn try_as_ref_and_try_as_mut() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let mut c = Py::new(py, Counter { count: 0 }).unwrap();
+    let mut other = c.clone_ref(py);
+
+    let shared = c.as_ref(py);
+    assert!(other.try_as_ref(py).is_ok());
+    assert_eq!(
+        other.try_as_mut(py).unwrap_err().to_string(),
+        "Already borrowed"
+    );
+    drop(shared);
+
+    let mut exclusive = other.try_as_mut(py).unwrap();
+    exclusive.count = 7;
+    assert!(c.try_as_ref(py).is_err());
+    assert!(c.try_as_mut(py).is_err());
+    drop(exclusive);
+
+    assert_eq!(c.try_as_ref(py).unwrap().count, 7);
+}
+
+#[test]
+fn borrow_error_is_runtime_error() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let mut c = Py::new(py, Counter { count: 0 }).unwrap();
+    let other = c.clone_ref(py);
+    let shared = other.as_ref(py);
+
+    let err: PyBorrowError = c.try_as_mut(py).unwrap_err();
+    assert_eq!(err.to_string(), "Already borrowed");
+    assert_eq!(other.try_as_ref(py).map(|r| r.count), Ok(0));
+    assert!(PyErr::from(err).is_instance::<RuntimeError>(py));
+    drop(shared);
+}
+
+#[pyfunction]
+fn increment_counter(counter: &mut Counter) {
+    counter.count += 1;
+}
+
+#[pyfunction]
+fn get_counter(counter: &Counter) -> u32 {
+    counter.count
+}
+
+#[test]
+fn reference_arguments_are_borrow_checked() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(py, Counter { count: 0 }).unwrap();
+    let increment = wrap_pyfunction!(increment_counter)(py);
+    let get = wrap_pyfunction!(get_counter)(py);
+    let d = [
+        ("c", c.to_object(py)),
+        ("increment", increment),
+        ("get", get),
+    ]
+    .into_py_dict(py);
+
+    // re-entering a `&mut` argument from a `&self` method raises instead of aliasing
+    for code in &[
+        "c.call(lambda: increment(c))",
+        "c.call_mut(lambda: get(c))",
+        "c.call_mut(lambda: increment(c))",
+    ] {
+        let err = py.run(code, None, Some(d)).unwrap_err();
+        assert!(err.is_instance::<RuntimeError>(py), "{}", code);
+    }
+    // the arguments are released when the functions return
+    py.run(
+        "c.call(lambda: get(c)); increment(c); assert get(c) == 3",
+        None,
+        Some(d),
+    )
+    .unwrap();
+    assert_eq!(c.as_ref(py).count, 3);
+}
+
+#[test]
+fn ref_from_ptr_or_err_returns_borrow_error() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(py, Counter { count: 0 }).unwrap();
+    let shared = c.as_ref(py);
+    let err = unsafe { PyRefMut::<Counter>::from_borrowed_ptr_or_err(py, c.as_ptr()) }
+        .err()
+        .unwrap();
+    assert!(err.is_instance::<RuntimeError>(py));
+    assert!(unsafe { PyRef::<Counter>::from_borrowed_ptr_or_err(py, c.as_ptr()) }.is_ok());
+    drop(shared);
+}
+
+#[test]
+fn extract_ref_guards() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(py, Counter { count: 0 }).unwrap();
+    let ob = c.to_object(py);
+    let ob = ob.as_ref(py);
+    {
+        let mut counter: PyRefMut<Counter> = ob.extract().unwrap();
+        counter.count += 1;
+        assert!(ob.extract::<PyRef<Counter>>().is_err());
+        assert!(ob.extract::<&Counter>().is_err());
+    }
+    // references don't hold a borrow, so the instance isn't locked after the extraction
+    let counter: &mut Counter = ob.extract().unwrap();
+    counter.count += 1;
+    assert_eq!(ob.extract::<PyRef<Counter>>().unwrap().count, 2);
+
+    // the guard keeps an instance that is only owned by the pool alive
+    let ptr = Py::new(py, Counter { count: 0 }).unwrap().into_ptr();
+    let ob: &PyAny = unsafe { py.from_owned_ptr(ptr) };
+    let counter: PyRef<Counter> = ob.extract().unwrap();
+    assert_eq!(counter.count, 0);
+}
+
+#[pyclass]
+struct Numbers {
+    items: Vec<i32>,
+}
+
+#[pyproto]
+impl pyo3::class::PySequenceProtocol for Numbers {
+    fn __len__(&self) -> PyResult<usize> {
+        Ok(self.items.len())
+    }
+
+    fn __setitem__(&mut self, index: isize, value: i32) -> PyResult<()> {
+        self.items[index as usize] = value;
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Numbers {
+    fn each(&self, py: Python, callback: PyObject) -> PyResult<()> {
+        for item in &self.items {
+            callback.call1(py, (*item,))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn protocol_methods_are_borrow_checked() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let n = Py::new(py, Numbers { items: vec![1, 2] }).unwrap();
+    py_run!(py, n, "n.each(lambda x: len(n))");
+    py_expect_exception!(py, n, "n.each(lambda x: n.__setitem__(0, x))", RuntimeError);
+    py_run!(py, n, "n[0] = 5");
+    assert_eq!(n.as_ref(py).items, vec![5, 2]);
+}
@@ -0,0 +1,80 @@
+use pyo3::exceptions::ValueError;
+use pyo3::prelude::*;
+use pyo3::py_run;
+use pyo3::type_object::initialize_type;
+use pyo3::types::PyTuple;
+
+mod common;
+
+#[pyclass]
+struct Parser {}
+
+#[pymethods]
+impl Parser {
+    #[classattr]
+    const VERSION: &'static str = "1.2";
+
+    #[classattr]
+    const MAX_DEPTH: u32 = 64;
+
+    const NOT_EXPOSED: u32 = 0;
+
+    #[classattr]
+    fn extensions() -> Vec<&'static str> {
+        vec![".json", ".toml"]
+    }
+
+    #[classattr]
+    fn limits(py: Python) -> &PyTuple {
+        PyTuple::new(py, &[Self::NOT_EXPOSED, Self::MAX_DEPTH])
+    }
+
+    #[classattr]
+    fn default() -> Parser {
+        Parser {}
+    }
+}
+
+#[test]
+fn class_attributes() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let parser = py.get_type::<Parser>();
+    py_assert!(py, parser, "parser.VERSION == '1.2'");
+    py_assert!(py, parser, "parser.MAX_DEPTH == 64");
+    py_assert!(py, parser, "not hasattr(parser, 'NOT_EXPOSED')");
+    py_assert!(py, parser, "parser.extensions == ['.json', '.toml']");
+    py_assert!(py, parser, "parser.limits == (0, 64)");
+    py_assert!(py, parser, "isinstance(parser.default, parser)");
+    py_assert!(py, parser, "parser.default.VERSION == '1.2'");
+}
+
+#[test]
+fn class_attributes_are_shared() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let parser = py.get_type::<Parser>();
+    py_run!(py, parser, "assert parser.default is parser.default");
+}
+
+#[pyclass]
+struct Failing {}
+
+#[pymethods]
+impl Failing {
+    #[classattr]
+    fn broken() -> PyResult<u32> {
+        Err(ValueError::py_err("no value"))
+    }
+}
+
+#[test]
+fn class_attribute_error() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let err = initialize_type::<Failing>(py, None).unwrap_err();
+    assert!(err.is_instance::<ValueError>(py));
+}
@@ -3,4 +3,7 @@ fn test_compile_errors() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/ui/reject_generics.rs");
     t.compile_fail("tests/ui/too_many_args_to_getter.rs");
+    t.compile_fail("tests/ui/with_pool_escape.rs");
+    t.compile_fail("tests/ui/with_gil_escape.rs");
+    t.compile_fail("tests/ui/invalid_transparent.rs");
 }
@@ -2,8 +2,11 @@
 
 use pyo3::ffi::*;
 use pyo3::prelude::*;
+use pyo3::py_run;
 use pyo3::types::{IntoPyDict, PyAny};
 
+mod common;
+
 #[allow(clippy::trivially_copy_pass_by_ref)]
 fn _get_subclasses<'p>(
     py: &'p Python,
@@ -206,3 +209,125 @@ fn test_pydatetime_out_of_bounds() {
         dt.unwrap_err();
     }
 }
+
+#[test]
+fn test_duration_to_timedelta() {
+    use pyo3::exceptions::OverflowError;
+    use pyo3::types::PyDelta;
+    use std::time::Duration;
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let delta = Duration::new(2 * 86400 + 3, 4_000_999).to_object(py);
+    py_assert!(py, delta, "delta.days == 2 and delta.seconds == 3");
+    py_assert!(py, delta, "delta.microseconds == 4000");
+
+    let err = PyDelta::from_duration(py, Duration::from_secs(u64::max_value())).unwrap_err();
+    assert!(err.is_instance::<OverflowError>(py));
+    let err = PyDelta::from_duration(py, Duration::from_secs(10u64.pow(9) * 86400)).unwrap_err();
+    assert!(err.is_instance::<OverflowError>(py));
+
+    let delta = Duration::from_secs(u64::max_value()).to_object(py);
+    py_assert!(py, delta, "delta == type(delta).max");
+}
+
+#[test]
+fn test_timedelta_to_duration() {
+    use pyo3::exceptions::{TypeError, ValueError};
+    use std::time::Duration;
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let datetime = py.import("datetime").unwrap();
+    let locals = [("datetime", datetime)].into_py_dict(py);
+    let eval = |code| py.eval(code, None, Some(locals)).unwrap();
+
+    let duration: Duration = eval("datetime.timedelta(days=2, seconds=3, microseconds=4)")
+        .extract()
+        .unwrap();
+    assert_eq!(duration, Duration::new(2 * 86400 + 3, 4000));
+
+    let err = eval("datetime.timedelta(microseconds=-1)")
+        .extract::<Duration>()
+        .unwrap_err();
+    assert!(err.is_instance::<ValueError>(py));
+    let err = eval("1.5").extract::<Duration>().unwrap_err();
+    assert!(err.is_instance::<TypeError>(py));
+}
+
+#[test]
+fn test_system_time_to_datetime() {
+    use pyo3::exceptions::OverflowError;
+    use pyo3::types::PyDateTime;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let datetime = py.import("datetime").unwrap();
+    let time = (UNIX_EPOCH + Duration::new(1_500_000_000, 123_456_789)).to_object(py);
+    // Times before the epoch are rounded down as well
+    let before_epoch = (UNIX_EPOCH - Duration::new(0, 1_500)).to_object(py);
+    py_run!(
+        py,
+        datetime time before_epoch,
+        r#"
+utc = datetime.timezone.utc
+assert time == datetime.datetime(2017, 7, 14, 2, 40, 0, 123456, utc)
+assert time.tzinfo is utc
+assert before_epoch == datetime.datetime(1969, 12, 31, 23, 59, 59, 999998, utc)
+"#
+    );
+
+    let far_future = UNIX_EPOCH + Duration::from_secs(10_000 * 366 * 86400);
+    let err = PyDateTime::from_system_time(py, far_future).unwrap_err();
+    assert!(err.is_instance::<OverflowError>(py));
+    let far_future = far_future.to_object(py);
+    py_run!(
+        py,
+        datetime far_future,
+        "assert far_future == datetime.datetime.max.replace(tzinfo=datetime.timezone.utc)"
+    );
+}
+
+#[test]
+fn test_datetime_to_system_time() {
+    use pyo3::exceptions::ValueError;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let datetime = py.import("datetime").unwrap();
+    let locals = [("datetime", datetime)].into_py_dict(py);
+    let eval = |code| py.eval(code, None, Some(locals)).unwrap();
+
+    let time: SystemTime = eval(
+        "datetime.datetime(2017, 7, 14, 4, 40, 0, 123456, \
+         datetime.timezone(datetime.timedelta(hours=2)))",
+    )
+    .extract()
+    .unwrap();
+    assert_eq!(time, UNIX_EPOCH + Duration::new(1_500_000_000, 123_456_000));
+
+    let time: SystemTime =
+        eval("datetime.datetime(1969, 12, 31, 23, 59, 59, 999998, datetime.timezone.utc)")
+            .extract()
+            .unwrap();
+    assert_eq!(time, UNIX_EPOCH - Duration::from_micros(2));
+
+    let now = SystemTime::now();
+    let round_trip: SystemTime = now.to_object(py).extract(py).unwrap();
+    assert!(now.duration_since(round_trip).unwrap() < Duration::from_micros(1));
+
+    let err = eval("datetime.datetime(2017, 7, 14)")
+        .extract::<SystemTime>()
+        .unwrap_err();
+    assert!(err.is_instance::<ValueError>(py));
+
+    // A tzinfo without a utc offset makes the datetime naive
+    let no_offset = eval(
+        "datetime.datetime(2017, 7, 14, tzinfo=type('NoOffset', (datetime.tzinfo,), \
+         {'utcoffset': lambda self, dt: None})())",
+    );
+    let err = no_offset.extract::<SystemTime>().unwrap_err();
+    assert!(err.is_instance::<ValueError>(py));
+}
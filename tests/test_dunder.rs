@@ -217,10 +217,10 @@ fn setitem() {
     let gil = Python::acquire_gil();
     let py = gil.python();
 
-    let c = PyRef::new(py, SetItem { key: 0, val: 0 }).unwrap();
+    let c = Py::new(py, SetItem { key: 0, val: 0 }).unwrap();
     py_run!(py, c, "c[1] = 2");
-    assert_eq!(c.key, 1);
-    assert_eq!(c.val, 2);
+    assert_eq!(c.as_ref(py).key, 1);
+    assert_eq!(c.as_ref(py).val, 2);
     py_expect_exception!(py, c, "del c[1]", NotImplementedError);
 }
 
@@ -242,9 +242,9 @@ fn delitem() {
     let gil = Python::acquire_gil();
     let py = gil.python();
 
-    let c = PyRef::new(py, DelItem { key: 0 }).unwrap();
+    let c = Py::new(py, DelItem { key: 0 }).unwrap();
     py_run!(py, c, "del c[1]");
-    assert_eq!(c.key, 1);
+    assert_eq!(c.as_ref(py).key, 1);
     py_expect_exception!(py, c, "c[1] = 2", NotImplementedError);
 }
 
@@ -271,11 +271,11 @@ fn setdelitem() {
     let gil = Python::acquire_gil();
     let py = gil.python();
 
-    let c = PyRef::new(py, SetDelItem { val: None }).unwrap();
+    let c = Py::new(py, SetDelItem { val: None }).unwrap();
     py_run!(py, c, "c[1] = 2");
-    assert_eq!(c.val, Some(2));
+    assert_eq!(c.as_ref(py).val, Some(2));
     py_run!(py, c, "del c[1]");
-    assert_eq!(c.val, None);
+    assert_eq!(c.as_ref(py).val, None);
 }
 
 #[pyclass]
@@ -350,22 +350,22 @@ fn context_manager() {
     let gil = Python::acquire_gil();
     let py = gil.python();
 
-    let mut c = PyRefMut::new(py, ContextManager { exit_called: false }).unwrap();
+    let mut c = Py::new(py, ContextManager { exit_called: false }).unwrap();
     py_run!(py, c, "with c as x: assert x == 42");
-    assert!(c.exit_called);
+    assert!(c.as_ref(py).exit_called);
 
-    c.exit_called = false;
+    c.as_mut(py).exit_called = false;
     py_run!(py, c, "with c as x: raise ValueError");
-    assert!(c.exit_called);
+    assert!(c.as_ref(py).exit_called);
 
-    c.exit_called = false;
+    c.as_mut(py).exit_called = false;
     py_expect_exception!(
         py,
         c,
         "with c as x: raise NotImplementedError",
         NotImplementedError
     );
-    assert!(c.exit_called);
+    assert!(c.as_ref(py).exit_called);
 }
 
 #[test]
@@ -0,0 +1,121 @@
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+
+mod common;
+
+#[pyclass]
+#[derive(Debug, PartialEq)]
+enum Color {
+    Red,
+    Green = 10,
+    Blue,
+}
+
+#[test]
+fn test_enum_class_attributes() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let color = py.get_type::<Color>();
+    py_assert!(py, color, "isinstance(color.Red, color)");
+    py_assert!(py, color, "color.Red == color.Red");
+    py_assert!(py, color, "color.Red != color.Green");
+    py_assert!(py, color, "color.Green == 10");
+    py_assert!(py, color, "color.Blue == 11");
+    py_assert!(py, color, "color.Blue != 'Blue'");
+}
+
+#[test]
+fn test_enum_dunder_methods() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let color = py.get_type::<Color>();
+    py_assert!(py, color, "repr(color.Red) == 'Color.Red'");
+    py_assert!(py, color, "int(color.Green) == 10");
+    py_assert!(py, color, "hash(color.Blue) == hash(color.Blue)");
+    py_assert!(py, color, "len({color.Red, color.Red, color.Blue}) == 2");
+}
+
+#[test]
+fn test_enum_conversions() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let blue = Color::Blue.into_py(py);
+    py_assert!(py, blue, "int(blue) == 11");
+
+    let color = py.get_type::<Color>();
+    let green: Color = color.getattr("Green").unwrap().extract().unwrap();
+    assert_eq!(green, Color::Green);
+    assert!(py.None().extract::<Color>(py).is_err());
+}
+
+#[pyclass(name = Form)]
+#[derive(Clone, Debug, PartialEq)]
+enum Shape {
+    /// A circle
+    Circle {
+        radius: f64,
+    },
+    Rectangle(f64, f64),
+    Empty,
+}
+
+#[test]
+fn test_data_enum_constructors() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let env = [("Form", py.get_type::<Shape>())].into_py_dict(py);
+    let eval = |code: &str| -> Shape { py.eval(code, None, Some(env)).unwrap().extract().unwrap() };
+
+    assert_eq!(
+        eval("Form.Circle(radius=1.5)"),
+        Shape::Circle { radius: 1.5 }
+    );
+    assert_eq!(eval("Form.Circle(2.0)"), Shape::Circle { radius: 2.0 });
+    assert_eq!(eval("Form.Rectangle(1.0, 2.0)"), Shape::Rectangle(1.0, 2.0));
+    assert_eq!(eval("Form.Empty"), Shape::Empty);
+    py.run("assert Form.Circle.__doc__ == 'A circle'", None, Some(env))
+        .unwrap();
+    assert!(py.eval("Form.Rectangle(1.0)", None, Some(env)).is_err());
+}
+
+#[test]
+fn test_data_enum_fields() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let circle: PyObject = Shape::Circle { radius: 1.5 }.into_py(py);
+    let rectangle: PyObject = Shape::Rectangle(1.0, 2.0).into_py(py);
+    py_assert!(py, circle, "circle.radius == 1.5");
+    py_assert!(py, rectangle, "(rectangle._0, rectangle._1) == (1.0, 2.0)");
+    py_assert!(py, circle, "not hasattr(circle, '_0')");
+    py_assert!(py, rectangle, "not hasattr(rectangle, 'radius')");
+    py_assert!(py, circle, "not hasattr(circle, '__pyo3_variant_Circle')");
+}
+
+#[test]
+fn test_data_enum_repr_and_eq() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let form = py.get_type::<Shape>();
+    py_assert!(
+        py,
+        form,
+        "repr(form.Circle(radius=1.5)) == 'Form.Circle(radius=1.5)'"
+    );
+    py_assert!(
+        py,
+        form,
+        "repr(form.Rectangle(1.0, 2.0)) == 'Form.Rectangle(1.0, 2.0)'"
+    );
+    py_assert!(py, form, "repr(form.Empty) == 'Form.Empty'");
+    py_assert!(py, form, "form.Circle(1.0) == form.Circle(radius=1.0)");
+    py_assert!(py, form, "form.Circle(1.0) != form.Circle(2.0)");
+    py_assert!(py, form, "form.Rectangle(1.0, 1.0) != form.Circle(1.0)");
+    py_assert!(py, form, "form.Empty == form.Empty");
+    py_assert!(py, form, "form.Empty != 0");
+}
@@ -0,0 +1,95 @@
+use pyo3::create_exception;
+use pyo3::exceptions::Exception;
+use pyo3::prelude::*;
+use pyo3::py_run;
+use pyo3::types::{PyDateTime, PyDict};
+use pyo3::wrap_pyfunction;
+use pyo3::{FinalizeError, SubInterpreter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod common;
+
+// The interpreter is shared by the process, so this file has a single test
+
+static AT_EXIT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[pyfunction]
+fn at_exit() {
+    AT_EXIT_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[pyclass(freelist = 4)]
+struct Holder {
+    value: PyObject,
+}
+
+#[pymethods]
+impl Holder {
+    fn value(&self, py: Python) -> PyObject {
+        self.value.clone_ref(py)
+    }
+}
+
+create_exception!(finalize, CustomError, Exception);
+
+/// Uses the objects that pyo3 caches, and keeps some of them alive until the finalization
+fn use_interpreter(py: Python) {
+    let at_exit = wrap_pyfunction!(at_exit)(py);
+    let holder = Py::new(
+        py,
+        Holder {
+            value: py.eval("[1, 2]", None, None).unwrap().to_object(py),
+        },
+    )
+    .unwrap();
+    let error = py.get_type::<CustomError>();
+    let date = PyDateTime::new(py, 2020, 1, 2, 3, 4, 5, 0, None).unwrap();
+    py_run!(
+        py,
+        at_exit holder error date,
+        r#"
+import atexit, sys
+atexit.register(at_exit)
+sys.holder = holder
+assert holder.value() == [1, 2]
+assert str(error) == "<class 'finalize.CustomError'>"
+assert date.year == 2020
+"#
+    );
+    drop(Py::new(py, Holder { value: py.None() }).unwrap());
+}
+
+#[test]
+fn finalize_and_initialize_again() {
+    assert_eq!(Python::finalize(), Err(FinalizeError::NotInitialized));
+
+    Python::with_gil(|py| {
+        use_interpreter(py);
+        assert_eq!(Python::finalize(), Err(FinalizeError::GILHeld));
+    });
+
+    let gil = Python::acquire_gil();
+    let sub = SubInterpreter::new(gil.python()).unwrap();
+    drop(gil);
+    assert_eq!(Python::finalize(), Err(FinalizeError::InUse));
+    drop(sub);
+
+    let leaked: PyObject = Python::with_gil(|py| PyDict::new(py).into());
+    assert_eq!(Python::finalize(), Err(FinalizeError::LeakedHandles(1)));
+    assert_eq!(AT_EXIT_CALLS.load(Ordering::SeqCst), 1);
+    drop(leaked);
+
+    Python::with_gil(|py| {
+        py.run("import sys; assert not hasattr(sys, 'holder')", None, None)
+            .unwrap();
+        use_interpreter(py);
+    });
+    assert_eq!(Python::finalize(), Ok(()));
+    assert_eq!(AT_EXIT_CALLS.load(Ordering::SeqCst), 2);
+
+    Python::with_gil(|py| {
+        let err = PyErr::from(FinalizeError::InUse);
+        assert!(err.is_instance::<pyo3::exceptions::RuntimeError>(py));
+        use_interpreter(py);
+    });
+}
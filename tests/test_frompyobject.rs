@@ -0,0 +1,212 @@
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict, PyString, PyTuple};
+
+mod common;
+
+fn error_message(py: Python, err: PyErr) -> String {
+    let instance = err.to_object(py);
+    let instance = instance.as_ref(py);
+    let message = instance.str().unwrap();
+    message.to_string_lossy().into_owned()
+}
+
+#[pyclass]
+struct Point {
+    #[pyo3(get)]
+    x: i32,
+    #[pyo3(get)]
+    y: i32,
+}
+
+#[derive(Debug, FromPyObject)]
+struct Coordinates {
+    x: i32,
+    #[pyo3(attribute("y"))]
+    vertical: i32,
+}
+
+#[test]
+fn test_named_fields_from_attributes() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let point = PyRef::new(py, Point { x: 1, y: 2 }).unwrap();
+    let coordinates: Coordinates = point.to_object(py).extract(py).unwrap();
+    assert_eq!(coordinates.x, 1);
+    assert_eq!(coordinates.vertical, 2);
+}
+
+#[derive(Debug, FromPyObject)]
+struct Config {
+    #[pyo3(item)]
+    name: String,
+    #[pyo3(item("max-size"))]
+    max_size: usize,
+    #[pyo3(item)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_named_fields_from_items() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let dict = [
+        ("name", "cache".to_object(py)),
+        ("max-size", 16.to_object(py)),
+    ]
+    .into_py_dict(py);
+    dict.set_item("tags", vec!["a", "b"]).unwrap();
+
+    let config: Config = dict.extract().unwrap();
+    assert_eq!(config.name, "cache");
+    assert_eq!(config.max_size, 16);
+    assert_eq!(config.tags, vec!["a", "b"]);
+}
+
+#[test]
+fn test_named_fields_error_names_field() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let dict = [
+        ("name", "cache".to_object(py)),
+        ("max-size", "big".to_object(py)),
+    ]
+    .into_py_dict(py);
+    let err = dict.extract::<Config>().unwrap_err();
+    assert!(err.is_instance::<pyo3::exceptions::TypeError>(py));
+    let cause = err.to_object(py).getattr(py, "__cause__").unwrap();
+    assert!(PyErr::from_instance(&cause.as_ref(py)).is_instance::<pyo3::exceptions::TypeError>(py));
+    let message = error_message(py, err);
+    assert!(message.starts_with("failed to extract field Config.max_size"));
+
+    let dict = PyDict::new(py);
+    let err = dict.extract::<Config>().unwrap_err();
+    let cause = err.to_object(py).getattr(py, "__cause__").unwrap();
+    assert!(PyErr::from_instance(&cause.as_ref(py)).is_instance::<pyo3::exceptions::KeyError>(py));
+    let message = error_message(py, err);
+    assert!(message.starts_with("failed to extract field Config.name (KeyError"));
+}
+
+#[derive(Debug, FromPyObject)]
+struct Rgb(u8, u8, u8);
+
+#[test]
+fn test_tuple_struct_from_sequence() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let rgb: Rgb = py.eval("(1, 2, 3)", None, None).unwrap().extract().unwrap();
+    assert_eq!((rgb.0, rgb.1, rgb.2), (1, 2, 3));
+    let rgb: Rgb = py.eval("[4, 5, 6]", None, None).unwrap().extract().unwrap();
+    assert_eq!((rgb.0, rgb.1, rgb.2), (4, 5, 6));
+
+    let too_short = PyTuple::new(py, &[1, 2]);
+    assert!(too_short.extract::<Rgb>().is_err());
+
+    let err = py
+        .eval("(1, 2, 300)", None, None)
+        .unwrap()
+        .extract::<Rgb>()
+        .unwrap_err();
+    let message = error_message(py, err);
+    assert!(message.starts_with("failed to extract field Rgb.2"));
+}
+
+#[derive(Debug, FromPyObject)]
+struct Wrapper<'a>(&'a PyString);
+
+#[derive(Debug, FromPyObject)]
+struct Generic<T> {
+    #[pyo3(item)]
+    value: T,
+}
+
+#[test]
+fn test_newtype_and_generics() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let s = PyString::new(py, "text");
+    let wrapper: Wrapper = s.extract().unwrap();
+    assert_eq!(wrapper.0.to_string().unwrap(), "text");
+
+    let dict = [("value", 5)].into_py_dict(py);
+    let generic: Generic<u64> = dict.extract().unwrap();
+    assert_eq!(generic.value, 5);
+}
+
+#[derive(Debug, FromPyObject)]
+enum Shape {
+    Circle {
+        #[pyo3(item)]
+        radius: f64,
+    },
+    Rect {
+        #[pyo3(item)]
+        width: f64,
+        #[pyo3(item)]
+        height: f64,
+    },
+    Size(f64),
+    Pair(f64, f64),
+}
+
+#[test]
+fn test_enum_tries_variants_in_order() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let circle: Shape = py
+        .eval("{'radius': 1.5}", None, None)
+        .unwrap()
+        .extract()
+        .unwrap();
+    match circle {
+        Shape::Circle { radius } => assert_eq!(radius, 1.5),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let rect: Shape = py
+        .eval("{'width': 2.0, 'height': 3.0}", None, None)
+        .unwrap()
+        .extract()
+        .unwrap();
+    match rect {
+        Shape::Rect { width, height } => assert_eq!((width, height), (2.0, 3.0)),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let size: Shape = py.eval("4.0", None, None).unwrap().extract().unwrap();
+    match size {
+        Shape::Size(size) => assert_eq!(size, 4.0),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let pair: Shape = py
+        .eval("(5.0, 6.0)", None, None)
+        .unwrap()
+        .extract()
+        .unwrap();
+    match pair {
+        Shape::Pair(a, b) => assert_eq!((a, b), (5.0, 6.0)),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_enum_error_lists_variants() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let err = py
+        .eval("'not a shape'", None, None)
+        .unwrap()
+        .extract::<Shape>()
+        .unwrap_err();
+    assert!(err.is_instance::<pyo3::exceptions::TypeError>(py));
+    let message = error_message(py, err);
+    assert!(message.starts_with("failed to extract enum Shape ('Circle | Rect | Size | Pair')"));
+    assert!(message.contains("- variant Size: "));
+}
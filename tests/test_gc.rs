@@ -4,8 +4,7 @@ use pyo3::class::PyVisit;
 use pyo3::ffi;
 use pyo3::prelude::*;
 use pyo3::py_run;
-use pyo3::types::PyAny;
-use pyo3::types::PyTuple;
+use pyo3::types::{IntoPyDict, PyAny, PyTuple};
 use pyo3::AsPyPointer;
 use pyo3::PyRawObject;
 use std::cell::RefCell;
@@ -277,3 +276,97 @@ fn inheritance_with_new_methods_with_drop() {
     assert!(drop_called1.load(Ordering::Relaxed));
     assert!(drop_called2.load(Ordering::Relaxed));
 }
+
+static TRAVERSABLE_DROPPED: AtomicBool = AtomicBool::new(false);
+
+#[pyclass(gc, subclass)]
+struct TraversableBase {
+    other: Option<PyObject>,
+}
+
+#[pymethods]
+impl TraversableBase {
+    #[new]
+    fn new(obj: &PyRawObject) {
+        obj.init(TraversableBase { other: None })
+    }
+
+    fn link(&mut self, other: PyObject) {
+        self.other = Some(other);
+    }
+}
+
+#[pyproto]
+impl PyGCProtocol for TraversableBase {
+    fn __traverse__(&self, visit: PyVisit) -> Result<(), PyTraverseError> {
+        if let Some(ref other) = self.other {
+            visit.call(other)?;
+        }
+        Ok(())
+    }
+
+    fn __clear__(&mut self) {
+        self.other = None;
+    }
+}
+
+impl Drop for TraversableBase {
+    fn drop(&mut self) {
+        TRAVERSABLE_DROPPED.store(true, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn gc_integration_with_python_subclass() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let d = [("TraversableBase", py.get_type::<TraversableBase>())].into_py_dict(py);
+
+    py.run(
+        r#"
+import gc
+
+class Sub(TraversableBase):
+    pass
+
+a, b = Sub(), Sub()
+# a cycle through the rust fields and one through the __dict__ of the subclass
+a.link(b)
+b.link(a)
+a.me = a
+del a, b
+gc.collect()
+"#,
+        None,
+        Some(d),
+    )
+    .map_err(|e| e.print(py))
+    .unwrap();
+    assert!(TRAVERSABLE_DROPPED.load(Ordering::Relaxed));
+}
+
+#[pyclass(gc)]
+struct PanickingTraverse {}
+
+#[pyproto]
+impl PyGCProtocol for PanickingTraverse {
+    fn __traverse__(&self, _visit: PyVisit) -> Result<(), PyTraverseError> {
+        panic!("traverse panicked")
+    }
+    fn __clear__(&mut self) {}
+}
+
+#[test]
+fn panic_in_traverse_is_unraisable() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let inst = PyRef::new(py, PanickingTraverse {}).unwrap();
+
+    // The panic neither aborts the collection nor leaves an exception behind
+    py_run!(
+        py,
+        inst,
+        "import gc; gc.collect(); assert inst in gc.get_objects()"
+    );
+    assert!(!PyErr::occurred(py));
+}
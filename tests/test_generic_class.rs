@@ -0,0 +1,105 @@
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+use pyo3::PyTypeInfo;
+use std::ops::Mul;
+
+mod common;
+
+#[pyclass(instantiate(MatrixF32 = Matrix<f32>, MatrixI64 = Matrix<i64>))]
+struct Matrix<T> {
+    #[pyo3(get)]
+    data: Vec<T>,
+    columns: usize,
+}
+
+#[pymethods(instantiate(Matrix<f32>, Matrix<i64>))]
+impl<T> Matrix<T>
+where
+    T: Copy + Mul<Output = T> + IntoPy<PyObject> + for<'a> FromPyObject<'a>,
+{
+    #[new]
+    fn new(obj: &PyRawObject, data: Vec<T>, columns: usize)
+    where
+        Self: PyTypeInfo,
+    {
+        obj.init(Matrix { data, columns })
+    }
+
+    #[getter]
+    fn rows(&self) -> usize {
+        self.data.len() / self.columns
+    }
+
+    fn get(&self, row: usize, column: usize) -> T {
+        self.data[row * self.columns + column]
+    }
+
+    fn scale(&mut self, factor: T) {
+        for value in self.data.iter_mut() {
+            *value = *value * factor;
+        }
+    }
+}
+
+#[pymethods]
+impl Matrix<f32> {
+    fn is_float(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_instantiations_are_separate_classes() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let matrix_f32 = py.get_type::<Matrix<f32>>();
+    let matrix_i64 = py.get_type::<Matrix<i64>>();
+    py_assert!(py, matrix_f32, "matrix_f32.__name__ == 'MatrixF32'");
+    py_assert!(py, matrix_i64, "matrix_i64.__name__ == 'MatrixI64'");
+    py_assert!(py, matrix_f32, "hasattr(matrix_f32, 'is_float')");
+    py_assert!(py, matrix_i64, "not hasattr(matrix_i64, 'is_float')");
+}
+
+#[test]
+fn test_generic_methods() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let env = [
+        ("MatrixF32", py.get_type::<Matrix<f32>>()),
+        ("MatrixI64", py.get_type::<Matrix<i64>>()),
+    ]
+    .into_py_dict(py);
+    let run = |code: &str| {
+        py.run(code, None, Some(env))
+            .map_err(|e| e.print(py))
+            .unwrap()
+    };
+
+    run("m = MatrixF32([1.0, 2.0, 3.0, 4.0], 2)");
+    run("assert m.rows == 2");
+    run("assert m.get(1, 0) == 3.0");
+    run("m.scale(0.5)");
+    run("assert m.data == [0.5, 1.0, 1.5, 2.0]");
+
+    run("m = MatrixI64([1, 2, 3], 3)");
+    run("assert m.rows == 1");
+    run("m.scale(2)");
+    run("assert m.data == [2, 4, 6]");
+    assert!(py.run("m.scale(0.5)", None, Some(env)).is_err());
+}
+
+#[test]
+fn test_generic_class_from_rust() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let matrix = Matrix {
+        data: vec![1i64, 2],
+        columns: 1,
+    };
+    let matrix = Py::new(py, matrix).unwrap();
+    py_assert!(py, matrix, "type(matrix).__name__ == 'MatrixI64'");
+    py_assert!(py, matrix, "matrix.rows == 2");
+}
@@ -1,8 +1,9 @@
+use pyo3::exceptions::Exception;
 use pyo3::prelude::*;
 use pyo3::py_run;
-#[cfg(feature = "unsound-subclass")]
-use pyo3::types::IntoPyDict;
+use pyo3::types::{IntoPyDict, PyDict, PyList};
 use std::isize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 mod common;
 
@@ -12,11 +13,9 @@ struct BaseClass {
     val1: usize,
 }
 
-#[cfg(feature = "unsound-subclass")]
 #[pyclass(subclass)]
 struct SubclassAble {}
 
-#[cfg(feature = "unsound-subclass")]
 #[test]
 fn subclass() {
     let gil = Python::acquire_gil();
@@ -32,6 +31,142 @@ fn subclass() {
     .unwrap();
 }
 
+static SHAPES_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+#[pyclass(subclass)]
+struct Shape {
+    sides: u32,
+}
+
+#[pymethods]
+impl Shape {
+    #[new]
+    fn new(obj: &PyRawObject, sides: u32) {
+        obj.init(Shape { sides })
+    }
+
+    fn sides(&self) -> u32 {
+        self.sides
+    }
+
+    fn describe(&self) -> String {
+        format!("{} sides", self.sides)
+    }
+}
+
+impl Drop for Shape {
+    fn drop(&mut self) {
+        SHAPES_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn subclass_from_python() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let d = [("Shape", py.get_type::<Shape>())].into_py_dict(py);
+
+    py.run(
+        r#"
+class Square(Shape):
+    def __new__(cls, color):
+        self = super().__new__(cls, 4)
+        self.color = color
+        return self
+
+    def describe(self):
+        return self.color + ' square with ' + super().describe()
+
+s = Square('red')
+assert isinstance(s, Shape)
+assert s.sides() == 4
+assert s.describe() == 'red square with 4 sides'
+assert s.__dict__ == {'color': 'red'}
+
+import weakref
+r = weakref.ref(s)
+assert r() is s
+del s
+assert r() is None
+"#,
+        None,
+        Some(d),
+    )
+    .map_err(|e| e.print(py))
+    .unwrap();
+}
+
+#[test]
+fn subclass_instances_are_dropped() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let d = [("Shape", py.get_type::<Shape>())].into_py_dict(py);
+
+    let before = SHAPES_DROPPED.load(Ordering::SeqCst);
+    py.run(
+        r#"
+class Triangle(Shape):
+    pass
+
+for _ in range(100):
+    t = Triangle(3)
+    t.tag = 'triangle'
+    assert t.sides() == 3
+del t
+"#,
+        None,
+        Some(d),
+    )
+    .map_err(|e| e.print(py))
+    .unwrap();
+    assert!(SHAPES_DROPPED.load(Ordering::SeqCst) - before >= 100);
+}
+
+#[pyclass(subclass, dict, weakref)]
+struct Node {
+    #[pyo3(get)]
+    id: u32,
+}
+
+#[pymethods]
+impl Node {
+    #[new]
+    fn new(obj: &PyRawObject, id: u32) {
+        obj.init(Node { id })
+    }
+}
+
+#[test]
+fn subclass_with_dict_and_weakref() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let d = [("Node", py.get_type::<Node>())].into_py_dict(py);
+
+    py.run(
+        r#"
+import weakref
+
+class Leaf(Node):
+    __slots__ = ('parent',)
+
+n = Node(1)
+n.label = 'root'
+leaf = Leaf(2)
+leaf.parent = n
+leaf.label = 'leaf'
+assert (leaf.id, leaf.parent.id, leaf.label) == (2, 1, 'leaf')
+
+refs = [weakref.ref(n), weakref.ref(leaf)]
+del n, leaf
+assert [r() for r in refs] == [None, None]
+"#,
+        None,
+        Some(d),
+    )
+    .map_err(|e| e.print(py))
+    .unwrap();
+}
+
 #[pymethods]
 impl BaseClass {
     #[new]
@@ -64,3 +199,147 @@ fn inheritance_with_new_methods() {
     let inst = typeobj.call((), None).unwrap();
     py_run!(py, inst, "assert inst.val1 == 10; assert inst.val2 == 5");
 }
+
+#[pyclass(extends=PyDict)]
+struct DictWithName {
+    #[pyo3(get)]
+    name: String,
+}
+
+#[pymethods]
+impl DictWithName {
+    #[new]
+    fn new(obj: &PyRawObject) {
+        obj.init(DictWithName {
+            name: "dict".to_string(),
+        })
+    }
+
+    fn size(slf: PyRef<Self>, py: Python) -> PyResult<usize> {
+        let obj = slf.to_object(py);
+        let dict: &PyDict = obj.extract(py)?;
+        Ok(dict.len())
+    }
+}
+
+#[test]
+fn inherit_dict() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let cls = py.get_type::<DictWithName>();
+    py_run!(
+        py,
+        cls,
+        r#"
+d = cls()
+assert isinstance(d, dict)
+assert d.name == 'dict'
+d['a'] = 1
+d.update(b=2)
+assert d == {'a': 1, 'b': 2}
+assert len(d) == 2 and d.size() == 2
+"#
+    );
+}
+
+#[pyclass(extends=PyList)]
+struct ListWithCounter {
+    counter: u32,
+}
+
+#[pymethods]
+impl ListWithCounter {
+    #[new]
+    fn new(obj: &PyRawObject) {
+        obj.init(ListWithCounter { counter: 0 })
+    }
+
+    fn count_up(&mut self) -> u32 {
+        self.counter += 1;
+        self.counter
+    }
+}
+
+#[test]
+fn inherit_list() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let l = Py::new(py, ListWithCounter { counter: 0 }).unwrap();
+    py_run!(
+        py,
+        l,
+        r#"
+assert isinstance(l, list)
+l.extend(range(100))
+assert sum(l) == 4950
+assert l.count_up() == 1 and l.count_up() == 2
+"#
+    );
+}
+
+#[pyclass(extends=Exception)]
+struct CustomError {
+    #[pyo3(get)]
+    code: u32,
+}
+
+#[pymethods]
+impl CustomError {
+    #[new]
+    fn new(obj: &PyRawObject, code: u32) {
+        obj.init(CustomError { code })
+    }
+
+    fn describe(&self) -> String {
+        format!("error {}", self.code)
+    }
+}
+
+fn fail_with_code(py: Python, code: u32) -> PyResult<()> {
+    let err: PyObject = Py::new(py, CustomError { code })?.into();
+    Err(PyErr::from_instance(&err.as_ref(py)))
+}
+
+#[test]
+fn inherit_exception() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let cls = py.get_type::<CustomError>();
+    py_run!(
+        py,
+        cls,
+        r#"
+try:
+    raise cls(3)
+except Exception as e:
+    assert isinstance(e, cls)
+    assert e.code == 3
+    assert e.args == (3,)
+    assert str(e) == '3'
+    assert e.describe() == 'error 3'
+else:
+    assert False
+
+import sys
+if sys.version_info >= (3, 11):
+    e = cls(4)
+    e.add_note('note')
+    assert e.__notes__ == ['note']
+"#
+    );
+}
+
+#[test]
+fn raise_custom_exception() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let err = fail_with_code(py, 7).unwrap_err();
+    assert!(err.is_instance::<CustomError>(py));
+    assert!(err.is_instance::<Exception>(py));
+
+    err.restore(py);
+    let value = PyErr::fetch(py).to_object(py);
+    let value: &CustomError = value.extract(py).unwrap();
+    assert_eq!(value.code, 7);
+}
@@ -0,0 +1,34 @@
+use pyo3::exceptions::RuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::InterpreterConfig;
+
+// The interpreter is shared by the process, so this file has a single test
+
+#[test]
+fn initialize_with_config() {
+    let extra_path = std::env::temp_dir().join("pyo3-interpreter-config");
+    InterpreterConfig::new()
+        .program_name("pyo3-test")
+        .argv(vec!["pyo3-test", "-c", "--flag"])
+        .path(&extra_path)
+        .site_import(false)
+        .initialize()
+        .unwrap();
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let sys = py.import("sys").unwrap();
+
+    let argv: Vec<String> = sys.get("argv").unwrap().extract().unwrap();
+    assert_eq!(argv, vec!["pyo3-test", "-c", "--flag"]);
+
+    let path: Vec<String> = sys.get("path").unwrap().extract().unwrap();
+    assert_eq!(path.last().unwrap(), extra_path.to_str().unwrap());
+
+    let modules: &PyDict = sys.get("modules").unwrap().downcast_ref().unwrap();
+    assert!(modules.get_item("site").is_none());
+
+    let err = InterpreterConfig::new().initialize().unwrap_err();
+    assert!(err.is_instance::<RuntimeError>(py));
+}
@@ -0,0 +1,125 @@
+use pyo3::prelude::*;
+
+mod common;
+
+#[derive(IntoPyObject)]
+struct Settings {
+    name: String,
+    #[pyo3(rename = "max-size")]
+    max_size: usize,
+    #[pyo3(skip)]
+    #[allow(dead_code)]
+    cache: Vec<u8>,
+}
+
+#[test]
+fn test_named_fields_to_dict() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let settings = Settings {
+        name: "cache".to_string(),
+        max_size: 16,
+        cache: vec![1, 2, 3],
+    };
+    let obj = settings.to_object(py);
+    py_assert!(py, obj, "obj == {'name': 'cache', 'max-size': 16}");
+    let obj: PyObject = settings.into_py(py);
+    py_assert!(py, obj, "obj == {'name': 'cache', 'max-size': 16}");
+}
+
+#[derive(IntoPyObject)]
+#[pyo3(tuple)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(IntoPyObject)]
+struct Rgb(u8, u8, u8);
+
+#[derive(IntoPyObject)]
+struct Meters(f64);
+
+#[derive(IntoPyObject)]
+struct Empty;
+
+#[test]
+fn test_tuples_newtypes_and_unit() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let point = Point { x: 1, y: 2 }.to_object(py);
+    py_assert!(py, point, "point == (1, 2)");
+    let rgb = Rgb(1, 2, 3).to_object(py);
+    py_assert!(py, rgb, "rgb == (1, 2, 3)");
+    let meters = Meters(1.5).to_object(py);
+    py_assert!(py, meters, "meters == 1.5");
+    let empty = Empty.to_object(py);
+    py_assert!(py, empty, "empty is None");
+}
+
+#[derive(IntoPyObject)]
+struct Generic<T> {
+    value: T,
+}
+
+#[test]
+fn test_generics() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let generic = Generic {
+        value: vec!["a", "b"],
+    }
+    .to_object(py);
+    py_assert!(py, generic, "generic == {'value': ['a', 'b']}");
+}
+
+#[derive(IntoPyObject)]
+enum Event {
+    Stop,
+    #[pyo3(rename = "move")]
+    Move {
+        x: i32,
+        y: i32,
+    },
+    Key(String),
+    Resize(u32, u32),
+}
+
+#[test]
+fn test_enum_is_tagged() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let stop = Event::Stop.to_object(py);
+    py_assert!(py, stop, "stop == 'Stop'");
+    let mv = Event::Move { x: 1, y: 2 }.to_object(py);
+    py_assert!(py, mv, "mv == {'move': {'x': 1, 'y': 2}}");
+    let key = Event::Key("q".to_string()).into_py(py);
+    py_assert!(py, key, "key == {'Key': 'q'}");
+    let resize = Event::Resize(640, 480).into_py(py);
+    py_assert!(py, resize, "resize == {'Resize': (640, 480)}");
+}
+
+#[pyclass]
+#[derive(Clone, IntoPyObject)]
+#[pyo3(pyclass)]
+struct Counter {
+    #[pyo3(get)]
+    count: u32,
+}
+
+#[test]
+fn test_pyclass_to_object() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let counter = Counter { count: 5 };
+    let obj = counter.to_object(py);
+    py_assert!(py, obj, "obj.count == 5");
+    py_assert!(py, obj, "type(obj).__name__ == 'Counter'");
+    let obj: &Counter = obj.extract(py).unwrap();
+    assert_eq!(obj.count, 5);
+}
@@ -1,4 +1,7 @@
+use pyo3::exceptions::{RuntimeError, ValueError};
 use pyo3::prelude::*;
+use pyo3::py_run;
+use std::cell::Cell;
 
 use pyo3::types::{IntoPyDict, PyTuple};
 
@@ -56,7 +59,7 @@ fn test_module_with_functions() {
 
     let d = [(
         "module_with_functions",
-        wrap_pymodule!(module_with_functions)(py),
+        wrap_pymodule!(module_with_functions)(py).unwrap(),
     )]
     .into_py_dict(py);
 
@@ -87,7 +90,7 @@ fn test_module_renaming() {
     let gil = Python::acquire_gil();
     let py = gil.python();
 
-    let d = [("different_name", wrap_pymodule!(other_name)(py))].into_py_dict(py);
+    let d = [("different_name", wrap_pymodule!(other_name)(py).unwrap())].into_py_dict(py);
 
     py.run(
         "assert different_name.__name__ == 'other_name'",
@@ -143,7 +146,7 @@ fn test_raw_idents() {
     let gil = Python::acquire_gil();
     let py = gil.python();
 
-    let module = wrap_pymodule!(raw_ident_module)(py);
+    let module = wrap_pymodule!(raw_ident_module)(py).unwrap();
 
     py_assert!(py, module, "module.move() == 42");
 }
@@ -181,7 +184,7 @@ fn test_module_nesting() {
 
     let gil = GILGuard::acquire();
     let py = gil.python();
-    let supermodule = wrap_pymodule!(supermodule)(py);
+    let supermodule = wrap_pymodule!(supermodule)(py).unwrap();
 
     py_assert!(
         py,
@@ -218,7 +221,7 @@ fn vararg_module(_py: Python, m: &PyModule) -> PyResult<()> {
 fn test_vararg_module() {
     let gil = Python::acquire_gil();
     let py = gil.python();
-    let m = pyo3::wrap_pymodule!(vararg_module)(py);
+    let m = pyo3::wrap_pymodule!(vararg_module)(py).unwrap();
 
     py_assert!(py, m, "m.ext_vararg_fn() == [5, ()]");
     py_assert!(py, m, "m.ext_vararg_fn(1, 2) == [1, (2,)]");
@@ -226,3 +229,79 @@ fn test_vararg_module() {
     py_assert!(py, m, "m.int_vararg_fn() == [5, ()]");
     py_assert!(py, m, "m.int_vararg_fn(1, 2) == [1, (2,)]");
 }
+
+/// A module with multi-phase initialization
+#[pymodule(multi_phase)]
+fn multi_phase_module(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_wrapped(pyo3::wrap_pyfunction!(double))?;
+    m.set_state(Cell::new(0u32))?;
+    Ok(())
+}
+
+#[test]
+fn test_multi_phase_module() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m1 = pyo3::wrap_pymodule!(multi_phase_module)(py).unwrap();
+    let m2 = pyo3::wrap_pymodule!(multi_phase_module)(py).unwrap();
+
+    py_assert!(py, m1, "m1.double(3) == 6");
+    py_assert!(py, m1, "m1.__name__ == 'multi_phase_module'");
+    py_assert!(
+        py,
+        m1,
+        "m1.__doc__ == 'A module with multi-phase initialization'"
+    );
+    py_run!(py, m1 m2, "assert m1 is not m2");
+
+    // every instance of the module has its own state
+    let m1: &PyModule = m1.cast_as(py).unwrap();
+    let m2: &PyModule = m2.cast_as(py).unwrap();
+    m1.state::<Cell<u32>>().unwrap().set(5);
+    assert_eq!(m1.state::<Cell<u32>>().unwrap().get(), 5);
+    assert_eq!(m2.state::<Cell<u32>>().unwrap().get(), 0);
+
+    assert!(m1.state::<String>().is_none());
+    let err = m1.set_state(1).unwrap_err();
+    assert!(err.is_instance::<RuntimeError>(py));
+}
+
+#[test]
+fn test_single_phase_module_state() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m = pyo3::wrap_pymodule!(vararg_module)(py).unwrap();
+    let m: &PyModule = m.cast_as(py).unwrap();
+
+    assert!(m.state::<u32>().is_none());
+    let err = m.set_state(1u32).unwrap_err();
+    assert!(err.is_instance::<RuntimeError>(py));
+    assert!(py.import("math").unwrap().state::<u32>().is_none());
+}
+
+#[pymodule]
+fn failing_module(_py: Python, _m: &PyModule) -> PyResult<()> {
+    Err(ValueError::py_err("failed to initialize"))
+}
+
+#[pymodule(multi_phase)]
+fn failing_multi_phase_module(_py: Python, _m: &PyModule) -> PyResult<()> {
+    Err(ValueError::py_err("failed to initialize"))
+}
+
+#[test]
+fn test_module_init_error() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let err = pyo3::wrap_pymodule!(failing_module)(py).unwrap_err();
+    assert!(err.is_instance::<ValueError>(py));
+    let err = pyo3::wrap_pymodule!(failing_multi_phase_module)(py).unwrap_err();
+    assert!(err.is_instance::<ValueError>(py));
+
+    let m = PyModule::new(py, "parent").unwrap();
+    let err = m
+        .add_wrapped(pyo3::wrap_pymodule!(failing_multi_phase_module))
+        .unwrap_err();
+    assert!(err.is_instance::<ValueError>(py));
+}
@@ -0,0 +1,108 @@
+use pyo3::class::PySequenceProtocol;
+use pyo3::exceptions::PanicException;
+use pyo3::prelude::*;
+use pyo3::py_run;
+use pyo3::wrap_pyfunction;
+
+mod common;
+
+#[pyfunction]
+fn fail(message: String) -> usize {
+    panic!("{}", message)
+}
+
+#[pyfunction]
+fn fail_static() {
+    panic!("static message")
+}
+
+#[test]
+fn panic_in_function() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let fail = wrap_pyfunction!(fail)(py);
+    let fail_static = wrap_pyfunction!(fail_static)(py);
+    py_expect_exception!(py, fail, "fail('oops')", PanicException);
+    py_expect_exception!(py, fail_static, "fail_static()", PanicException);
+
+    let err = fail.call1(py, ("oops",)).unwrap_err();
+    assert!(err.is_instance::<PanicException>(py));
+    py_assert!(py, err, "err.args == ('oops',)");
+}
+
+#[test]
+fn panic_is_not_an_exception() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let fail = wrap_pyfunction!(fail)(py);
+    let panic_exception = py.get_type::<PanicException>();
+    py_run!(
+        py,
+        fail panic_exception,
+        r#"
+assert not issubclass(panic_exception, Exception)
+assert issubclass(panic_exception, BaseException)
+try:
+    try:
+        fail("inner")
+    except Exception:
+        assert False, "PanicException was caught as an Exception"
+except panic_exception as e:
+    assert e.args == ("inner",)
+"#
+    );
+}
+
+#[pyclass]
+struct Failing {
+    count: u32,
+}
+
+#[pymethods]
+impl Failing {
+    fn method(&mut self) {
+        self.count += 1;
+        panic!("method")
+    }
+
+    #[getter]
+    fn value(&self) -> u32 {
+        panic!("getter")
+    }
+
+    #[staticmethod]
+    fn static_method() {
+        panic!("static method")
+    }
+
+    fn __repr__(&self) -> String {
+        panic!("repr")
+    }
+}
+
+#[pyproto]
+impl PySequenceProtocol for Failing {
+    fn __len__(&self) -> PyResult<usize> {
+        panic!("len")
+    }
+}
+
+#[test]
+fn panic_in_methods() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let obj = Py::new(py, Failing { count: 0 }).unwrap();
+    py_expect_exception!(py, obj, "obj.method()", PanicException);
+    py_expect_exception!(py, obj, "obj.value", PanicException);
+    py_expect_exception!(py, obj, "obj.static_method()", PanicException);
+    py_expect_exception!(py, obj, "repr(obj)", PanicException);
+    py_expect_exception!(py, obj, "len(obj)", PanicException);
+
+    // the borrow taken by the method is released when it panics
+    assert_eq!(obj.as_ref(py).count, 1);
+    py_expect_exception!(py, obj, "obj.method()", PanicException);
+    assert_eq!(obj.as_ref(py).count, 2);
+}
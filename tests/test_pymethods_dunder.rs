@@ -0,0 +1,377 @@
+use pyo3::class::basic::CompareOp;
+use pyo3::exceptions::{IndexError, KeyError};
+use pyo3::prelude::*;
+use pyo3::{py_run, AsPyPointer};
+
+mod common;
+
+#[pyclass]
+struct Number {
+    value: i64,
+}
+
+#[pymethods]
+impl Number {
+    #[new]
+    fn new(obj: &PyRawObject, value: i64) {
+        obj.init(Number { value })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Number({})", self.value)
+    }
+
+    fn __str__(&self) -> String {
+        self.value.to_string()
+    }
+
+    fn __hash__(&self) -> i64 {
+        self.value
+    }
+
+    fn __bool__(&self) -> bool {
+        self.value != 0
+    }
+
+    fn __int__(&self) -> i64 {
+        self.value
+    }
+
+    fn __neg__(&self) -> Number {
+        Number { value: -self.value }
+    }
+
+    fn __add__(&self, other: i64) -> Number {
+        Number {
+            value: self.value + other,
+        }
+    }
+
+    fn __radd__(&self, other: i64) -> Number {
+        Number {
+            value: other + self.value,
+        }
+    }
+
+    fn __sub__(&self, other: &Number) -> Number {
+        Number {
+            value: self.value - other.value,
+        }
+    }
+
+    fn __rsub__(&self, other: i64) -> Number {
+        Number {
+            value: other - self.value,
+        }
+    }
+
+    fn __pow__(&self, exponent: u32, modulo: Option<i64>) -> i64 {
+        match modulo {
+            Some(modulo) => self.value.pow(exponent) % modulo,
+            None => self.value.pow(exponent),
+        }
+    }
+
+    fn __iadd__(&mut self, other: i64) {
+        self.value += other;
+    }
+
+    fn __eq__(&self, other: &Number) -> bool {
+        self.value == other.value
+    }
+
+    fn __lt__(&self, other: i64) -> bool {
+        self.value < other
+    }
+}
+
+#[test]
+fn object_slots() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let n = Py::new(py, Number { value: 5 }).unwrap();
+    py_assert!(py, n, "repr(n) == 'Number(5)'");
+    py_assert!(py, n, "str(n) == '5'");
+    py_assert!(py, n, "hash(n) == 5");
+    py_assert!(py, n, "bool(n)");
+    py_assert!(py, n, "int(n) == 5");
+    py_assert!(py, n, "not Number(0)");
+}
+
+#[test]
+fn number_slots() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let n = Py::new(py, Number { value: 5 }).unwrap();
+    py_assert!(py, n, "repr(-n) == 'Number(-5)'");
+    py_assert!(py, n, "repr(n + 1) == 'Number(6)'");
+    py_assert!(py, n, "repr(1 + n) == 'Number(6)'");
+    py_assert!(py, n, "repr(n - Number(2)) == 'Number(3)'");
+    py_assert!(py, n, "repr(10 - n) == 'Number(5)'");
+    py_assert!(py, n, "n ** 2 == 25");
+    py_assert!(py, n, "pow(n, 2, 7) == 4");
+    py_run!(
+        py,
+        n,
+        "m = n; m += 3; assert m is n; assert repr(n) == 'Number(8)'"
+    );
+    py_expect_exception!(py, n, "n + 'a'", TypeError);
+    py_expect_exception!(py, n, "n - 1", TypeError);
+}
+
+#[test]
+fn comparison_slots() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let n = Py::new(py, Number { value: 5 }).unwrap();
+    py_assert!(py, n, "n == Number(5)");
+    py_assert!(py, n, "n != Number(6)");
+    py_assert!(py, n, "n < 6");
+    py_assert!(py, n, "not n < 5");
+    py_assert!(py, n, "n != 5");
+    py_expect_exception!(py, n, "n > 6", TypeError);
+}
+
+#[pyclass]
+struct Ordered {
+    value: i64,
+}
+
+#[pymethods]
+impl Ordered {
+    fn __richcmp__(&self, other: i64, op: CompareOp) -> bool {
+        match op {
+            CompareOp::Lt => self.value < other,
+            CompareOp::Le => self.value <= other,
+            CompareOp::Eq => self.value == other,
+            CompareOp::Ne => self.value != other,
+            CompareOp::Gt => self.value > other,
+            CompareOp::Ge => self.value >= other,
+        }
+    }
+}
+
+#[test]
+fn richcmp_slot() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let o = Py::new(py, Ordered { value: 5 }).unwrap();
+    py_assert!(py, o, "o == 5");
+    py_assert!(py, o, "o < 6 and o <= 5 and o > 4 and o >= 5 and o != 4");
+    py_assert!(py, o, "o != 'a'");
+}
+
+#[pyclass]
+struct Container {
+    items: Vec<i32>,
+}
+
+#[pymethods]
+impl Container {
+    fn __len__(&self) -> usize {
+        self.items.len()
+    }
+
+    fn __getitem__(&self, index: usize) -> PyResult<i32> {
+        self.items
+            .get(index)
+            .cloned()
+            .ok_or_else(|| IndexError::py_err("index out of range"))
+    }
+
+    fn __setitem__(&mut self, index: usize, value: i32) -> PyResult<()> {
+        match self.items.get_mut(index) {
+            Some(item) => {
+                *item = value;
+                Ok(())
+            }
+            None => Err(IndexError::py_err("index out of range")),
+        }
+    }
+
+    fn __contains__(&self, value: i32) -> bool {
+        self.items.contains(&value)
+    }
+
+    fn __iter__(slf: PyRef<Self>) -> ContainerIter {
+        ContainerIter {
+            items: slf.items.clone().into_iter(),
+        }
+    }
+}
+
+#[pyclass]
+struct ContainerIter {
+    items: std::vec::IntoIter<i32>,
+}
+
+#[pymethods]
+impl ContainerIter {
+    fn __iter__(slf: PyRefMut<Self>) -> PyRefMut<Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<i32> {
+        self.items.next()
+    }
+}
+
+#[test]
+fn container_slots() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(
+        py,
+        Container {
+            items: vec![1, 2, 3],
+        },
+    )
+    .unwrap();
+    py_assert!(py, c, "len(c) == 3");
+    py_assert!(py, c, "c[1] == 2");
+    py_assert!(py, c, "2 in c and 4 not in c");
+    py_assert!(py, c, "list(c) == [1, 2, 3]");
+    py_run!(py, c, "c[0] = 10; assert list(c) == [10, 2, 3]");
+    py_expect_exception!(py, c, "c[5]", IndexError);
+    py_expect_exception!(py, c, "del c[0]", NotImplementedError);
+}
+
+/// The `Vector` of the guide, which only has `__len__` and `__getitem__`
+#[pyclass]
+struct Vector {
+    items: Vec<f64>,
+}
+
+#[pymethods]
+impl Vector {
+    fn __len__(&self) -> usize {
+        self.items.len()
+    }
+
+    fn __getitem__(&self, index: usize) -> PyResult<f64> {
+        self.items
+            .get(index)
+            .cloned()
+            .ok_or_else(|| IndexError::py_err(index))
+    }
+
+    fn __pow__(&self, exponent: i32) -> Vector {
+        Vector {
+            items: self.items.iter().map(|x| x.powi(exponent)).collect(),
+        }
+    }
+
+    fn __ipow__(&mut self, exponent: i32) {
+        for x in self.items.iter_mut() {
+            *x = x.powi(exponent);
+        }
+    }
+}
+
+#[test]
+fn getitem_makes_a_sequence() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let v = Py::new(
+        py,
+        Vector {
+            items: vec![1.0, 2.0, 3.0],
+        },
+    )
+    .unwrap();
+    py_assert!(py, v, "list(v) == [1.0, 2.0, 3.0]");
+    py_assert!(py, v, "[x for x in v] == [1.0, 2.0, 3.0]");
+    py_assert!(py, v, "2.0 in v and 4.0 not in v");
+    py_assert!(py, v, "tuple(reversed(v)) == (3.0, 2.0, 1.0)");
+    py_assert!(py, v, "list(v ** 2) == [1.0, 4.0, 9.0]");
+    py_expect_exception!(py, v, "pow(v, 2, 5)", TypeError);
+}
+
+#[test]
+fn inplace_pow_rejects_modulo() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let v = Py::new(
+        py,
+        Vector {
+            items: vec![1.0, 2.0],
+        },
+    )
+    .unwrap();
+    py_run!(py, v, "v **= 2; assert list(v) == [1.0, 4.0]");
+
+    let two = 2.to_object(py);
+    let five = 5.to_object(py);
+    let result =
+        unsafe { pyo3::ffi::PyNumber_InPlacePower(v.as_ptr(), two.as_ptr(), five.as_ptr()) };
+    assert!(result.is_null());
+    assert!(PyErr::fetch(py).is_instance::<pyo3::exceptions::TypeError>(py));
+}
+
+#[pyclass(dict)]
+struct Attributes {}
+
+#[pymethods]
+impl Attributes {
+    #[getter]
+    fn real(&self) -> i32 {
+        1
+    }
+
+    fn __getattr__(&self, name: &str) -> PyResult<String> {
+        if name.starts_with("missing") {
+            Err(KeyError::py_err(name.to_string()))
+        } else {
+            Ok(name.to_uppercase())
+        }
+    }
+
+    fn __delattr__(&mut self, name: &str) -> PyResult<()> {
+        Err(KeyError::py_err(name.to_string()))
+    }
+}
+
+#[test]
+fn attribute_slots() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let a = Py::new(py, Attributes {}).unwrap();
+    py_assert!(py, a, "a.real == 1");
+    py_assert!(py, a, "a.virtual == 'VIRTUAL'");
+    py_run!(py, a, "a.stored = 2; assert a.stored == 2");
+    py_expect_exception!(py, a, "a.missing", KeyError);
+    py_expect_exception!(py, a, "del a.stored", KeyError);
+}
+
+#[pyclass]
+struct Counter {
+    count: u32,
+}
+
+#[pymethods]
+impl Counter {
+    fn __iadd__(mut slf: PyRefMut<Self>, other: u32) {
+        slf.count += other;
+    }
+
+    fn __len__(slf: PyRef<Self>) -> usize {
+        slf.count as usize
+    }
+}
+
+#[test]
+fn pyself_receiver() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = Py::new(py, Counter { count: 0 }).unwrap();
+    py_run!(py, c, "c += 2; c += 3; assert len(c) == 5");
+}
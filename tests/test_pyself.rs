@@ -108,11 +108,11 @@ fn test_clone_ref() {
 fn test_nested_iter_reset() {
     let gil = Python::acquire_gil();
     let py = gil.python();
-    let reader = PyRef::new(py, reader()).unwrap();
+    let reader = Py::new(py, reader()).unwrap();
     py_assert!(
         py,
         reader,
         "list(reader.get_iter_and_reset(bytes([3, 5, 2]))) == ['c', 'e', 'b']"
     );
-    assert!(reader.inner.is_empty());
+    assert!(reader.as_ref(py).inner.is_empty());
 }
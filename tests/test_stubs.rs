@@ -0,0 +1,162 @@
+use pyo3::class::basic::PyObjectProtocol;
+use pyo3::prelude::*;
+use pyo3::stubs::module_stub;
+use pyo3::types::PyModule;
+use pyo3::{wrap_pyfunction, wrap_pymodule, PathlibPath, PyRawObject};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[pyclass]
+struct Point {
+    #[pyo3(get, set)]
+    x: f64,
+    #[pyo3(get)]
+    label: String,
+}
+
+#[pymethods]
+impl Point {
+    #[classattr]
+    const DIMENSIONS: u32 = 1;
+
+    #[new]
+    fn new(obj: &PyRawObject, x: f64, label: Option<String>) {
+        obj.init(Point {
+            x,
+            label: label.unwrap_or_default(),
+        })
+    }
+
+    #[getter]
+    fn length(&self) -> f64 {
+        self.x.abs()
+    }
+
+    fn translate(&mut self, dx: f64) {
+        self.x += dx;
+    }
+
+    #[staticmethod]
+    fn origin() -> Point {
+        Point {
+            x: 0.0,
+            label: String::new(),
+        }
+    }
+}
+
+#[pyproto]
+impl PyObjectProtocol for Point {
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("Point({})", self.x))
+    }
+}
+
+#[pyfunction]
+fn distance(a: &Point, b: &Point) -> f64 {
+    (a.x - b.x).abs()
+}
+
+#[pyfunction(words = "*", minimum = 1)]
+fn count(words: &pyo3::types::PyTuple, minimum: usize) -> PyResult<HashMap<String, usize>> {
+    let mut counts = HashMap::new();
+    for word in words.iter() {
+        *counts.entry(word.extract()?).or_insert(0) += 1;
+    }
+    counts.retain(|_, count| *count >= minimum);
+    Ok(counts)
+}
+
+#[pyfunction]
+fn resolve(py: Python, path: PathBuf) -> PyResult<PathlibPath> {
+    PathlibPath::new(py, path)
+}
+
+#[pymodule]
+fn geometry(_py: Python, m: &PyModule) -> PyResult<()> {
+    #[pyfn(m, "midpoint")]
+    fn midpoint(a: &Point, b: &Point, label: Option<&str>) -> (f64, Option<String>) {
+        ((a.x + b.x) / 2.0, label.map(String::from))
+    }
+
+    m.add_class::<Point>()?;
+    m.add_wrapped(wrap_pyfunction!(distance))?;
+    m.add_wrapped(wrap_pyfunction!(count))?;
+    m.add_wrapped(wrap_pyfunction!(resolve))?;
+    m.add("VERSION", "1.0")?;
+    Ok(())
+}
+
+#[test]
+fn test_module_stub() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let module = wrap_pymodule!(geometry)(py).unwrap();
+    let module: &PyModule = module.cast_as(py).unwrap();
+    let stub = module_stub(module).unwrap();
+
+    assert!(stub.starts_with("# This file was generated by pyo3"));
+    assert!(stub.contains("\nimport pathlib\nfrom typing import "));
+    assert!(stub
+        .contains("class Point:\n    x: float\n    @property\n    def label(self) -> str: ...\n"));
+    assert!(stub.contains("    DIMENSIONS: ClassVar[int]\n"));
+    assert!(stub.contains("    @property\n    def length(self) -> float: ...\n"));
+    assert!(
+        stub.contains("    def __new__(cls, x: float, label: Optional[str] = ...) -> Point: ...\n")
+    );
+    assert!(stub.contains("    def translate(self, dx: float) -> None: ...\n"));
+    assert!(stub.contains("    @staticmethod\n    def origin() -> Point: ...\n"));
+    assert!(stub.contains("    def __repr__(self) -> str: ...\n"));
+    assert!(stub.contains(
+        "\ndef midpoint(a: Point, b: Point, label: Optional[str] = ...) \
+         -> Tuple[float, Optional[str]]: ...\n"
+    ));
+    assert!(stub.contains("\ndef distance(a: Point, b: Point) -> float: ...\n"));
+    assert!(stub.contains("\ndef count(*words, minimum: int = ...) -> Dict[str, int]: ...\n"));
+    assert!(stub.contains("\ndef resolve(path: str) -> pathlib.Path: ...\n"));
+    assert!(stub.contains("\nVERSION: str\n"));
+}
+
+mod text {
+    use super::*;
+
+    #[pyfunction]
+    fn convert(value: String) -> String {
+        value
+    }
+
+    pub fn add_functions(m: &PyModule) -> PyResult<()> {
+        m.add_wrapped(wrap_pyfunction!(convert))
+    }
+}
+
+mod number {
+    use super::*;
+
+    #[pyfunction]
+    fn convert(value: i64) -> i64 {
+        value
+    }
+
+    pub fn add_functions(m: &PyModule) -> PyResult<()> {
+        m.add_wrapped(wrap_pyfunction!(convert))
+    }
+}
+
+#[test]
+fn test_functions_with_the_same_name() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let module = PyModule::new(py, "text").unwrap();
+    text::add_functions(module).unwrap();
+    let stub = module_stub(module).unwrap();
+    assert!(stub.contains("\ndef convert(value: str) -> str: ...\n"));
+    assert!(!stub.contains("import pathlib"));
+
+    let module = PyModule::new(py, "number").unwrap();
+    number::add_functions(module).unwrap();
+    let stub = module_stub(module).unwrap();
+    assert!(stub.contains("\ndef convert(value: int) -> int: ...\n"));
+}
@@ -0,0 +1,158 @@
+use pyo3::exceptions::ImportError;
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+use pyo3::{wrap_pyfunction, wrap_pymodule, SubInterpreter};
+use std::cell::Cell;
+
+mod common;
+
+#[test]
+fn sub_interpreter_is_isolated() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    py.run("import sys; sys.in_main = True", None, None)
+        .unwrap();
+
+    let sub = SubInterpreter::new(py).unwrap();
+    unsafe {
+        sub.run(py, |py| {
+            py.run(
+                "import sys; assert not hasattr(sys, 'in_main'); sys.in_sub = True",
+                None,
+                None,
+            )
+        })
+        .unwrap();
+        sub.run(py, |py| py.run("import sys; assert sys.in_sub", None, None))
+            .unwrap();
+    }
+
+    py.run(
+        "import sys; assert sys.in_main and not hasattr(sys, 'in_sub')",
+        None,
+        None,
+    )
+    .unwrap();
+}
+
+#[test]
+fn acquire_gil_in_sub_interpreter() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let sub = SubInterpreter::new(py).unwrap();
+
+    unsafe {
+        sub.run(py, |py| {
+            py.run("import sys; sys.marker = 1", None, None).unwrap();
+            let gil = Python::acquire_gil();
+            gil.python()
+                .run("import sys; assert sys.marker == 1", None, None)
+                .unwrap();
+        });
+    }
+}
+
+#[test]
+fn panic_in_sub_interpreter_switches_back() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    py.run("import sys; sys.in_main = True", None, None)
+        .unwrap();
+
+    let sub = SubInterpreter::new(py).unwrap();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+        sub.run(py, |_py| panic!("inside the sub-interpreter"))
+    }));
+    assert!(result.is_err());
+
+    py.run("import sys; assert sys.in_main", None, None)
+        .unwrap();
+}
+
+#[pyfunction]
+fn triple(x: usize) -> usize {
+    x * 3
+}
+
+#[pymodule(multi_phase)]
+fn per_interpreter(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_wrapped(wrap_pyfunction!(triple))?;
+    m.set_state(Cell::new(0u32))?;
+    Ok(())
+}
+
+#[test]
+fn multi_phase_module_in_sub_interpreter() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let main_module = wrap_pymodule!(per_interpreter)(py).unwrap();
+    let main_module: &PyModule = main_module.cast_as(py).unwrap();
+    main_module.state::<Cell<u32>>().unwrap().set(1);
+
+    let sub = SubInterpreter::new(py).unwrap();
+    unsafe {
+        sub.run(py, |py| {
+            let m = wrap_pymodule!(per_interpreter)(py).unwrap();
+            py_assert!(py, m, "m.triple(2) == 6");
+            let m: &PyModule = m.cast_as(py).unwrap();
+            assert_eq!(m.state::<Cell<u32>>().unwrap().get(), 0);
+        });
+    }
+}
+
+#[pyclass]
+struct Shared {}
+
+#[pymodule]
+fn single_phase(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_wrapped(wrap_pyfunction!(triple))
+}
+
+#[test]
+fn single_phase_module_in_two_interpreters() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m = wrap_pymodule!(single_phase)(py).unwrap();
+    py_assert!(py, m, "m.triple(2) == 6");
+
+    let sub = SubInterpreter::new(py).unwrap();
+    unsafe {
+        sub.run(py, |py| {
+            let module = PyInit_single_phase();
+            assert!(module.is_null());
+            assert!(PyErr::fetch(py).is_instance::<ImportError>(py));
+        });
+    }
+}
+
+#[test]
+fn class_in_two_interpreters() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m = PyModule::new(py, "classes").unwrap();
+    m.add_class::<Shared>().unwrap();
+
+    let sub = SubInterpreter::new(py).unwrap();
+    unsafe {
+        sub.run(py, |py| {
+            let m = PyModule::new(py, "classes").unwrap();
+            let err = m.add_class::<Shared>().unwrap_err();
+            assert!(err.is_instance::<ImportError>(py));
+        });
+    }
+}
+
+#[test]
+fn with_gil_in_sub_interpreter() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let sub = SubInterpreter::new(py).unwrap();
+
+    unsafe {
+        sub.run(py, |py| {
+            py.run("import sys; sys.marker = 2", None, None).unwrap();
+            Python::with_gil(|py| py.run("import sys; assert sys.marker == 2", None, None))
+                .unwrap();
+        });
+    }
+}
@@ -0,0 +1,121 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple, PyType};
+use pyo3::wrap_pyfunction;
+
+mod common;
+
+#[pyfunction(b = 5, args = "*", c = 10, kwargs = "**")]
+fn positional_and_keywords(
+    a: i32,
+    b: i32,
+    d: Option<i32>,
+    args: &PyTuple,
+    c: i32,
+    kwargs: Option<&PyDict>,
+) -> i32 {
+    let _ = (d, args, kwargs);
+    a + b + c
+}
+
+#[pyfunction(a = "\"text\"", b = "-1.5", c = "true")]
+fn defaults(a: &str, b: f64, c: bool) -> String {
+    format!("{} {} {}", a, b, c)
+}
+
+#[pyfunction]
+#[text_signature = "(x, /)"]
+fn overridden(x: i32) -> i32 {
+    x
+}
+
+#[pyfunction]
+#[text_signature = false]
+fn disabled(x: i32) -> i32 {
+    x
+}
+
+#[test]
+fn test_function_signature() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let f = wrap_pyfunction!(positional_and_keywords)(py);
+    py_assert!(
+        py,
+        f,
+        "f.__text_signature__ == '(a, b=5, d=None, *args, c=10, **kwargs)'"
+    );
+    py_assert!(py, f, "f.__doc__ is None or '--' not in f.__doc__");
+
+    let f = wrap_pyfunction!(defaults)(py);
+    py_assert!(py, f, "f.__text_signature__ == '(a=..., b=-1.5, c=True)'");
+
+    let f = wrap_pyfunction!(overridden)(py);
+    py_assert!(py, f, "f.__text_signature__ == '(x, /)'");
+
+    let f = wrap_pyfunction!(disabled)(py);
+    py_assert!(py, f, "f.__text_signature__ is None");
+}
+
+#[pyclass]
+struct MyClass {}
+
+#[pymethods]
+impl MyClass {
+    /// Adds numbers
+    #[args(a, "*", b = 1)]
+    fn method(&self, a: i32, b: i32) -> i32 {
+        a + b
+    }
+
+    fn no_args(&self) {}
+
+    #[classmethod]
+    fn class_method(_cls: &PyType, a: i32) -> i32 {
+        a
+    }
+
+    #[staticmethod]
+    fn static_method(a: i32, py: Python) -> PyObject {
+        a.into_py(py)
+    }
+
+    #[text_signature = "(a, b, /)"]
+    fn overridden(&self, a: i32, b: i32) -> i32 {
+        a + b
+    }
+}
+
+#[test]
+fn test_method_signature() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let typeobj = py.get_type::<MyClass>();
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.method.__text_signature__ == '($self, a, *, b=1)'"
+    );
+    py_assert!(py, typeobj, "typeobj.method.__doc__ == 'Adds numbers'");
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.no_args.__text_signature__ == '($self)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.class_method.__text_signature__ == '($cls, a)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.static_method.__text_signature__ == '(a)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.overridden.__text_signature__ == '($self, a, b, /)'"
+    );
+}
@@ -0,0 +1,10 @@
+use pyo3::prelude::*;
+
+#[derive(IntoPyObject)]
+#[pyo3(transparent)]
+struct Wrapper {
+    a: i32,
+    b: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use pyo3::prelude::*;
+use pyo3::types::PyAny;
+
+fn main() {
+    let gil = Python::acquire_gil();
+    let outer = gil.python();
+    // the list would be released by the pool of the inner guard
+    let _list: &PyAny = Python::with_gil(|_| outer.eval("[]", None, None).unwrap());
+}
@@ -0,0 +1,11 @@
+use pyo3::prelude::*;
+
+fn main() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let list = py.eval("[1, 2, 3]", None, None).unwrap();
+    py.with_pool(|_| {
+        // `list.py()` would create objects that outlive the pool
+        list.len().unwrap()
+    });
+}